@@ -22,11 +22,14 @@ CRON_FILE="/etc/cron.d/piggy-pulse-generate-periods"
   [ -n "${PIGGY_PULSE_LOGGING__LEVEL:-}" ] && echo "PIGGY_PULSE_LOGGING__LEVEL=${PIGGY_PULSE_LOGGING__LEVEL}"
   [ -n "${PIGGY_PULSE_LOGGING__JSON_FORMAT:-}" ] && echo "PIGGY_PULSE_LOGGING__JSON_FORMAT=${PIGGY_PULSE_LOGGING__JSON_FORMAT}"
   echo "${CRON_SCHEDULE} root /app/cron generate-periods >> /proc/1/fd/1 2>> /proc/1/fd/2"
+  echo "${CRON_SCHEDULE} root /app/cron fund-allowances >> /proc/1/fd/1 2>> /proc/1/fd/2"
+  echo "${CRON_SCHEDULE} root /app/cron instantiate-overlays >> /proc/1/fd/1 2>> /proc/1/fd/2"
+  echo "${CRON_SCHEDULE} root /app/cron evaluate-alerts >> /proc/1/fd/1 2>> /proc/1/fd/2"
 } > "${CRON_FILE}"
 
 chmod 0644 "${CRON_FILE}"
 
 echo "Cron schedule: ${CRON_SCHEDULE}"
-echo "Cron job: /app/cron generate-periods"
+echo "Cron jobs: /app/cron generate-periods, /app/cron fund-allowances, /app/cron instantiate-overlays, /app/cron evaluate-alerts"
 
 exec cron -f
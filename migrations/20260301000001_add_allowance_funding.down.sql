@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS allowance_funding_run;
+DROP TABLE IF EXISTS allowance_funding_rule;
@@ -0,0 +1,39 @@
+-- Funding rules that top up allowance accounts from a source account
+CREATE TABLE IF NOT EXISTS allowance_funding_rule (
+    id                       UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id                  UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    allowance_account_id     UUID        NOT NULL UNIQUE REFERENCES account (id) ON DELETE CASCADE,
+    source_account_id        UUID        NOT NULL REFERENCES account (id) ON DELETE CASCADE,
+    category_id              UUID        NOT NULL REFERENCES category (id) ON DELETE CASCADE,
+    cadence                  TEXT        NOT NULL CHECK (cadence IN ('period', 'interval')),
+    interval_value           INTEGER     NULL CHECK (interval_value > 0),
+    interval_unit            TEXT        NULL CHECK (interval_unit IN ('days', 'weeks', 'months')),
+    starts_on                DATE        NOT NULL,
+    next_run_date            DATE        NULL,
+    skip_next_run            BOOLEAN     NOT NULL DEFAULT FALSE,
+    next_run_amount_override BIGINT      NULL CHECK (next_run_amount_override >= 0),
+    is_active                BOOLEAN     NOT NULL DEFAULT TRUE,
+    created_at               TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at               TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT allowance_funding_rule_distinct_accounts CHECK (allowance_account_id <> source_account_id),
+    CONSTRAINT allowance_funding_rule_interval_check CHECK (
+        cadence = 'period' OR (interval_value IS NOT NULL AND interval_unit IS NOT NULL AND next_run_date IS NOT NULL)
+    )
+);
+
+CREATE INDEX idx_allowance_funding_rule_user ON allowance_funding_rule (user_id, created_at DESC, id DESC);
+
+-- One row per scheduled run; the unique key makes the cron job idempotent
+CREATE TABLE IF NOT EXISTS allowance_funding_run (
+    id             UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    rule_id        UUID        NOT NULL REFERENCES allowance_funding_rule (id) ON DELETE CASCADE,
+    user_id        UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    scheduled_for  DATE        NOT NULL,
+    status         TEXT        NOT NULL CHECK (status IN ('funded', 'skipped')),
+    amount         BIGINT      NOT NULL DEFAULT 0,
+    transaction_id UUID        NULL REFERENCES transaction (id) ON DELETE SET NULL,
+    created_at     TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (rule_id, scheduled_for)
+);
+
+CREATE INDEX idx_allowance_funding_run_rule ON allowance_funding_run (rule_id, created_at DESC, id DESC);
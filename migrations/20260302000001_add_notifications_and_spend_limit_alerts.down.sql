@@ -0,0 +1,5 @@
+ALTER TABLE settings
+    DROP COLUMN IF EXISTS spend_limit_email_alerts,
+    DROP COLUMN IF EXISTS spend_limit_alert_thresholds;
+
+DROP TABLE IF EXISTS notification;
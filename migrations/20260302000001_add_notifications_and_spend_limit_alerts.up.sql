@@ -0,0 +1,21 @@
+-- In-app notifications; dedupe_key guarantees an alert is raised at most once
+CREATE TABLE IF NOT EXISTS notification (
+    id         UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    kind       TEXT        NOT NULL,
+    dedupe_key TEXT        NOT NULL,
+    title      TEXT        NOT NULL,
+    message    TEXT        NOT NULL,
+    metadata   JSONB       NOT NULL DEFAULT '{}'::jsonb,
+    is_read    BOOLEAN     NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, dedupe_key)
+);
+
+CREATE INDEX idx_notification_user_created ON notification (user_id, created_at DESC, id DESC);
+CREATE INDEX idx_notification_user_unread ON notification (user_id) WHERE is_read = FALSE;
+
+-- Spend limit alert thresholds in basis points of the account spend limit
+ALTER TABLE settings
+    ADD COLUMN IF NOT EXISTS spend_limit_alert_thresholds INTEGER[] NOT NULL DEFAULT '{8000,10000}',
+    ADD COLUMN IF NOT EXISTS spend_limit_email_alerts BOOLEAN NOT NULL DEFAULT TRUE;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS savings_goal_account;
+DROP TABLE IF EXISTS savings_goal;
@@ -0,0 +1,24 @@
+-- Savings goals with a target amount and optional deadline
+CREATE TABLE IF NOT EXISTS savings_goal (
+    id            UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id       UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name          TEXT        NOT NULL,
+    description   TEXT        NULL,
+    target_amount BIGINT      NOT NULL CHECK (target_amount > 0),
+    target_date   DATE        NULL,
+    tracking_mode TEXT        NOT NULL CHECK (tracking_mode IN ('balance', 'contributions')),
+    starts_on     DATE        NOT NULL,
+    created_at    TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at    TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_savings_goal_user ON savings_goal (user_id, created_at DESC, id DESC);
+
+-- Accounts whose balance or incoming transfers count towards a goal
+CREATE TABLE IF NOT EXISTS savings_goal_account (
+    goal_id    UUID NOT NULL REFERENCES savings_goal (id) ON DELETE CASCADE,
+    account_id UUID NOT NULL REFERENCES account (id) ON DELETE CASCADE,
+    PRIMARY KEY (goal_id, account_id)
+);
+
+CREATE INDEX idx_savings_goal_account_account ON savings_goal_account (account_id);
@@ -0,0 +1,3 @@
+ALTER TABLE budget_category
+DROP COLUMN IF EXISTS rollover_started_on,
+DROP COLUMN IF EXISTS rollover_enabled;
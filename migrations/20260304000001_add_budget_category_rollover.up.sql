@@ -0,0 +1,4 @@
+-- Envelope-style rollover: unspent (or overspent) budget carries into the next period
+ALTER TABLE budget_category
+ADD COLUMN rollover_enabled BOOLEAN NOT NULL DEFAULT FALSE,
+ADD COLUMN rollover_started_on DATE NULL;
@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS category_target_move;
+
+ALTER TABLE settings
+    DROP COLUMN IF EXISTS budgeting_mode;
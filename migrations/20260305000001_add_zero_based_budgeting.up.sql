@@ -0,0 +1,19 @@
+-- Budgeting mode: 'standard' targets or zero-based "ready to assign"
+ALTER TABLE settings
+    ADD COLUMN IF NOT EXISTS budgeting_mode TEXT NOT NULL DEFAULT 'standard'
+        CHECK (budgeting_mode IN ('standard', 'zero_based'));
+
+-- Audit trail of money moved between category targets within a period
+CREATE TABLE IF NOT EXISTS category_target_move (
+    id               UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id          UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    period_id        UUID        NOT NULL REFERENCES budget_period (id) ON DELETE CASCADE,
+    from_category_id UUID        NOT NULL REFERENCES category (id) ON DELETE CASCADE,
+    to_category_id   UUID        NOT NULL REFERENCES category (id) ON DELETE CASCADE,
+    amount           INTEGER     NOT NULL CHECK (amount > 0),
+    note             TEXT        NULL,
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    CONSTRAINT category_target_move_distinct_categories CHECK (from_category_id <> to_category_id)
+);
+
+CREATE INDEX idx_category_target_move_period ON category_target_move (user_id, period_id, created_at DESC, id DESC);
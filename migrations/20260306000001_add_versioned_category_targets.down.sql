@@ -0,0 +1,5 @@
+DROP FUNCTION IF EXISTS budget_category_target_for_period(UUID, UUID, DATE);
+DROP TRIGGER IF EXISTS budget_category_target_history_trigger ON budget_category;
+DROP FUNCTION IF EXISTS record_budget_category_target_history();
+DROP TABLE IF EXISTS budget_category_period_override;
+DROP TABLE IF EXISTS budget_category_target_history;
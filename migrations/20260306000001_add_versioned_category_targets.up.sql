@@ -0,0 +1,81 @@
+-- History of default category targets. A default applies from `effective_from` until the next entry,
+-- so changing a target never rewrites periods that were already closed.
+CREATE TABLE IF NOT EXISTS budget_category_target_history (
+    id                 UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    budget_category_id UUID        NOT NULL REFERENCES budget_category (id) ON DELETE CASCADE,
+    budgeted_value     INTEGER     NOT NULL,
+    effective_from     DATE        NOT NULL,
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (budget_category_id, effective_from)
+);
+
+-- Existing targets have always applied
+INSERT INTO budget_category_target_history (budget_category_id, budgeted_value, effective_from)
+SELECT id, budgeted_value, '-infinity'::date
+FROM budget_category;
+
+-- Per-period overrides of the default target
+CREATE TABLE IF NOT EXISTS budget_category_period_override (
+    id                 UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id            UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    budget_category_id UUID        NOT NULL REFERENCES budget_category (id) ON DELETE CASCADE,
+    period_id          UUID        NOT NULL REFERENCES budget_period (id) ON DELETE CASCADE,
+    budgeted_value     INTEGER     NOT NULL CHECK (budgeted_value >= 0),
+    created_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at         TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (budget_category_id, period_id)
+);
+
+CREATE INDEX idx_budget_category_period_override_period ON budget_category_period_override (period_id);
+
+-- A new default takes effect from the start of the earliest period that is still open (today if there is none)
+CREATE OR REPLACE FUNCTION record_budget_category_target_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.budgeted_value = NEW.budgeted_value THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO budget_category_target_history (budget_category_id, budgeted_value, effective_from)
+    VALUES (
+        NEW.id,
+        NEW.budgeted_value,
+        COALESCE(
+            (SELECT MIN(bp.start_date) FROM budget_period bp WHERE bp.user_id = NEW.user_id AND bp.end_date >= CURRENT_DATE),
+            CURRENT_DATE
+        )
+    )
+    ON CONFLICT (budget_category_id, effective_from)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value, created_at = now();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER budget_category_target_history_trigger
+AFTER INSERT OR UPDATE OF budgeted_value ON budget_category
+FOR EACH ROW EXECUTE FUNCTION record_budget_category_target_history();
+
+-- Target of a budget category in a period: the period override, otherwise the default in effect when the period started.
+-- NULL when the category had no target yet.
+CREATE OR REPLACE FUNCTION budget_category_target_for_period(p_budget_category_id UUID, p_period_id UUID, p_period_start DATE)
+RETURNS INTEGER AS $$
+    SELECT COALESCE(
+        (
+            SELECT o.budgeted_value
+            FROM budget_category_period_override o
+            WHERE o.budget_category_id = p_budget_category_id
+              AND o.period_id = p_period_id
+        ),
+        (
+            SELECT h.budgeted_value
+            FROM budget_category_target_history h
+            WHERE h.budget_category_id = p_budget_category_id
+              AND h.effective_from <= p_period_start
+            ORDER BY h.effective_from DESC
+            LIMIT 1
+        )
+    );
+$$ LANGUAGE sql STABLE;
+
+COMMENT ON FUNCTION budget_category_target_for_period(UUID, UUID, DATE) IS 'Resolves the versioned target of a budget category for a budget period';
@@ -0,0 +1,20 @@
+-- Named sets of category targets and exclusions that can be applied to budget periods
+CREATE TABLE IF NOT EXISTS budget_template (
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id     UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name        TEXT        NOT NULL,
+    description TEXT        NULL,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX idx_budget_template_user ON budget_template (user_id, created_at DESC, id DESC);
+
+CREATE TABLE IF NOT EXISTS budget_template_entry (
+    template_id    UUID    NOT NULL REFERENCES budget_template (id) ON DELETE CASCADE,
+    category_id    UUID    NOT NULL REFERENCES category (id) ON DELETE CASCADE,
+    budgeted_value INTEGER NOT NULL CHECK (budgeted_value >= 0),
+    is_excluded    BOOLEAN NOT NULL DEFAULT FALSE,
+    PRIMARY KEY (template_id, category_id)
+);
@@ -0,0 +1,59 @@
+CREATE OR REPLACE FUNCTION budget_category_target_for_period(p_budget_category_id UUID, p_period_id UUID, p_period_start DATE)
+RETURNS INTEGER AS $$
+    SELECT COALESCE(
+        (
+            SELECT o.budgeted_value
+            FROM budget_category_period_override o
+            WHERE o.budget_category_id = p_budget_category_id
+              AND o.period_id = p_period_id
+        ),
+        (
+            SELECT h.budgeted_value
+            FROM budget_category_target_history h
+            WHERE h.budget_category_id = p_budget_category_id
+              AND h.effective_from <= p_period_start
+            ORDER BY h.effective_from DESC
+            LIMIT 1
+        )
+    );
+$$ LANGUAGE sql STABLE;
+
+DROP FUNCTION IF EXISTS budget_period_income(UUID, TEXT);
+
+DROP TRIGGER IF EXISTS budget_category_target_history_trigger ON budget_category;
+
+CREATE OR REPLACE FUNCTION record_budget_category_target_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE' AND OLD.budgeted_value = NEW.budgeted_value THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO budget_category_target_history (budget_category_id, budgeted_value, effective_from)
+    VALUES (
+        NEW.id,
+        NEW.budgeted_value,
+        COALESCE(
+            (SELECT MIN(bp.start_date) FROM budget_period bp WHERE bp.user_id = NEW.user_id AND bp.end_date >= CURRENT_DATE),
+            CURRENT_DATE
+        )
+    )
+    ON CONFLICT (budget_category_id, effective_from)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value, created_at = now();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER budget_category_target_history_trigger
+AFTER INSERT OR UPDATE OF budgeted_value ON budget_category
+FOR EACH ROW EXECUTE FUNCTION record_budget_category_target_history();
+
+ALTER TABLE budget_category_target_history
+    DROP COLUMN IF EXISTS target_income_source,
+    DROP COLUMN IF EXISTS target_income_basis_points;
+
+ALTER TABLE budget_category
+    DROP CONSTRAINT IF EXISTS budget_category_income_target_complete,
+    DROP COLUMN IF EXISTS target_income_source,
+    DROP COLUMN IF EXISTS target_income_basis_points;
@@ -0,0 +1,98 @@
+-- A target can be a share of the period's income instead of a fixed amount.
+-- `target_income_basis_points` is the share (10000 = 100%) of either the income received in the period ('actual')
+-- or the targets of the Incoming categories ('expected').
+ALTER TABLE budget_category
+    ADD COLUMN target_income_basis_points INTEGER NULL CHECK (target_income_basis_points BETWEEN 0 AND 10000),
+    ADD COLUMN target_income_source       TEXT    NULL CHECK (target_income_source IN ('actual', 'expected')),
+    ADD CONSTRAINT budget_category_income_target_complete
+        CHECK ((target_income_basis_points IS NULL) = (target_income_source IS NULL));
+
+ALTER TABLE budget_category_target_history
+    ADD COLUMN target_income_basis_points INTEGER NULL,
+    ADD COLUMN target_income_source       TEXT    NULL;
+
+CREATE OR REPLACE FUNCTION record_budget_category_target_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND OLD.budgeted_value = NEW.budgeted_value
+        AND OLD.target_income_basis_points IS NOT DISTINCT FROM NEW.target_income_basis_points
+        AND OLD.target_income_source IS NOT DISTINCT FROM NEW.target_income_source THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO budget_category_target_history (budget_category_id, budgeted_value, target_income_basis_points, target_income_source, effective_from)
+    VALUES (
+        NEW.id,
+        NEW.budgeted_value,
+        NEW.target_income_basis_points,
+        NEW.target_income_source,
+        COALESCE(
+            (SELECT MIN(bp.start_date) FROM budget_period bp WHERE bp.user_id = NEW.user_id AND bp.end_date >= CURRENT_DATE),
+            CURRENT_DATE
+        )
+    )
+    ON CONFLICT (budget_category_id, effective_from)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value,
+                  target_income_basis_points = EXCLUDED.target_income_basis_points,
+                  target_income_source = EXCLUDED.target_income_source,
+                  created_at = now();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS budget_category_target_history_trigger ON budget_category;
+CREATE TRIGGER budget_category_target_history_trigger
+AFTER INSERT OR UPDATE OF budgeted_value, target_income_basis_points, target_income_source ON budget_category
+FOR EACH ROW EXECUTE FUNCTION record_budget_category_target_history();
+
+-- Income of a budget period: received from Incoming categories ('actual') or targeted for them ('expected').
+-- Incoming categories cannot use income-based targets themselves, so the two functions never recurse.
+CREATE OR REPLACE FUNCTION budget_period_income(p_period_id UUID, p_source TEXT)
+RETURNS BIGINT AS $$
+    SELECT CASE
+        WHEN p_source = 'actual' THEN (
+            SELECT COALESCE(SUM(t.amount), 0)::bigint
+            FROM budget_period bp
+            JOIN transaction t
+              ON t.user_id = bp.user_id
+             AND t.occurred_at >= bp.start_date
+             AND t.occurred_at <= bp.end_date
+            JOIN category c ON c.id = t.category_id
+            WHERE bp.id = p_period_id
+              AND c.category_type = 'Incoming'
+        )
+        ELSE (
+            SELECT COALESCE(SUM(budget_category_target_for_period(bc.id, bp.id, bp.start_date)), 0)::bigint
+            FROM budget_period bp
+            JOIN budget_category bc ON bc.user_id = bp.user_id AND bc.is_excluded = FALSE
+            JOIN category c ON c.id = bc.category_id
+            WHERE bp.id = p_period_id
+              AND c.category_type = 'Incoming'
+        )
+    END;
+$$ LANGUAGE sql STABLE;
+
+CREATE OR REPLACE FUNCTION budget_category_target_for_period(p_budget_category_id UUID, p_period_id UUID, p_period_start DATE)
+RETURNS INTEGER AS $$
+    SELECT COALESCE(
+        (
+            SELECT o.budgeted_value
+            FROM budget_category_period_override o
+            WHERE o.budget_category_id = p_budget_category_id
+              AND o.period_id = p_period_id
+        ),
+        (
+            SELECT CASE
+                WHEN h.target_income_basis_points IS NULL THEN h.budgeted_value
+                ELSE ROUND(budget_period_income(p_period_id, h.target_income_source) * h.target_income_basis_points / 10000.0)::integer
+            END
+            FROM budget_category_target_history h
+            WHERE h.budget_category_id = p_budget_category_id
+              AND h.effective_from <= p_period_start
+            ORDER BY h.effective_from DESC
+            LIMIT 1
+        )
+    );
+$$ LANGUAGE sql STABLE;
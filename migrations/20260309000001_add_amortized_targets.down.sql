@@ -0,0 +1,74 @@
+DROP FUNCTION IF EXISTS budget_category_accrual_for_period(UUID, UUID);
+
+CREATE OR REPLACE FUNCTION budget_category_target_for_period(p_budget_category_id UUID, p_period_id UUID, p_period_start DATE)
+RETURNS INTEGER AS $$
+    SELECT COALESCE(
+        (
+            SELECT o.budgeted_value
+            FROM budget_category_period_override o
+            WHERE o.budget_category_id = p_budget_category_id
+              AND o.period_id = p_period_id
+        ),
+        (
+            SELECT CASE
+                WHEN h.target_income_basis_points IS NULL THEN h.budgeted_value
+                ELSE ROUND(budget_period_income(p_period_id, h.target_income_source) * h.target_income_basis_points / 10000.0)::integer
+            END
+            FROM budget_category_target_history h
+            WHERE h.budget_category_id = p_budget_category_id
+              AND h.effective_from <= p_period_start
+            ORDER BY h.effective_from DESC
+            LIMIT 1
+        )
+    );
+$$ LANGUAGE sql STABLE;
+
+DROP FUNCTION IF EXISTS budget_horizon_period_accrual(INTEGER, INTEGER, INTEGER, DATE, DATE);
+DROP FUNCTION IF EXISTS budget_horizon_accrued(INTEGER, INTEGER, INTEGER, DATE);
+DROP FUNCTION IF EXISTS budget_horizon_cycle_start(INTEGER, INTEGER, DATE);
+
+CREATE OR REPLACE FUNCTION record_budget_category_target_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND OLD.budgeted_value = NEW.budgeted_value
+        AND OLD.target_income_basis_points IS NOT DISTINCT FROM NEW.target_income_basis_points
+        AND OLD.target_income_source IS NOT DISTINCT FROM NEW.target_income_source THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO budget_category_target_history (budget_category_id, budgeted_value, target_income_basis_points, target_income_source, effective_from)
+    VALUES (
+        NEW.id,
+        NEW.budgeted_value,
+        NEW.target_income_basis_points,
+        NEW.target_income_source,
+        COALESCE(
+            (SELECT MIN(bp.start_date) FROM budget_period bp WHERE bp.user_id = NEW.user_id AND bp.end_date >= CURRENT_DATE),
+            CURRENT_DATE
+        )
+    )
+    ON CONFLICT (budget_category_id, effective_from)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value,
+                  target_income_basis_points = EXCLUDED.target_income_basis_points,
+                  target_income_source = EXCLUDED.target_income_source,
+                  created_at = now();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS budget_category_target_history_trigger ON budget_category;
+CREATE TRIGGER budget_category_target_history_trigger
+AFTER INSERT OR UPDATE OF budgeted_value, target_income_basis_points, target_income_source ON budget_category
+FOR EACH ROW EXECUTE FUNCTION record_budget_category_target_history();
+
+ALTER TABLE budget_category_target_history
+    DROP COLUMN IF EXISTS target_due_month,
+    DROP COLUMN IF EXISTS target_horizon_months;
+
+ALTER TABLE budget_category
+    DROP CONSTRAINT IF EXISTS budget_category_single_target_kind,
+    DROP CONSTRAINT IF EXISTS budget_category_horizon_target_complete,
+    DROP COLUMN IF EXISTS target_due_month,
+    DROP COLUMN IF EXISTS target_horizon_months;
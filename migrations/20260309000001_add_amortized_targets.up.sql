@@ -0,0 +1,161 @@
+-- Targets with a longer horizon than one period, e.g. a yearly amount due in March.
+-- `budgeted_value` holds the amount for the whole horizon; each period is assigned its accrual.
+ALTER TABLE budget_category
+    ADD COLUMN target_horizon_months INTEGER NULL CHECK (target_horizon_months IN (2, 3, 4, 6, 12)),
+    ADD COLUMN target_due_month      INTEGER NULL CHECK (target_due_month BETWEEN 1 AND 12),
+    ADD CONSTRAINT budget_category_horizon_target_complete
+        CHECK ((target_horizon_months IS NULL) = (target_due_month IS NULL)),
+    ADD CONSTRAINT budget_category_single_target_kind
+        CHECK (target_horizon_months IS NULL OR target_income_basis_points IS NULL);
+
+ALTER TABLE budget_category_target_history
+    ADD COLUMN target_horizon_months INTEGER NULL,
+    ADD COLUMN target_due_month      INTEGER NULL;
+
+CREATE OR REPLACE FUNCTION record_budget_category_target_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND OLD.budgeted_value = NEW.budgeted_value
+        AND OLD.target_income_basis_points IS NOT DISTINCT FROM NEW.target_income_basis_points
+        AND OLD.target_income_source IS NOT DISTINCT FROM NEW.target_income_source
+        AND OLD.target_horizon_months IS NOT DISTINCT FROM NEW.target_horizon_months
+        AND OLD.target_due_month IS NOT DISTINCT FROM NEW.target_due_month THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO budget_category_target_history (
+        budget_category_id, budgeted_value, target_income_basis_points, target_income_source,
+        target_horizon_months, target_due_month, effective_from
+    )
+    VALUES (
+        NEW.id,
+        NEW.budgeted_value,
+        NEW.target_income_basis_points,
+        NEW.target_income_source,
+        NEW.target_horizon_months,
+        NEW.target_due_month,
+        COALESCE(
+            (SELECT MIN(bp.start_date) FROM budget_period bp WHERE bp.user_id = NEW.user_id AND bp.end_date >= CURRENT_DATE),
+            CURRENT_DATE
+        )
+    )
+    ON CONFLICT (budget_category_id, effective_from)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value,
+                  target_income_basis_points = EXCLUDED.target_income_basis_points,
+                  target_income_source = EXCLUDED.target_income_source,
+                  target_horizon_months = EXCLUDED.target_horizon_months,
+                  target_due_month = EXCLUDED.target_due_month,
+                  created_at = now();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS budget_category_target_history_trigger ON budget_category;
+CREATE TRIGGER budget_category_target_history_trigger
+AFTER INSERT OR UPDATE OF budgeted_value, target_income_basis_points, target_income_source, target_horizon_months, target_due_month
+ON budget_category
+FOR EACH ROW EXECUTE FUNCTION record_budget_category_target_history();
+
+-- First day of the horizon cycle containing p_date. Cycles are p_months long and end with the due month.
+CREATE OR REPLACE FUNCTION budget_horizon_cycle_start(p_months INTEGER, p_due_month INTEGER, p_date DATE)
+RETURNS DATE AS $$
+    SELECT (
+        date_trunc('month', p_date::timestamp)
+        - make_interval(months => ((EXTRACT(MONTH FROM p_date)::integer - p_due_month - 1) % p_months + p_months) % p_months)
+    )::date;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Part of p_amount accrued from the start of the cycle containing p_date through p_date, spread evenly over its days
+CREATE OR REPLACE FUNCTION budget_horizon_accrued(p_amount INTEGER, p_months INTEGER, p_due_month INTEGER, p_date DATE)
+RETURNS BIGINT AS $$
+    SELECT p_amount::bigint * (p_date - c.cycle_start + 1) / ((c.cycle_start + make_interval(months => p_months))::date - c.cycle_start)
+    FROM (SELECT budget_horizon_cycle_start(p_months, p_due_month, p_date) AS cycle_start) c;
+$$ LANGUAGE sql IMMUTABLE;
+
+-- Accrual assigned to the period p_start..p_end, including the tail of the previous cycle when the period spans two
+CREATE OR REPLACE FUNCTION budget_horizon_period_accrual(p_amount INTEGER, p_months INTEGER, p_due_month INTEGER, p_start DATE, p_end DATE)
+RETURNS INTEGER AS $$
+    SELECT (
+        budget_horizon_accrued(p_amount, p_months, p_due_month, p_end)
+        - budget_horizon_accrued(p_amount, p_months, p_due_month, p_start - 1)
+        + CASE
+            WHEN budget_horizon_cycle_start(p_months, p_due_month, p_start - 1) < budget_horizon_cycle_start(p_months, p_due_month, p_end)
+                THEN p_amount
+            ELSE 0
+          END
+    )::integer;
+$$ LANGUAGE sql IMMUTABLE;
+
+CREATE OR REPLACE FUNCTION budget_category_target_for_period(p_budget_category_id UUID, p_period_id UUID, p_period_start DATE)
+RETURNS INTEGER AS $$
+    SELECT COALESCE(
+        (
+            SELECT o.budgeted_value
+            FROM budget_category_period_override o
+            WHERE o.budget_category_id = p_budget_category_id
+              AND o.period_id = p_period_id
+        ),
+        (
+            SELECT CASE
+                WHEN h.target_horizon_months IS NOT NULL THEN (
+                    SELECT budget_horizon_period_accrual(h.budgeted_value, h.target_horizon_months, h.target_due_month, bp.start_date, bp.end_date)
+                    FROM budget_period bp
+                    WHERE bp.id = p_period_id
+                )
+                WHEN h.target_income_basis_points IS NULL THEN h.budgeted_value
+                ELSE ROUND(budget_period_income(p_period_id, h.target_income_source) * h.target_income_basis_points / 10000.0)::integer
+            END
+            FROM budget_category_target_history h
+            WHERE h.budget_category_id = p_budget_category_id
+              AND h.effective_from <= p_period_start
+            ORDER BY h.effective_from DESC
+            LIMIT 1
+        )
+    );
+$$ LANGUAGE sql STABLE;
+
+-- Progress through the horizon cycle that contains the end of a period, for categories with a long-horizon target.
+-- No row when the category's target for the period is a per-period amount.
+CREATE OR REPLACE FUNCTION budget_category_accrual_for_period(p_budget_category_id UUID, p_period_id UUID)
+RETURNS TABLE (
+    horizon_amount  INTEGER,
+    horizon_months  INTEGER,
+    due_month       INTEGER,
+    cycle_start     DATE,
+    cycle_end       DATE,
+    accrued_amount  BIGINT,
+    spent_amount    BIGINT
+) AS $$
+    SELECT
+        h.budgeted_value,
+        h.target_horizon_months,
+        h.target_due_month,
+        cs.cycle_start,
+        (cs.cycle_start + make_interval(months => h.target_horizon_months))::date - 1,
+        budget_horizon_accrued(h.budgeted_value, h.target_horizon_months, h.target_due_month, bp.end_date),
+        (
+            SELECT COALESCE(SUM(t.amount), 0)::bigint
+            FROM transaction t
+            WHERE t.user_id = bc.user_id
+              AND t.category_id = bc.category_id
+              AND t.occurred_at >= cs.cycle_start
+              AND t.occurred_at <= bp.end_date
+        )
+    FROM budget_category bc
+    JOIN budget_period bp ON bp.id = p_period_id
+    CROSS JOIN LATERAL (
+        SELECT *
+        FROM budget_category_target_history th
+        WHERE th.budget_category_id = bc.id
+          AND th.effective_from <= bp.start_date
+        ORDER BY th.effective_from DESC
+        LIMIT 1
+    ) h
+    CROSS JOIN LATERAL (
+        SELECT budget_horizon_cycle_start(h.target_horizon_months, h.target_due_month, bp.end_date) AS cycle_start
+    ) cs
+    WHERE bc.id = p_budget_category_id
+      AND h.target_horizon_months IS NOT NULL;
+$$ LANGUAGE sql STABLE;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS category_alert_setting;
+
+ALTER TABLE settings
+    DROP COLUMN IF EXISTS category_email_alerts,
+    DROP COLUMN IF EXISTS category_alert_thresholds;
@@ -0,0 +1,18 @@
+-- Category budget alert thresholds in basis points of the period target
+ALTER TABLE settings
+    ADD COLUMN IF NOT EXISTS category_alert_thresholds INTEGER[] NOT NULL DEFAULT '{8000,10000}',
+    ADD COLUMN IF NOT EXISTS category_email_alerts BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Per-category overrides of the global thresholds; is_enabled = FALSE silences a category
+CREATE TABLE IF NOT EXISTS category_alert_setting (
+    id          UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id     UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    category_id UUID        NOT NULL REFERENCES category (id) ON DELETE CASCADE,
+    thresholds  INTEGER[]   NOT NULL,
+    is_enabled  BOOLEAN     NOT NULL DEFAULT TRUE,
+    created_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at  TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (category_id)
+);
+
+CREATE INDEX idx_category_alert_setting_user ON category_alert_setting (user_id);
@@ -0,0 +1,6 @@
+DROP TABLE IF EXISTS budget_period_account_snapshot;
+DROP TABLE IF EXISTS budget_period_category_snapshot;
+DROP TABLE IF EXISTS budget_period_snapshot;
+
+ALTER TABLE budget_period
+    DROP COLUMN IF EXISTS closed_at;
@@ -0,0 +1,39 @@
+-- Closed periods are locked: their transactions cannot change and their reports read from the snapshot below
+ALTER TABLE budget_period
+    ADD COLUMN IF NOT EXISTS closed_at TIMESTAMPTZ NULL;
+
+-- Period totals at closing time
+CREATE TABLE IF NOT EXISTS budget_period_snapshot (
+    period_id         UUID PRIMARY KEY     REFERENCES budget_period (id) ON DELETE CASCADE,
+    user_id           UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    total_budgeted    BIGINT      NOT NULL,
+    total_spent       BIGINT      NOT NULL,
+    total_income      BIGINT      NOT NULL,
+    -- Outgoing spend with long-horizon categories paid within their horizon counted at their accrual
+    stability_spent   BIGINT      NOT NULL,
+    transaction_count BIGINT      NOT NULL,
+    created_at        TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+-- Target outcome per category; names are copied so the snapshot survives renames and deletions
+CREATE TABLE IF NOT EXISTS budget_period_category_snapshot (
+    period_id      UUID    NOT NULL REFERENCES budget_period_snapshot (period_id) ON DELETE CASCADE,
+    category_id    UUID    NOT NULL,
+    category_name  TEXT    NOT NULL,
+    category_type  TEXT    NOT NULL,
+    target         INTEGER NULL,
+    actual         BIGINT  NOT NULL,
+    is_excluded    BOOLEAN NOT NULL,
+    within_horizon BOOLEAN NOT NULL,
+    PRIMARY KEY (period_id, category_id)
+);
+
+-- Account balances at the end of the period
+CREATE TABLE IF NOT EXISTS budget_period_account_snapshot (
+    period_id       UUID   NOT NULL REFERENCES budget_period_snapshot (period_id) ON DELETE CASCADE,
+    account_id      UUID   NOT NULL,
+    account_name    TEXT   NOT NULL,
+    account_type    TEXT   NOT NULL,
+    closing_balance BIGINT NOT NULL,
+    PRIMARY KEY (period_id, account_id)
+);
@@ -0,0 +1,6 @@
+ALTER TABLE period_schedule DROP CONSTRAINT IF EXISTS period_schedule_kind_fields_check;
+
+ALTER TABLE period_schedule
+    DROP COLUMN IF EXISTS anchor_date,
+    DROP COLUMN IF EXISTS second_day,
+    DROP COLUMN IF EXISTS schedule_kind;
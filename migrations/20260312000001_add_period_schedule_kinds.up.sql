@@ -0,0 +1,15 @@
+-- Schedule kinds beyond a fixed start day plus duration:
+--   semi_monthly      periods start on start_day and second_day of every month
+--   anchored          periods of duration_value/duration_unit counted from anchor_date
+--   last_business_day periods start on the last weekday of every month
+ALTER TABLE period_schedule
+    ADD COLUMN schedule_kind TEXT NOT NULL DEFAULT 'interval'
+        CHECK (schedule_kind IN ('interval', 'semi_monthly', 'anchored', 'last_business_day')),
+    ADD COLUMN second_day INTEGER CHECK (second_day >= 1 AND second_day <= 31),
+    ADD COLUMN anchor_date DATE;
+
+ALTER TABLE period_schedule
+    ADD CONSTRAINT period_schedule_kind_fields_check CHECK (
+        (schedule_kind <> 'semi_monthly' OR (second_day IS NOT NULL AND second_day > start_day))
+        AND (schedule_kind <> 'anchored' OR anchor_date IS NOT NULL)
+    );
@@ -0,0 +1,9 @@
+ALTER TABLE period_schedule
+    DROP COLUMN IF EXISTS holiday_adjustment;
+
+ALTER TABLE settings
+    DROP CONSTRAINT IF EXISTS settings_single_holiday_calendar_check,
+    DROP COLUMN IF EXISTS holiday_calendar_id,
+    DROP COLUMN IF EXISTS holiday_calendar_code;
+
+DROP TABLE IF EXISTS holiday_calendar;
@@ -0,0 +1,20 @@
+-- Holiday calendars uploaded by users; bundled calendars ship with the application
+CREATE TABLE IF NOT EXISTS holiday_calendar (
+    id         UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name       TEXT        NOT NULL,
+    rules      JSONB       NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_holiday_calendar_user ON holiday_calendar (user_id);
+
+-- The calendar a user's dates are adjusted against: a bundled one by code or an uploaded one
+ALTER TABLE settings
+    ADD COLUMN holiday_calendar_code TEXT,
+    ADD COLUMN holiday_calendar_id   UUID REFERENCES holiday_calendar (id) ON DELETE SET NULL,
+    ADD CONSTRAINT settings_single_holiday_calendar_check CHECK (holiday_calendar_code IS NULL OR holiday_calendar_id IS NULL);
+
+-- Where a period boundary moves when it falls on a weekday holiday
+ALTER TABLE period_schedule
+    ADD COLUMN holiday_adjustment TEXT NOT NULL DEFAULT 'keep' CHECK (holiday_adjustment IN ('keep', 'previous', 'next'));
@@ -0,0 +1,31 @@
+-- Only id rules at the top level, or directly under a top-level "or", survive the downgrade
+UPDATE overlays o
+SET rules = jsonb_build_object(
+        'category_ids', COALESCE((
+            SELECT jsonb_agg(id)
+            FROM jsonb_array_elements(CASE WHEN o.rules ->> 'type' = 'or' THEN o.rules -> 'rules' ELSE jsonb_build_array(o.rules) END) term,
+                 jsonb_array_elements(term -> 'ids') id
+            WHERE term ->> 'type' = 'category'
+        ), '[]'::jsonb),
+        'vendor_ids', COALESCE((
+            SELECT jsonb_agg(id)
+            FROM jsonb_array_elements(CASE WHEN o.rules ->> 'type' = 'or' THEN o.rules -> 'rules' ELSE jsonb_build_array(o.rules) END) term,
+                 jsonb_array_elements(term -> 'ids') id
+            WHERE term ->> 'type' = 'vendor'
+        ), '[]'::jsonb),
+        'account_ids', COALESCE((
+            SELECT jsonb_agg(id)
+            FROM jsonb_array_elements(CASE WHEN o.rules ->> 'type' = 'or' THEN o.rules -> 'rules' ELSE jsonb_build_array(o.rules) END) term,
+                 jsonb_array_elements(term -> 'ids') id
+            WHERE term ->> 'type' = 'account'
+        ), '[]'::jsonb)
+    )
+WHERE o.rules ? 'type';
+
+UPDATE overlays
+SET rules = '{"category_ids": [], "vendor_ids": [], "account_ids": []}'::jsonb
+WHERE rules IS NULL;
+
+ALTER TABLE overlays
+    ALTER COLUMN rules SET DEFAULT '{"category_ids": [], "vendor_ids": [], "account_ids": []}'::jsonb,
+    ALTER COLUMN rules SET NOT NULL;
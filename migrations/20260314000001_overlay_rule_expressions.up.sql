@@ -0,0 +1,30 @@
+-- Overlay rules become a boolean expression tree; NULL means no rule matches
+ALTER TABLE overlays
+    ALTER COLUMN rules DROP NOT NULL,
+    ALTER COLUMN rules DROP DEFAULT;
+
+-- Legacy {category_ids, vendor_ids, account_ids} lists matched when any list matched
+UPDATE overlays o
+SET rules = migrated.rules
+FROM (
+    SELECT id,
+           CASE
+               WHEN jsonb_array_length(terms) = 0 THEN NULL
+               ELSE jsonb_build_object('type', 'or', 'rules', terms)
+           END AS rules
+    FROM (
+        SELECT id,
+               (
+                   SELECT COALESCE(jsonb_agg(jsonb_build_object('type', legacy.kind, 'ids', legacy.ids)), '[]'::jsonb)
+                   FROM (
+                       VALUES ('category', rules -> 'category_ids'),
+                              ('vendor', rules -> 'vendor_ids'),
+                              ('account', rules -> 'account_ids')
+                   ) AS legacy(kind, ids)
+                   WHERE CASE WHEN jsonb_typeof(legacy.ids) = 'array' THEN jsonb_array_length(legacy.ids) ELSE 0 END > 0
+               ) AS terms
+        FROM overlays
+        WHERE rules ? 'category_ids' OR rules ? 'vendor_ids' OR rules ? 'account_ids'
+    ) legacy_rules
+) migrated
+WHERE o.id = migrated.id;
@@ -0,0 +1,3 @@
+-- Overlay cap alert thresholds in basis points of each cap
+ALTER TABLE overlays
+    ADD COLUMN IF NOT EXISTS alert_thresholds INTEGER[] NOT NULL DEFAULT '{8000,10000}';
@@ -0,0 +1,6 @@
+DROP INDEX IF EXISTS idx_overlays_template_start;
+
+ALTER TABLE overlays
+    DROP COLUMN IF EXISTS template_id;
+
+DROP TABLE IF EXISTS overlay_template;
@@ -0,0 +1,26 @@
+-- Overlays saved for reuse with a relative date rule; instances point back at their template
+CREATE TABLE IF NOT EXISTS overlay_template (
+    id               UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id          UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name             TEXT        NOT NULL,
+    icon             TEXT        NULL,
+    inclusion_mode   TEXT        NOT NULL CHECK (inclusion_mode IN ('manual', 'rules', 'all')),
+    total_cap_amount BIGINT      NULL,
+    category_caps    JSONB       NOT NULL DEFAULT '[]'::jsonb,
+    alert_thresholds INTEGER[]   NOT NULL DEFAULT '{8000,10000}',
+    rules            JSONB       NULL,
+    date_rule        JSONB       NOT NULL,
+    auto_create      BOOLEAN     NOT NULL DEFAULT FALSE,
+    lead_days        INTEGER     NOT NULL DEFAULT 14 CHECK (lead_days BETWEEN 0 AND 365),
+    created_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    updated_at       TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, name)
+);
+
+CREATE INDEX idx_overlay_template_auto_create ON overlay_template (auto_create) WHERE auto_create = TRUE;
+
+ALTER TABLE overlays
+    ADD COLUMN IF NOT EXISTS template_id UUID NULL REFERENCES overlay_template (id) ON DELETE SET NULL;
+
+-- A template is instantiated at most once per start date
+CREATE UNIQUE INDEX idx_overlays_template_start ON overlays (template_id, start_date) WHERE template_id IS NOT NULL;
@@ -0,0 +1,4 @@
+ALTER TABLE overlays
+    DROP COLUMN IF EXISTS membership_refreshed_at;
+
+DROP TABLE IF EXISTS overlay_transaction_membership;
@@ -0,0 +1,14 @@
+-- Computed overlay membership, one row per included transaction.
+-- Kept up to date when transactions, overlays or manual inclusions change.
+CREATE TABLE IF NOT EXISTS overlay_transaction_membership (
+    overlay_id       UUID NOT NULL REFERENCES overlays (id) ON DELETE CASCADE,
+    transaction_id   UUID NOT NULL REFERENCES transaction (id) ON DELETE CASCADE,
+    inclusion_source TEXT NOT NULL CHECK (inclusion_source IN ('manual', 'rules', 'all')),
+    PRIMARY KEY (overlay_id, transaction_id)
+);
+
+CREATE INDEX idx_overlay_tx_membership_tx ON overlay_transaction_membership (transaction_id);
+
+-- NULL until the membership of an overlay has been computed; existing overlays are filled in on first read
+ALTER TABLE overlays
+    ADD COLUMN IF NOT EXISTS membership_refreshed_at TIMESTAMPTZ;
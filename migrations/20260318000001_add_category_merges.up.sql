@@ -0,0 +1,13 @@
+-- Audit trail of category merges; the source category is deleted by the merge, so its name is copied
+CREATE TABLE IF NOT EXISTS category_merge (
+    id                        UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id                   UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    source_category_id        UUID        NOT NULL,
+    source_category_name      TEXT        NOT NULL,
+    destination_category_id   UUID        NULL REFERENCES category (id) ON DELETE SET NULL,
+    destination_category_name TEXT        NOT NULL,
+    counts                    JSONB       NOT NULL,
+    created_at                TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX idx_category_merge_user ON category_merge (user_id, created_at DESC, id DESC);
@@ -0,0 +1,12 @@
+-- Raw transaction descriptions that stand for a vendor, e.g. "AMZN Mktp*" for Amazon
+CREATE TABLE IF NOT EXISTS vendor_alias (
+    id         UUID PRIMARY KEY     DEFAULT gen_random_uuid(),
+    user_id    UUID        NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    vendor_id  UUID        NOT NULL REFERENCES vendor (id) ON DELETE CASCADE,
+    pattern    TEXT        NOT NULL,
+    match_type TEXT        NOT NULL CHECK (match_type IN ('exact', 'pattern')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, match_type, pattern)
+);
+
+CREATE INDEX idx_vendor_alias_vendor ON vendor_alias (vendor_id);
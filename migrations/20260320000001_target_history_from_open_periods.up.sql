@@ -0,0 +1,41 @@
+-- A new default target takes effect from the earliest open period, the same rule that decides which
+-- periods targets may still be written to. Depends on `budget_period.closed_at` (20260311000001).
+CREATE OR REPLACE FUNCTION record_budget_category_target_history()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF TG_OP = 'UPDATE'
+        AND OLD.budgeted_value = NEW.budgeted_value
+        AND OLD.target_income_basis_points IS NOT DISTINCT FROM NEW.target_income_basis_points
+        AND OLD.target_income_source IS NOT DISTINCT FROM NEW.target_income_source
+        AND OLD.target_horizon_months IS NOT DISTINCT FROM NEW.target_horizon_months
+        AND OLD.target_due_month IS NOT DISTINCT FROM NEW.target_due_month THEN
+        RETURN NEW;
+    END IF;
+
+    INSERT INTO budget_category_target_history (
+        budget_category_id, budgeted_value, target_income_basis_points, target_income_source,
+        target_horizon_months, target_due_month, effective_from
+    )
+    VALUES (
+        NEW.id,
+        NEW.budgeted_value,
+        NEW.target_income_basis_points,
+        NEW.target_income_source,
+        NEW.target_horizon_months,
+        NEW.target_due_month,
+        COALESCE(
+            (SELECT MIN(bp.start_date) FROM budget_period bp WHERE bp.user_id = NEW.user_id AND bp.closed_at IS NULL),
+            CURRENT_DATE
+        )
+    )
+    ON CONFLICT (budget_category_id, effective_from)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value,
+                  target_income_basis_points = EXCLUDED.target_income_basis_points,
+                  target_income_source = EXCLUDED.target_income_source,
+                  target_horizon_months = EXCLUDED.target_horizon_months,
+                  target_due_month = EXCLUDED.target_due_month,
+                  created_at = now();
+
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
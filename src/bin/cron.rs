@@ -1,8 +1,8 @@
-use piggy_pulse::{Config, generate_periods};
+use piggy_pulse::{Config, evaluate_alerts, fund_allowances, generate_periods, instantiate_overlays};
 use tracing_subscriber::EnvFilter;
 
 fn print_usage(bin_name: &str) {
-    eprintln!("Usage: {bin_name} generate-periods");
+    eprintln!("Usage: {bin_name} <generate-periods|fund-allowances|evaluate-alerts|instantiate-overlays>");
 }
 
 fn init_tracing(log_level: &str, json_format: bool) {
@@ -24,10 +24,13 @@ async fn main() {
     let bin_name = args.next().unwrap_or_else(|| "cron".to_string());
     let command = args.next();
 
-    if command.as_deref() != Some("generate-periods") || args.next().is_some() {
-        print_usage(&bin_name);
-        std::process::exit(2);
-    }
+    let command = match command.as_deref() {
+        Some(command @ ("generate-periods" | "fund-allowances" | "evaluate-alerts" | "instantiate-overlays")) if args.next().is_none() => command.to_string(),
+        _ => {
+            print_usage(&bin_name);
+            std::process::exit(2);
+        }
+    };
 
     let config = match Config::load() {
         Ok(config) => config,
@@ -39,13 +42,35 @@ async fn main() {
 
     init_tracing(&config.logging.level, config.logging.json_format);
 
-    match generate_periods(&config).await {
-        Ok(result) => {
-            println!(
+    let outcome = match command.as_str() {
+        "fund-allowances" => fund_allowances(&config).await.map(|result| {
+            format!(
+                "Allowance funding completed: rules_processed={}, transfers_created={}, runs_skipped={}",
+                result.rules_processed, result.transfers_created, result.runs_skipped
+            )
+        }),
+        "evaluate-alerts" => evaluate_alerts(&config).await.map(|result| {
+            format!(
+                "Alert evaluation completed: users_processed={}, alerts_raised={}, users_failed={}",
+                result.users_processed, result.alerts_raised, result.users_failed
+            )
+        }),
+        "instantiate-overlays" => instantiate_overlays(&config).await.map(|result| {
+            format!(
+                "Overlay template instantiation completed: templates_processed={}, overlays_created={}",
+                result.templates_processed, result.overlays_created
+            )
+        }),
+        _ => generate_periods(&config).await.map(|result| {
+            format!(
                 "Automatic period generation completed: users_processed={}, periods_created={}",
                 result.users_processed, result.periods_created
-            );
-        }
+            )
+        }),
+    };
+
+    match outcome {
+        Ok(summary) => println!("{summary}"),
         Err(err) => {
             eprintln!("Cron job failed: {err}");
             std::process::exit(1);
@@ -1,6 +1,7 @@
 use crate::Config;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::db::init_pool;
+use crate::service::alert::AlertService;
 
 #[derive(Debug, Clone, Copy)]
 pub struct GeneratePeriodsResult {
@@ -26,3 +27,101 @@ pub async fn generate_periods(config: &Config) -> Result<GeneratePeriodsResult,
         periods_created: result.periods_created,
     })
 }
+
+#[derive(Debug, Clone, Copy)]
+pub struct FundAllowancesResult {
+    pub rules_processed: i64,
+    pub transfers_created: i64,
+    pub runs_skipped: i64,
+}
+
+pub async fn fund_allowances(config: &Config) -> Result<FundAllowancesResult, String> {
+    let pool = init_pool(&config.database, config.logging.slow_query_ms)
+        .await
+        .map_err(|err| format!("Failed to initialize database pool: {err}"))?;
+
+    let repo = PostgresRepository { pool: pool.clone() };
+    let today = chrono::Utc::now().date_naive();
+    let result = repo
+        .run_allowance_funding(today)
+        .await
+        .map_err(|err| format!("Failed to fund allowance accounts: {err:?}"))?;
+
+    pool.close().await;
+
+    Ok(FundAllowancesResult {
+        rules_processed: result.rules_processed,
+        transfers_created: result.transfers_created,
+        runs_skipped: result.runs_skipped,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct InstantiateOverlaysResult {
+    pub templates_processed: i64,
+    pub overlays_created: i64,
+}
+
+pub async fn instantiate_overlays(config: &Config) -> Result<InstantiateOverlaysResult, String> {
+    let pool = init_pool(&config.database, config.logging.slow_query_ms)
+        .await
+        .map_err(|err| format!("Failed to initialize database pool: {err}"))?;
+
+    let repo = PostgresRepository { pool: pool.clone() };
+    let today = chrono::Utc::now().date_naive();
+    let result = repo
+        .run_overlay_template_instantiation(today)
+        .await
+        .map_err(|err| format!("Failed to instantiate overlay templates: {err:?}"))?;
+
+    pool.close().await;
+
+    Ok(InstantiateOverlaysResult {
+        templates_processed: result.templates_processed,
+        overlays_created: result.overlays_created,
+    })
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EvaluateAlertsResult {
+    pub users_processed: i64,
+    pub alerts_raised: i64,
+    pub users_failed: i64,
+}
+
+/// Catches thresholds crossed without a transaction write, e.g. after a target was lowered.
+pub async fn evaluate_alerts(config: &Config) -> Result<EvaluateAlertsResult, String> {
+    let pool = init_pool(&config.database, config.logging.slow_query_ms)
+        .await
+        .map_err(|err| format!("Failed to initialize database pool: {err}"))?;
+
+    let repo = PostgresRepository { pool: pool.clone() };
+    let today = chrono::Utc::now().date_naive();
+    let user_ids = repo
+        .list_users_with_period_on(today)
+        .await
+        .map_err(|err| format!("Failed to list users with a current period: {err:?}"))?;
+
+    let alerts = AlertService::new(&repo, &config.email);
+    let mut result = EvaluateAlertsResult {
+        users_processed: 0,
+        alerts_raised: 0,
+        users_failed: 0,
+    };
+    for user_id in &user_ids {
+        match alerts.evaluate(user_id, today).await {
+            Ok(raised) => {
+                result.users_processed += 1;
+                result.alerts_raised += raised.total() as i64;
+            }
+            Err(err) => {
+                tracing::warn!("Failed to evaluate alerts for user {}: {:?}", user_id, err);
+                result.users_failed += 1;
+            }
+        }
+    }
+
+    pool.close().await;
+
+    Ok(result)
+}
@@ -1,18 +1,32 @@
 pub mod account;
+pub mod allowance_funding;
 pub mod audit;
 pub mod budget_category;
 pub mod budget_period;
+pub mod budget_period_snapshot;
+pub mod budget_template;
+pub mod cash_flow;
 pub mod category;
+pub mod category_alert;
+pub mod category_merge;
 pub mod category_target;
+pub mod category_tree;
 pub mod currency;
 pub mod dashboard;
+pub mod forecast;
+pub mod holiday_calendar;
+pub mod notification;
 pub mod overlay;
+pub mod overlay_template;
 pub mod password_reset;
+pub mod period_comparison;
 pub mod postgres_repository;
 pub mod rate_limit;
+pub mod savings_goal;
 pub mod session;
 pub mod settings;
 pub mod transaction;
 pub mod two_factor;
 pub mod user;
 pub mod vendor;
+pub mod vendor_alias;
@@ -2,7 +2,8 @@ use crate::database::postgres_repository::{PostgresRepository, is_unique_violati
 use crate::error::app_error::AppError;
 use crate::models::account::{
     Account, AccountBalanceHistoryPoint, AccountBalancePerDay, AccountContextResponse, AccountDetailResponse, AccountManagementResponse, AccountRequest,
-    AccountStability, AccountTransactionResponse, AccountType, AccountUpdateRequest, AccountWithMetrics, CategoryImpactItem,
+    AccountSpendInPeriod, AccountStability, AccountTransactionResponse, AccountType, AccountUpdateRequest, AccountWithMetrics, CategoryImpactItem,
+    spend_limit_remaining,
 };
 use crate::models::currency::{Currency, CurrencyResponse, SymbolPosition};
 use crate::models::pagination::CursorParams;
@@ -72,6 +73,7 @@ struct AccountMetricsRow {
     currency_symbol_position: SymbolPosition,
     current_balance: i64,
     balance_change_this_period: i64,
+    spent_this_period: i64,
     transaction_count: i64,
 }
 
@@ -99,6 +101,7 @@ impl From<AccountMetricsRow> for AccountWithMetrics {
             },
             current_balance: row.current_balance,
             balance_change_this_period: row.balance_change_this_period,
+            spent_this_period: row.spent_this_period,
             transaction_count: row.transaction_count,
         }
     }
@@ -311,6 +314,16 @@ impl PostgresRepository {
                             ELSE 0
                         END
                     ), 0)::bigint AS balance_change_this_period,
+                    COALESCE(SUM(
+                        CASE
+                            WHEN p.start_date IS NOT NULL
+                             AND t.occurred_at >= p.start_date
+                             AND t.occurred_at <= p.end_date
+                             AND cat.category_type = 'Outgoing'
+                             AND t.from_account_id = a.id THEN t.amount::bigint
+                            ELSE 0
+                        END
+                    ), 0)::bigint AS spent_this_period,
                     COALESCE(SUM(
                         CASE
                             WHEN t.from_account_id = a.id THEN 1
@@ -401,6 +414,16 @@ impl PostgresRepository {
                             ELSE 0
                         END
                     ), 0)::bigint AS balance_change_this_period,
+                    COALESCE(SUM(
+                        CASE
+                            WHEN p.start_date IS NOT NULL
+                             AND t.occurred_at >= p.start_date
+                             AND t.occurred_at <= p.end_date
+                             AND cat.category_type = 'Outgoing'
+                             AND t.from_account_id = a.id THEN t.amount::bigint
+                            ELSE 0
+                        END
+                    ), 0)::bigint AS spent_this_period,
                     COALESCE(SUM(
                         CASE
                             WHEN t.from_account_id = a.id THEN 1
@@ -904,8 +927,10 @@ ORDER BY a.id, d.day
         #[derive(sqlx::FromRow)]
         struct DetailRow {
             balance: i64,
+            spend_limit: Option<i32>,
             inflows: i64,
             outflows: i64,
+            spent: i64,
             period_start: NaiveDate,
             period_end: NaiveDate,
         }
@@ -942,13 +967,19 @@ flow AS (
             WHEN category_type = 'Outgoing' THEN amount
             WHEN category_type = 'Transfer' AND from_account_id = $1 THEN amount
             ELSE 0
-        END), 0) AS outflows
+        END), 0) AS outflows,
+        COALESCE(SUM(CASE
+            WHEN category_type = 'Outgoing' AND from_account_id = $1 THEN amount
+            ELSE 0
+        END), 0) AS spent
     FROM period_txs
 )
 SELECT
     a.balance                         AS balance,
+    a.spend_limit                     AS spend_limit,
     flow.inflows::bigint              AS inflows,
     flow.outflows::bigint             AS outflows,
+    flow.spent::bigint                AS spent,
     period.start_date                 AS period_start,
     period.end_date                   AS period_end
 FROM account a
@@ -974,9 +1005,55 @@ WHERE a.id = $1 AND a.user_id = $3
             net,
             period_start: row.period_start,
             period_end: row.period_end,
+            spend_limit: row.spend_limit,
+            spent_this_period: row.spent,
+            spend_limit_remaining: spend_limit_remaining(row.spend_limit, row.spent),
         })
     }
 
+    /// Spend of every account with a spend limit in the budget period that contains `date`.
+    pub async fn list_account_spend_for_date(&self, user_id: &Uuid, date: NaiveDate) -> Result<Vec<AccountSpendInPeriod>, AppError> {
+        let rows = sqlx::query_as::<_, AccountSpendInPeriod>(
+            r#"
+            WITH period AS (
+                SELECT id, name, start_date, end_date
+                FROM budget_period
+                WHERE user_id = $1 AND start_date <= $2 AND end_date >= $2
+                ORDER BY start_date DESC
+                LIMIT 1
+            )
+            SELECT
+                a.id AS account_id,
+                a.name AS account_name,
+                a.spend_limit,
+                COALESCE(SUM(t.amount) FILTER (WHERE c.category_type = 'Outgoing'), 0)::bigint AS spent,
+                cur.symbol AS currency_symbol,
+                cur.decimal_places AS currency_decimal_places,
+                p.id AS period_id,
+                p.name AS period_name
+            FROM account a
+            CROSS JOIN period p
+            JOIN currency cur ON cur.id = a.currency_id
+            LEFT JOIN transaction t
+                ON t.from_account_id = a.id
+               AND t.user_id = $1
+               AND t.occurred_at >= p.start_date
+               AND t.occurred_at <= p.end_date
+            LEFT JOIN category c ON c.id = t.category_id
+            WHERE a.user_id = $1
+              AND a.is_archived = FALSE
+              AND a.spend_limit IS NOT NULL
+            GROUP BY a.id, a.name, a.spend_limit, cur.symbol, cur.decimal_places, p.id, p.name
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
     pub async fn get_account_balance_history(
         &self,
         account_id: &Uuid,
@@ -0,0 +1,566 @@
+use crate::database::budget_period::add_duration;
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
+use crate::error::app_error::AppError;
+use crate::models::allowance_funding::{
+    AllowanceFundingResult, AllowanceFundingRule, AllowanceFundingRuleRequest, AllowanceFundingRun, FundingCadence, FundingRunStatus, NextRunAdjustmentRequest,
+};
+use crate::models::pagination::CursorParams;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+/// Upper bound on how many missed interval runs a single cron pass will catch up on per rule.
+const MAX_CATCH_UP_RUNS: usize = 366;
+
+const ALLOWANCE_TOP_UP_DESCRIPTION: &str = "Allowance top-up";
+
+// For period cadence the next run is the earliest budget period start that has not been funded yet.
+const FUNDING_RULE_SELECT_FIELDS: &str = r#"
+    r.id,
+    r.user_id,
+    r.allowance_account_id,
+    r.source_account_id,
+    r.category_id,
+    r.cadence,
+    r.interval_value,
+    r.interval_unit,
+    r.starts_on,
+    CASE
+        WHEN r.cadence = 'period' THEN (
+            SELECT MIN(bp.start_date)
+            FROM budget_period bp
+            WHERE bp.user_id = r.user_id
+              AND bp.start_date >= r.starts_on
+              AND NOT EXISTS (
+                  SELECT 1 FROM allowance_funding_run fr
+                  WHERE fr.rule_id = r.id AND fr.scheduled_for = bp.start_date
+              )
+        )
+        ELSE r.next_run_date
+    END AS next_run_date,
+    r.skip_next_run,
+    r.next_run_amount_override,
+    r.is_active,
+    a.next_transfer_amount
+"#;
+
+fn build_funding_rule_query(from_clause: &str, where_clause: &str) -> String {
+    let mut query = format!(
+        "SELECT {} FROM {} JOIN account a ON a.id = r.allowance_account_id",
+        FUNDING_RULE_SELECT_FIELDS, from_clause
+    );
+    if !where_clause.is_empty() {
+        query.push_str(" WHERE ");
+        query.push_str(where_clause);
+    }
+    query
+}
+
+impl PostgresRepository {
+    async fn validate_funding_rule_references(&self, request: &AllowanceFundingRuleRequest, user_id: &Uuid) -> Result<(), AppError> {
+        let allowance_type: Option<String> = sqlx::query_scalar("SELECT account_type::text FROM account WHERE id = $1 AND user_id = $2")
+            .bind(request.allowance_account_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        match allowance_type.as_deref() {
+            None => return Err(AppError::BadRequest("Invalid allowance_account_id for current user".to_string())),
+            Some("Allowance") => {}
+            Some(_) => return Err(AppError::BadRequest("Funding rules can only target Allowance accounts".to_string())),
+        }
+
+        let source_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM account WHERE id = $1 AND user_id = $2 AND is_archived = FALSE)")
+            .bind(request.source_account_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !source_exists {
+            return Err(AppError::BadRequest("Invalid source_account_id for current user".to_string()));
+        }
+
+        let category_type: Option<String> = sqlx::query_scalar("SELECT category_type::text FROM category WHERE id = $1 AND user_id = $2")
+            .bind(request.category_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        match category_type.as_deref() {
+            None => Err(AppError::BadRequest("Invalid category_id for current user".to_string())),
+            Some("Transfer") => Ok(()),
+            Some(_) => Err(AppError::BadRequest("Funding rules require a Transfer category".to_string())),
+        }
+    }
+
+    pub async fn create_allowance_funding_rule(&self, request: &AllowanceFundingRuleRequest, user_id: &Uuid) -> Result<AllowanceFundingRule, AppError> {
+        self.validate_funding_rule_references(request, user_id).await?;
+
+        let next_run_date = (request.cadence == FundingCadence::Interval).then_some(request.starts_on);
+        let query = format!(
+            r#"
+            WITH inserted AS (
+                INSERT INTO allowance_funding_rule (
+                    user_id, allowance_account_id, source_account_id, category_id,
+                    cadence, interval_value, interval_unit, starts_on, next_run_date, is_active
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                RETURNING *
+            )
+            {}
+            "#,
+            build_funding_rule_query("inserted r", "")
+        );
+
+        let result = sqlx::query_as::<_, AllowanceFundingRule>(&query)
+            .bind(user_id)
+            .bind(request.allowance_account_id)
+            .bind(request.source_account_id)
+            .bind(request.category_id)
+            .bind(request.cadence)
+            .bind(request.interval_value)
+            .bind(request.interval_unit)
+            .bind(request.starts_on)
+            .bind(next_run_date)
+            .bind(request.is_active)
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(rule) => Ok(rule),
+            Err(err) if is_unique_violation(&err) => Err(AppError::BadRequest("This allowance account already has a funding rule".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn get_allowance_funding_rule(&self, id: &Uuid, user_id: &Uuid) -> Result<AllowanceFundingRule, AppError> {
+        let query = build_funding_rule_query("allowance_funding_rule r", "r.id = $1 AND r.user_id = $2");
+        let rule = sqlx::query_as::<_, AllowanceFundingRule>(&query)
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn list_allowance_funding_rules(&self, user_id: &Uuid) -> Result<Vec<AllowanceFundingRule>, AppError> {
+        let query = format!(
+            "{} ORDER BY r.created_at DESC, r.id DESC",
+            build_funding_rule_query("allowance_funding_rule r", "r.user_id = $1")
+        );
+        let rules = sqlx::query_as::<_, AllowanceFundingRule>(&query).bind(user_id).fetch_all(&self.pool).await?;
+
+        Ok(rules)
+    }
+
+    pub async fn update_allowance_funding_rule(
+        &self,
+        id: &Uuid,
+        request: &AllowanceFundingRuleRequest,
+        user_id: &Uuid,
+    ) -> Result<AllowanceFundingRule, AppError> {
+        self.validate_funding_rule_references(request, user_id).await?;
+
+        // Interval rules keep their progress: the next run never moves back before a run that already happened.
+        let query = format!(
+            r#"
+            WITH updated AS (
+                UPDATE allowance_funding_rule
+                SET allowance_account_id = $1,
+                    source_account_id = $2,
+                    category_id = $3,
+                    cadence = $4,
+                    interval_value = $5,
+                    interval_unit = $6,
+                    starts_on = $7,
+                    next_run_date = CASE
+                        WHEN $4 = 'interval' THEN GREATEST($7, COALESCE(next_run_date, $7))
+                        ELSE NULL
+                    END,
+                    is_active = $8,
+                    updated_at = now()
+                WHERE id = $9 AND user_id = $10
+                RETURNING *
+            )
+            {}
+            "#,
+            build_funding_rule_query("updated r", "")
+        );
+
+        let result = sqlx::query_as::<_, AllowanceFundingRule>(&query)
+            .bind(request.allowance_account_id)
+            .bind(request.source_account_id)
+            .bind(request.category_id)
+            .bind(request.cadence)
+            .bind(request.interval_value)
+            .bind(request.interval_unit)
+            .bind(request.starts_on)
+            .bind(request.is_active)
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(rule) => Ok(rule),
+            Err(err) if is_unique_violation(&err) => Err(AppError::BadRequest("This allowance account already has a funding rule".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn delete_allowance_funding_rule(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM allowance_funding_rule WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Skip the next run or change the amount it will transfer. Both flags are cleared once the run is recorded.
+    pub async fn adjust_allowance_funding_next_run(
+        &self,
+        id: &Uuid,
+        request: &NextRunAdjustmentRequest,
+        user_id: &Uuid,
+    ) -> Result<AllowanceFundingRule, AppError> {
+        let query = format!(
+            r#"
+            WITH updated AS (
+                UPDATE allowance_funding_rule
+                SET skip_next_run = $1,
+                    next_run_amount_override = $2,
+                    updated_at = now()
+                WHERE id = $3 AND user_id = $4
+                RETURNING *
+            )
+            {}
+            "#,
+            build_funding_rule_query("updated r", "")
+        );
+
+        let rule = sqlx::query_as::<_, AllowanceFundingRule>(&query)
+            .bind(request.skip)
+            .bind(request.amount_override)
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(rule)
+    }
+
+    pub async fn list_allowance_funding_runs(&self, rule_id: &Uuid, params: &CursorParams, user_id: &Uuid) -> Result<Vec<AllowanceFundingRun>, AppError> {
+        // Surface a 404 for unknown rules instead of an empty history
+        self.get_allowance_funding_rule(rule_id, user_id).await?;
+
+        let runs = if let Some(cursor) = params.cursor {
+            sqlx::query_as::<_, AllowanceFundingRun>(
+                r#"
+                SELECT id, rule_id, scheduled_for, status, amount, transaction_id, created_at
+                FROM allowance_funding_run
+                WHERE rule_id = $1
+                  AND user_id = $2
+                  AND (created_at, id) < (SELECT created_at, id FROM allowance_funding_run WHERE id = $3)
+                ORDER BY created_at DESC, id DESC
+                LIMIT $4
+                "#,
+            )
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(cursor)
+            .bind(params.fetch_limit())
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            sqlx::query_as::<_, AllowanceFundingRun>(
+                r#"
+                SELECT id, rule_id, scheduled_for, status, amount, transaction_id, created_at
+                FROM allowance_funding_run
+                WHERE rule_id = $1 AND user_id = $2
+                ORDER BY created_at DESC, id DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(rule_id)
+            .bind(user_id)
+            .bind(params.fetch_limit())
+            .fetch_all(&self.pool)
+            .await?
+        };
+
+        Ok(runs)
+    }
+
+    /// Creates the transfer transactions for every due funding run across all users.
+    /// Each run is keyed on (rule, scheduled date), so re-running the job never funds the same date twice.
+    pub async fn run_allowance_funding(&self, today: NaiveDate) -> Result<AllowanceFundingResult, AppError> {
+        let query = build_funding_rule_query("allowance_funding_rule r", "r.is_active = TRUE AND a.is_archived = FALSE");
+        let rules = sqlx::query_as::<_, AllowanceFundingRule>(&query).fetch_all(&self.pool).await?;
+
+        let mut transfers_created = 0_i64;
+        let mut runs_skipped = 0_i64;
+
+        for rule in &rules {
+            let due_dates = match rule.cadence {
+                FundingCadence::Period => {
+                    sqlx::query_scalar::<_, NaiveDate>(
+                        r#"
+                        SELECT bp.start_date
+                        FROM budget_period bp
+                        WHERE bp.user_id = $1
+                          AND bp.start_date >= $2
+                          AND bp.start_date <= $3
+                          AND NOT EXISTS (
+                              SELECT 1 FROM allowance_funding_run fr
+                              WHERE fr.rule_id = $4 AND fr.scheduled_for = bp.start_date
+                          )
+                        ORDER BY bp.start_date
+                        "#,
+                    )
+                    .bind(rule.user_id)
+                    .bind(rule.starts_on)
+                    .bind(today)
+                    .bind(rule.id)
+                    .fetch_all(&self.pool)
+                    .await?
+                }
+                FundingCadence::Interval => due_interval_dates(rule, today),
+            };
+
+            let mut skip = rule.skip_next_run;
+            let mut amount = rule.next_run_amount();
+
+            for scheduled_for in due_dates {
+                let next_run_date = interval_run_dates(rule).find(|date| *date > scheduled_for);
+
+                let status = if skip || amount.unwrap_or(0) <= 0 {
+                    FundingRunStatus::Skipped
+                } else {
+                    FundingRunStatus::Funded
+                };
+
+                let recorded = self
+                    .record_allowance_funding_run(rule, scheduled_for, status, amount.unwrap_or(0), next_run_date)
+                    .await?;
+
+                if let Some((status, adjustments_applied)) = recorded {
+                    match status {
+                        FundingRunStatus::Funded => transfers_created += 1,
+                        FundingRunStatus::Skipped => runs_skipped += 1,
+                    }
+                    // One-off adjustments only apply to the first run they were set for
+                    if adjustments_applied {
+                        skip = false;
+                        amount = rule.next_transfer_amount;
+                    }
+                }
+            }
+        }
+
+        Ok(AllowanceFundingResult {
+            rules_processed: rules.len() as i64,
+            transfers_created,
+            runs_skipped,
+        })
+    }
+
+    /// Records one run and its transfer atomically. A run dated in a closed period is recorded as skipped and leaves
+    /// the rule's one-off adjustments for the next run. Returns the recorded status and whether those adjustments were
+    /// used up, or `None` if the run already existed.
+    async fn record_allowance_funding_run(
+        &self,
+        rule: &AllowanceFundingRule,
+        scheduled_for: NaiveDate,
+        status: FundingRunStatus,
+        amount: i64,
+        next_run_date: Option<NaiveDate>,
+    ) -> Result<Option<(FundingRunStatus, bool)>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        // Locking the period keeps it from being closed while the transfer is written into it
+        let in_closed_period = sqlx::query_scalar::<_, bool>(
+            "SELECT closed_at IS NOT NULL FROM budget_period WHERE user_id = $1 AND start_date <= $2 AND end_date >= $2 FOR SHARE",
+        )
+        .bind(rule.user_id)
+        .bind(scheduled_for)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .any(|is_closed| is_closed);
+        let status = if in_closed_period { FundingRunStatus::Skipped } else { status };
+
+        let run_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO allowance_funding_run (rule_id, user_id, scheduled_for, status, amount)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (rule_id, scheduled_for) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(rule.id)
+        .bind(rule.user_id)
+        .bind(scheduled_for)
+        .bind(status)
+        .bind(if status == FundingRunStatus::Funded { amount } else { 0 })
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(run_id) = run_id else {
+            // Another pass already handled this date; still move an interval rule past it.
+            sqlx::query("UPDATE allowance_funding_rule SET next_run_date = GREATEST(next_run_date, $2) WHERE id = $1 AND cadence = 'interval'")
+                .bind(rule.id)
+                .bind(next_run_date)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+            return Ok(None);
+        };
+
+        let mut funded_transaction_id = None;
+        if status == FundingRunStatus::Funded {
+            let transaction_id: Uuid = sqlx::query_scalar(
+                r#"
+                INSERT INTO transaction (user_id, amount, description, occurred_at, category_id, from_account_id, to_account_id)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                RETURNING id
+                "#,
+            )
+            .bind(rule.user_id)
+            .bind(amount)
+            .bind(ALLOWANCE_TOP_UP_DESCRIPTION)
+            .bind(scheduled_for)
+            .bind(rule.category_id)
+            .bind(rule.source_account_id)
+            .bind(rule.allowance_account_id)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            sqlx::query("UPDATE allowance_funding_run SET transaction_id = $1 WHERE id = $2")
+                .bind(transaction_id)
+                .bind(run_id)
+                .execute(&mut *tx)
+                .await?;
+            funded_transaction_id = Some(transaction_id);
+        }
+
+        sqlx::query(
+            r#"
+            UPDATE allowance_funding_rule
+            SET skip_next_run = skip_next_run AND $3,
+                next_run_amount_override = CASE WHEN $3 THEN next_run_amount_override END,
+                next_run_date = CASE WHEN cadence = 'interval' THEN $2 ELSE next_run_date END,
+                updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(rule.id)
+        .bind(next_run_date)
+        .bind(in_closed_period)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        if let Some(transaction_id) = funded_transaction_id {
+            self.refresh_transaction_membership_after_commit(&transaction_id, &rule.user_id).await;
+        }
+
+        Ok(Some((status, !in_closed_period)))
+    }
+}
+
+/// Run dates of an interval rule, each counted from `starts_on` so monthly runs keep their day of the month
+/// after a shorter month.
+pub(crate) fn interval_run_dates(rule: &AllowanceFundingRule) -> impl Iterator<Item = NaiveDate> + '_ {
+    let interval = rule.interval_value.zip(rule.interval_unit);
+    (0_i32..).map_while(move |n| {
+        let (value, unit) = interval?;
+        add_duration(rule.starts_on, value.checked_mul(n)?, &unit)
+    })
+}
+
+fn due_interval_dates(rule: &AllowanceFundingRule, today: NaiveDate) -> Vec<NaiveDate> {
+    let Some(next_run_date) = rule.next_run_date else {
+        return Vec::new();
+    };
+
+    interval_run_dates(rule)
+        .skip_while(|date| *date < next_run_date)
+        .take_while(|date| *date <= today)
+        .take(MAX_CATCH_UP_RUNS)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::budget_period::DurationUnit;
+
+    fn interval_rule(next_run_date: NaiveDate, value: i32, unit: DurationUnit) -> AllowanceFundingRule {
+        AllowanceFundingRule {
+            id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            allowance_account_id: Uuid::new_v4(),
+            source_account_id: Uuid::new_v4(),
+            category_id: Uuid::new_v4(),
+            cadence: FundingCadence::Interval,
+            interval_value: Some(value),
+            interval_unit: Some(unit),
+            starts_on: next_run_date,
+            next_run_date: Some(next_run_date),
+            skip_next_run: false,
+            next_run_amount_override: None,
+            is_active: true,
+            next_transfer_amount: Some(5_000),
+        }
+    }
+
+    #[test]
+    fn due_interval_dates_catches_up_missed_runs() {
+        let rule = interval_rule(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(), 2, DurationUnit::Weeks);
+        let dates = due_interval_dates(&rule, NaiveDate::from_ymd_opt(2026, 1, 29).unwrap());
+
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 1, 15).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 1, 29).unwrap(),
+            ]
+        );
+    }
+
+    #[test]
+    fn monthly_run_dates_do_not_drift_after_short_months() {
+        let mut rule = interval_rule(NaiveDate::from_ymd_opt(2026, 1, 31).unwrap(), 1, DurationUnit::Months);
+        rule.next_run_date = NaiveDate::from_ymd_opt(2026, 2, 28);
+        let dates = due_interval_dates(&rule, NaiveDate::from_ymd_opt(2026, 4, 30).unwrap());
+
+        assert_eq!(
+            dates,
+            vec![
+                NaiveDate::from_ymd_opt(2026, 2, 28).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 3, 31).unwrap(),
+                NaiveDate::from_ymd_opt(2026, 4, 30).unwrap(),
+            ]
+        );
+        assert_eq!(
+            interval_run_dates(&rule).find(|date| *date > NaiveDate::from_ymd_opt(2026, 4, 30).unwrap()),
+            NaiveDate::from_ymd_opt(2026, 5, 31)
+        );
+    }
+
+    #[test]
+    fn due_interval_dates_empty_when_next_run_is_in_future() {
+        let rule = interval_rule(NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(), 1, DurationUnit::Months);
+        assert!(due_interval_dates(&rule, NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()).is_empty());
+    }
+
+    #[test]
+    fn next_run_amount_prefers_override() {
+        let mut rule = interval_rule(NaiveDate::from_ymd_opt(2026, 1, 1).unwrap(), 1, DurationUnit::Months);
+        assert_eq!(rule.next_run_amount(), Some(5_000));
+
+        rule.next_run_amount_override = Some(1_250);
+        assert_eq!(rule.next_run_amount(), Some(1_250));
+    }
+}
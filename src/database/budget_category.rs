@@ -174,12 +174,22 @@ impl PostgresRepository {
     }
 
     pub async fn update_budget_category_value(&self, id: &Uuid, new_budget_value: &i32, user_id: &Uuid) -> Result<(), AppError> {
-        sqlx::query("UPDATE budget_category SET budgeted_value = $1 WHERE id = $2 AND user_id = $3")
-            .bind(new_budget_value)
-            .bind(id)
-            .bind(user_id)
-            .execute(&self.pool)
-            .await?;
+        sqlx::query(
+            r#"
+            UPDATE budget_category
+            SET budgeted_value = $1,
+                target_income_basis_points = NULL,
+                target_income_source = NULL,
+                target_horizon_months = NULL,
+                target_due_month = NULL
+            WHERE id = $2 AND user_id = $3
+            "#,
+        )
+        .bind(new_budget_value)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
 
         Ok(())
     }
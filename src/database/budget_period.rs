@@ -1,11 +1,13 @@
 use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
 use crate::error::app_error::AppError;
 use crate::models::budget_period::{
-    AutoPeriodGenerationResponse, BudgetPeriod, BudgetPeriodRequest, BudgetPeriodWithMetrics, DurationUnit, GapsResponse, PeriodSchedule,
-    PeriodScheduleRequest, UnassignedTransaction, WeekendAdjustment,
+    AutoPeriodGenerationResponse, BudgetPeriod, BudgetPeriodRequest, BudgetPeriodWithMetrics, DurationUnit, FillGapsRequest, FillGapsResponse, GapsResponse,
+    HolidayAdjustment, PeriodGap, PeriodPreview, PeriodSchedule, PeriodScheduleRequest, ScheduleKind, UnassignedTransaction, WeekendAdjustment,
 };
+use crate::models::holiday_calendar::HolidayCalendar;
 use crate::models::pagination::CursorParams;
 use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use std::collections::HashSet;
 use uuid::Uuid;
 
 impl PostgresRepository {
@@ -61,7 +63,7 @@ impl PostgresRepository {
     pub async fn get_budget_period(&self, budget_period_id: &Uuid, user_id: &Uuid) -> Result<BudgetPeriod, AppError> {
         let budget_period = sqlx::query_as::<_, BudgetPeriod>(
             r#"
-            SELECT id, user_id, name, start_date, end_date, is_auto_generated, created_at
+            SELECT id, user_id, name, start_date, end_date, is_auto_generated, created_at, closed_at
             FROM budget_period
             WHERE id = $1 AND user_id = $2
             "#,
@@ -75,6 +77,15 @@ impl PostgresRepository {
     }
 
     pub async fn update_budget_period(&self, id: &Uuid, request: &BudgetPeriodRequest, user_id: &Uuid) -> Result<BudgetPeriod, AppError> {
+        let is_closed: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM budget_period WHERE id = $1 AND user_id = $2 AND closed_at IS NOT NULL)")
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if is_closed {
+            return Err(AppError::BadRequest("Reopen the budget period before changing it".to_string()));
+        }
+
         let name_exists: bool = sqlx::query_scalar(
             r#"
             SELECT EXISTS (
@@ -99,7 +110,7 @@ impl PostgresRepository {
             UPDATE budget_period
             SET name = $1, start_date = $2, end_date = $3
             WHERE id = $4 AND user_id = $5
-            RETURNING id, user_id, name, start_date, end_date, is_auto_generated, created_at
+            RETURNING id, user_id, name, start_date, end_date, is_auto_generated, created_at, closed_at
             "#,
         )
         .bind(&request.name)
@@ -122,7 +133,17 @@ impl PostgresRepository {
     }
 
     pub async fn delete_budget_period(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
-        sqlx::query("DELETE FROM budget_period WHERE id = $1 AND user_id = $2")
+        let is_closed: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM budget_period WHERE id = $1 AND user_id = $2 AND closed_at IS NOT NULL)")
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if is_closed {
+            return Err(AppError::BadRequest("Reopen the budget period before deleting it".to_string()));
+        }
+
+        // The closed_at condition guards against a close between the check and the delete
+        sqlx::query("DELETE FROM budget_period WHERE id = $1 AND user_id = $2 AND closed_at IS NULL")
             .bind(id)
             .bind(user_id)
             .execute(&self.pool)
@@ -137,21 +158,25 @@ impl PostgresRepository {
         let schedule = sqlx::query_as::<_, PeriodSchedule>(
             r#"
             INSERT INTO period_schedule (
-                user_id, start_day, duration_value, duration_unit,
-                saturday_adjustment, sunday_adjustment, name_pattern, generate_ahead
+                user_id, schedule_kind, start_day, second_day, anchor_date, duration_value, duration_unit,
+                saturday_adjustment, sunday_adjustment, holiday_adjustment, name_pattern, generate_ahead
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, user_id, start_day, duration_value, duration_unit,
-                      saturday_adjustment, sunday_adjustment, name_pattern,
-                      generate_ahead, created_at, updated_at
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING id, user_id, schedule_kind, start_day, second_day, anchor_date,
+                      duration_value, duration_unit, saturday_adjustment, sunday_adjustment,
+                      holiday_adjustment, name_pattern, generate_ahead, created_at, updated_at
             "#,
         )
         .bind(user_id)
+        .bind(request.schedule_kind)
         .bind(request.start_day)
+        .bind(request.second_day)
+        .bind(request.anchor_date)
         .bind(request.duration_value)
         .bind(request.duration_unit)
         .bind(request.saturday_adjustment)
         .bind(request.sunday_adjustment)
+        .bind(request.holiday_adjustment)
         .bind(&request.name_pattern)
         .bind(request.generate_ahead)
         .fetch_one(&self.pool)
@@ -163,9 +188,9 @@ impl PostgresRepository {
     pub async fn get_period_schedule(&self, user_id: &Uuid) -> Result<PeriodSchedule, AppError> {
         let schedule = sqlx::query_as::<_, PeriodSchedule>(
             r#"
-            SELECT id, user_id, start_day, duration_value, duration_unit,
-                   saturday_adjustment, sunday_adjustment, name_pattern,
-                   generate_ahead, created_at, updated_at
+            SELECT id, user_id, schedule_kind, start_day, second_day, anchor_date,
+                   duration_value, duration_unit, saturday_adjustment, sunday_adjustment,
+                   holiday_adjustment, name_pattern, generate_ahead, created_at, updated_at
             FROM period_schedule
             WHERE user_id = $1
             "#,
@@ -181,25 +206,33 @@ impl PostgresRepository {
         let schedule = sqlx::query_as::<_, PeriodSchedule>(
             r#"
             UPDATE period_schedule
-            SET start_day = $1,
-                duration_value = $2,
-                duration_unit = $3,
-                saturday_adjustment = $4,
-                sunday_adjustment = $5,
-                name_pattern = $6,
-                generate_ahead = $7,
+            SET schedule_kind = $1,
+                start_day = $2,
+                second_day = $3,
+                anchor_date = $4,
+                duration_value = $5,
+                duration_unit = $6,
+                saturday_adjustment = $7,
+                sunday_adjustment = $8,
+                holiday_adjustment = $9,
+                name_pattern = $10,
+                generate_ahead = $11,
                 updated_at = now()
-            WHERE user_id = $8
-            RETURNING id, user_id, start_day, duration_value, duration_unit,
-                      saturday_adjustment, sunday_adjustment, name_pattern,
-                      generate_ahead, created_at, updated_at
+            WHERE user_id = $12
+            RETURNING id, user_id, schedule_kind, start_day, second_day, anchor_date,
+                      duration_value, duration_unit, saturday_adjustment, sunday_adjustment,
+                      holiday_adjustment, name_pattern, generate_ahead, created_at, updated_at
             "#,
         )
+        .bind(request.schedule_kind)
         .bind(request.start_day)
+        .bind(request.second_day)
+        .bind(request.anchor_date)
         .bind(request.duration_value)
         .bind(request.duration_unit)
         .bind(request.saturday_adjustment)
         .bind(request.sunday_adjustment)
+        .bind(request.holiday_adjustment)
         .bind(&request.name_pattern)
         .bind(request.generate_ahead)
         .bind(user_id)
@@ -219,22 +252,11 @@ impl PostgresRepository {
     }
 
     pub async fn generate_automatic_budget_periods(&self) -> Result<AutoPeriodGenerationResponse, AppError> {
-        #[derive(sqlx::FromRow)]
-        struct ScheduleRow {
-            user_id: Uuid,
-            start_day: i32,
-            duration_value: i32,
-            duration_unit: DurationUnit,
-            saturday_adjustment: WeekendAdjustment,
-            sunday_adjustment: WeekendAdjustment,
-            name_pattern: String,
-            generate_ahead: i32,
-        }
-
-        let schedules = sqlx::query_as::<_, ScheduleRow>(
+        let schedules = sqlx::query_as::<_, PeriodSchedule>(
             r#"
-            SELECT user_id, start_day, duration_value, duration_unit,
-                   saturday_adjustment, sunday_adjustment, name_pattern, generate_ahead
+            SELECT id, user_id, schedule_kind, start_day, second_day, anchor_date,
+                   duration_value, duration_unit, saturday_adjustment, sunday_adjustment,
+                   holiday_adjustment, name_pattern, generate_ahead, created_at, updated_at
             FROM period_schedule
             "#,
         )
@@ -263,45 +285,11 @@ impl PostgresRepository {
                 continue;
             }
 
-            let max_end_date: Option<NaiveDate> = sqlx::query_scalar(
-                r#"
-                SELECT MAX(end_date)
-                FROM budget_period
-                WHERE user_id = $1
-                "#,
-            )
-            .bind(schedule.user_id)
-            .fetch_one(&self.pool)
-            .await?;
+            let max_end_date = self.latest_budget_period_end_date(&schedule.user_id).await?;
+            let holidays = self.get_holiday_calendar(&schedule.user_id).await?;
+            let periods = plan_schedule_periods(&ScheduleRule::from(schedule), &holidays, max_end_date, today, missing as usize)?;
 
-            let mut anchor_start = if let Some(end_date) = max_end_date {
-                end_date
-                    .checked_add_days(Days::new(1))
-                    .ok_or_else(|| AppError::BadRequest("Invalid end date when generating automatic periods".to_string()))?
-            } else {
-                compute_initial_anchor_start(
-                    today,
-                    schedule.start_day,
-                    schedule.duration_value,
-                    &schedule.duration_unit,
-                    schedule.saturday_adjustment,
-                    schedule.sunday_adjustment,
-                )
-                .ok_or_else(|| AppError::BadRequest("Unable to compute automatic period start from schedule".to_string()))?
-            };
-
-            for _ in 0..missing {
-                let start_date = apply_weekend_adjustment(anchor_start, schedule.saturday_adjustment, schedule.sunday_adjustment)
-                    .ok_or_else(|| AppError::BadRequest("Date overflow while applying weekend adjustment".to_string()))?;
-                let anchor_end_exclusive = add_duration(anchor_start, schedule.duration_value, &schedule.duration_unit)
-                    .ok_or_else(|| AppError::BadRequest("Date overflow while generating automatic period".to_string()))?;
-                let raw_end_date = anchor_end_exclusive
-                    .checked_sub_days(Days::new(1))
-                    .ok_or_else(|| AppError::BadRequest("Invalid period end date during automatic generation".to_string()))?;
-                let end_date = apply_weekend_adjustment(raw_end_date, schedule.saturday_adjustment, schedule.sunday_adjustment)
-                    .ok_or_else(|| AppError::BadRequest("Date overflow while applying weekend adjustment".to_string()))?;
-
-                let generated_name = render_period_name(&schedule.name_pattern, start_date, end_date);
+            for period in &periods {
                 let insert_result = sqlx::query_scalar::<_, Uuid>(
                     r#"
                     INSERT INTO budget_period (user_id, name, start_date, end_date, is_auto_generated)
@@ -310,9 +298,9 @@ impl PostgresRepository {
                     "#,
                 )
                 .bind(schedule.user_id)
-                .bind(&generated_name)
-                .bind(start_date)
-                .bind(end_date)
+                .bind(&period.name)
+                .bind(period.start_date)
+                .bind(period.end_date)
                 .fetch_one(&self.pool)
                 .await;
 
@@ -321,7 +309,7 @@ impl PostgresRepository {
                         periods_created += 1;
                     }
                     Err(err) if is_unique_violation(&err) => {
-                        let fallback_name = format!("{} ({})", generated_name, start_date.format("%Y-%m-%d"));
+                        let fallback_name = format!("{} ({})", period.name, period.start_date.format("%Y-%m-%d"));
                         let fallback_insert = sqlx::query_scalar::<_, Uuid>(
                             r#"
                             INSERT INTO budget_period (user_id, name, start_date, end_date, is_auto_generated)
@@ -331,8 +319,8 @@ impl PostgresRepository {
                         )
                         .bind(schedule.user_id)
                         .bind(fallback_name)
-                        .bind(start_date)
-                        .bind(end_date)
+                        .bind(period.start_date)
+                        .bind(period.end_date)
                         .fetch_one(&self.pool)
                         .await;
 
@@ -344,8 +332,6 @@ impl PostgresRepository {
                     }
                     Err(err) => return Err(err.into()),
                 }
-
-                anchor_start = anchor_end_exclusive;
             }
         }
 
@@ -355,6 +341,29 @@ impl PostgresRepository {
         })
     }
 
+    /// The next `count` periods `request` would generate after the user's existing periods, without saving anything.
+    pub async fn preview_period_schedule(&self, request: &PeriodScheduleRequest, count: usize, user_id: &Uuid) -> Result<Vec<PeriodPreview>, AppError> {
+        let today = chrono::Utc::now().date_naive();
+        let max_end_date = self.latest_budget_period_end_date(user_id).await?;
+        let holidays = self.get_holiday_calendar(user_id).await?;
+        plan_schedule_periods(&ScheduleRule::from(request), &holidays, max_end_date, today, count)
+    }
+
+    async fn latest_budget_period_end_date(&self, user_id: &Uuid) -> Result<Option<NaiveDate>, AppError> {
+        let max_end_date: Option<NaiveDate> = sqlx::query_scalar(
+            r#"
+            SELECT MAX(end_date)
+            FROM budget_period
+            WHERE user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(max_end_date)
+    }
+
     // ===== Budget Period with Metrics =====
 
     pub async fn list_budget_periods_with_metrics(&self, params: &CursorParams, user_id: &Uuid) -> Result<Vec<BudgetPeriodWithMetrics>, AppError> {
@@ -367,6 +376,7 @@ impl PostgresRepository {
             end_date: chrono::NaiveDate,
             is_auto_generated: bool,
             created_at: chrono::DateTime<chrono::Utc>,
+            closed_at: Option<chrono::DateTime<chrono::Utc>>,
             transaction_count: Option<i64>,
             total_spent: Option<i64>,
             total_budgeted: Option<i64>,
@@ -383,20 +393,26 @@ impl PostgresRepository {
                     bp.end_date,
                     bp.is_auto_generated,
                     bp.created_at,
-                    COUNT(DISTINCT t.id) as transaction_count,
-                    COALESCE(SUM(CASE WHEN c.category_type = 'Outgoing' THEN t.amount ELSE 0 END), 0)::INT8 as total_spent,
-                    COALESCE(SUM(bc.budgeted_value), 0) as total_budgeted
+                    bp.closed_at,
+                    COALESCE(s.transaction_count, COUNT(DISTINCT t.id)) as transaction_count,
+                    COALESCE(s.total_spent, COALESCE(SUM(CASE WHEN c.category_type = 'Outgoing' THEN t.amount ELSE 0 END), 0))::INT8 as total_spent,
+                    COALESCE(s.total_budgeted, (
+                        SELECT COALESCE(SUM(budget_category_target_for_period(bc.id, bp.id, bp.start_date)), 0)
+                        FROM budget_category bc
+                        WHERE bc.user_id = bp.user_id
+                    ))::INT8 as total_budgeted
                 FROM budget_period bp
+                LEFT JOIN budget_period_snapshot s ON s.period_id = bp.id
                 LEFT JOIN transaction t ON t.user_id = bp.user_id
                     AND t.occurred_at >= bp.start_date
                     AND t.occurred_at <= bp.end_date
                 LEFT JOIN category c ON t.category_id = c.id
-                LEFT JOIN budget_category bc ON bc.user_id = bp.user_id
                 WHERE bp.user_id = $1
                     AND (bp.start_date, bp.id) > (
                         SELECT start_date, id FROM budget_period WHERE id = $2
                     )
-                GROUP BY bp.id, bp.user_id, bp.name, bp.start_date, bp.end_date, bp.is_auto_generated, bp.created_at
+                GROUP BY bp.id, bp.user_id, bp.name, bp.start_date, bp.end_date, bp.is_auto_generated, bp.created_at, bp.closed_at,
+                    s.transaction_count, s.total_spent, s.total_budgeted
                 ORDER BY bp.start_date ASC, bp.id ASC
                 LIMIT $3
                 "#,
@@ -417,17 +433,23 @@ impl PostgresRepository {
                     bp.end_date,
                     bp.is_auto_generated,
                     bp.created_at,
-                    COUNT(DISTINCT t.id) as transaction_count,
-                    COALESCE(SUM(CASE WHEN c.category_type = 'Outgoing' THEN t.amount ELSE 0 END), 0)::INT8 as total_spent,
-                    COALESCE(SUM(bc.budgeted_value), 0) as total_budgeted
+                    bp.closed_at,
+                    COALESCE(s.transaction_count, COUNT(DISTINCT t.id)) as transaction_count,
+                    COALESCE(s.total_spent, COALESCE(SUM(CASE WHEN c.category_type = 'Outgoing' THEN t.amount ELSE 0 END), 0))::INT8 as total_spent,
+                    COALESCE(s.total_budgeted, (
+                        SELECT COALESCE(SUM(budget_category_target_for_period(bc.id, bp.id, bp.start_date)), 0)
+                        FROM budget_category bc
+                        WHERE bc.user_id = bp.user_id
+                    ))::INT8 as total_budgeted
                 FROM budget_period bp
+                LEFT JOIN budget_period_snapshot s ON s.period_id = bp.id
                 LEFT JOIN transaction t ON t.user_id = bp.user_id
                     AND t.occurred_at >= bp.start_date
                     AND t.occurred_at <= bp.end_date
                 LEFT JOIN category c ON t.category_id = c.id
-                LEFT JOIN budget_category bc ON bc.user_id = bp.user_id
                 WHERE bp.user_id = $1
-                GROUP BY bp.id, bp.user_id, bp.name, bp.start_date, bp.end_date, bp.is_auto_generated, bp.created_at
+                GROUP BY bp.id, bp.user_id, bp.name, bp.start_date, bp.end_date, bp.is_auto_generated, bp.created_at, bp.closed_at,
+                    s.transaction_count, s.total_spent, s.total_budgeted
                 ORDER BY bp.start_date ASC, bp.id ASC
                 LIMIT $2
                 "#,
@@ -458,6 +480,7 @@ impl PostgresRepository {
                         end_date: row.end_date,
                         is_auto_generated: row.is_auto_generated,
                         created_at: row.created_at,
+                        closed_at: row.closed_at,
                     },
                     transaction_count: row.transaction_count.unwrap_or(0),
                     budget_used_percentage,
@@ -478,6 +501,7 @@ impl PostgresRepository {
             end_date: chrono::NaiveDate,
             is_auto_generated: bool,
             created_at: chrono::DateTime<chrono::Utc>,
+            closed_at: Option<chrono::DateTime<chrono::Utc>>,
             transaction_count: Option<i64>,
             total_spent: Option<i64>,
             total_budgeted: Option<i64>,
@@ -493,19 +517,25 @@ impl PostgresRepository {
                 bp.end_date,
                 bp.is_auto_generated,
                 bp.created_at,
-                COUNT(DISTINCT t.id) as transaction_count,
-                COALESCE(SUM(CASE WHEN c.category_type = 'Outgoing' THEN t.amount ELSE 0 END), 0)::INT8 as total_spent,
-                COALESCE(SUM(bc.budgeted_value), 0) as total_budgeted
+                bp.closed_at,
+                COALESCE(s.transaction_count, COUNT(DISTINCT t.id)) as transaction_count,
+                COALESCE(s.total_spent, COALESCE(SUM(CASE WHEN c.category_type = 'Outgoing' THEN t.amount ELSE 0 END), 0))::INT8 as total_spent,
+                COALESCE(s.total_budgeted, (
+                    SELECT COALESCE(SUM(budget_category_target_for_period(bc.id, bp.id, bp.start_date)), 0)
+                    FROM budget_category bc
+                    WHERE bc.user_id = bp.user_id
+                ))::INT8 as total_budgeted
             FROM budget_period bp
+            LEFT JOIN budget_period_snapshot s ON s.period_id = bp.id
             LEFT JOIN transaction t ON t.user_id = bp.user_id
                 AND t.occurred_at >= bp.start_date
                 AND t.occurred_at <= bp.end_date
             LEFT JOIN category c ON t.category_id = c.id
-            LEFT JOIN budget_category bc ON bc.user_id = bp.user_id
             WHERE bp.user_id = $1
                 AND bp.start_date <= now()
                 AND bp.end_date >= now()
-            GROUP BY bp.id, bp.user_id, bp.name, bp.start_date, bp.end_date, bp.is_auto_generated, bp.created_at
+            GROUP BY bp.id, bp.user_id, bp.name, bp.start_date, bp.end_date, bp.is_auto_generated, bp.created_at, bp.closed_at,
+                s.transaction_count, s.total_spent, s.total_budgeted
             "#,
         )
         .bind(user_id)
@@ -529,6 +559,7 @@ impl PostgresRepository {
                 end_date: row.end_date,
                 is_auto_generated: row.is_auto_generated,
                 created_at: row.created_at,
+                closed_at: row.closed_at,
             },
             transaction_count: row.transaction_count.unwrap_or(0),
             budget_used_percentage,
@@ -546,6 +577,8 @@ impl PostgresRepository {
             amount: i64,
         }
 
+        let (periods, unassigned_days) = self.load_gap_inputs(user_id).await?;
+
         let transactions = sqlx::query_as::<_, UnassignedTransactionRow>(
             r#"
             SELECT t.id, t.occurred_at, t.description, t.amount
@@ -566,7 +599,6 @@ impl PostgresRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        let unassigned_count = transactions.len() as i64;
         let transactions = transactions
             .into_iter()
             .map(|row| UnassignedTransaction {
@@ -578,10 +610,580 @@ impl PostgresRepository {
             .collect();
 
         Ok(GapsResponse {
-            unassigned_count,
+            unassigned_count: unassigned_days.iter().map(|day| day.transaction_count).sum(),
+            gaps: find_period_gaps(&periods, &unassigned_days).iter().map(|gap| gap.gap.clone()).collect(),
             transactions,
         })
     }
+
+    /// The fewest periods that cover every unassigned transaction, following the user's period schedule unless
+    /// the request asks for a fixed duration. Nothing is saved.
+    pub async fn preview_gap_fill(&self, request: &FillGapsRequest, user_id: &Uuid) -> Result<FillGapsResponse, AppError> {
+        let (periods, unassigned_days) = self.load_gap_inputs(user_id).await?;
+        let gaps = find_period_gaps(&periods, &unassigned_days);
+        if gaps.is_empty() {
+            return Ok(FillGapsResponse {
+                periods: Vec::new(),
+                covered_transaction_count: 0,
+            });
+        }
+
+        let schedule = if request.duration_value.is_some() {
+            None
+        } else {
+            match self.get_period_schedule(user_id).await {
+                Ok(schedule) => Some(schedule),
+                Err(AppError::NotFound(_)) => {
+                    return Err(AppError::BadRequest(
+                        "No period schedule configured; pass duration_value and duration_unit".to_string(),
+                    ));
+                }
+                Err(err) => return Err(err),
+            }
+        };
+        let holidays = self.get_holiday_calendar(user_id).await?;
+
+        let name_pattern = request
+            .name_pattern
+            .as_deref()
+            .or(schedule.as_ref().map(|schedule| schedule.name_pattern.as_str()))
+            .unwrap_or(GAP_PERIOD_NAME_PATTERN);
+        let rule_for_gap = |first_date: NaiveDate| match (&schedule, request.duration_value, request.duration_unit) {
+            (_, Some(duration_value), Some(duration_unit)) => Ok(ScheduleRule {
+                kind: ScheduleKind::Anchored,
+                start_day: 1,
+                second_day: None,
+                anchor_date: Some(first_date),
+                duration_value,
+                duration_unit,
+                saturday_adjustment: WeekendAdjustment::Keep,
+                sunday_adjustment: WeekendAdjustment::Keep,
+                holiday_adjustment: HolidayAdjustment::Keep,
+                name_pattern,
+            }),
+            (Some(schedule), _, _) => Ok(ScheduleRule {
+                name_pattern,
+                ..ScheduleRule::from(schedule)
+            }),
+            (None, _, _) => Err(AppError::BadRequest("duration_value and duration_unit must be passed together".to_string())),
+        };
+
+        let mut planned = plan_gap_periods(&gaps, &unassigned_days, &holidays, rule_for_gap)?;
+
+        let mut taken_names: HashSet<String> = sqlx::query_scalar("SELECT name FROM budget_period WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .collect();
+        for period in &mut planned {
+            if taken_names.contains(&period.name) {
+                period.name = format!("{} ({})", period.name, period.start_date.format("%Y-%m-%d"));
+            }
+            if !taken_names.insert(period.name.clone()) {
+                return Err(AppError::BadRequest(format!("Budget period name {} already exists", period.name)));
+            }
+        }
+
+        Ok(FillGapsResponse {
+            periods: planned,
+            covered_transaction_count: unassigned_days.iter().map(|day| day.transaction_count).sum(),
+        })
+    }
+
+    /// Create the periods `preview_gap_fill` plans, all or none.
+    pub async fn fill_period_gaps(&self, request: &FillGapsRequest, user_id: &Uuid) -> Result<FillGapsResponse, AppError> {
+        let plan = self.preview_gap_fill(request, user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        for period in &plan.periods {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO budget_period (user_id, name, start_date, end_date)
+                SELECT $1, $2, $3, $4
+                WHERE NOT EXISTS (
+                    SELECT 1
+                    FROM budget_period
+                    WHERE user_id = $1 AND start_date <= $4 AND end_date >= $3
+                )
+                "#,
+            )
+            .bind(user_id)
+            .bind(&period.name)
+            .bind(period.start_date)
+            .bind(period.end_date)
+            .execute(&mut *tx)
+            .await;
+
+            match result {
+                Ok(result) if result.rows_affected() == 1 => {}
+                Ok(_) => return Err(AppError::BadRequest("Budget periods changed while filling gaps; try again".to_string())),
+                Err(err) if is_unique_violation(&err) => {
+                    return Err(AppError::BadRequest(format!("Budget period name {} already exists", period.name)));
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+        tx.commit().await?;
+
+        Ok(plan)
+    }
+
+    async fn load_gap_inputs(&self, user_id: &Uuid) -> Result<(Vec<(NaiveDate, NaiveDate)>, Vec<UnassignedDay>), AppError> {
+        let periods = sqlx::query_as::<_, (NaiveDate, NaiveDate)>("SELECT start_date, end_date FROM budget_period WHERE user_id = $1 ORDER BY start_date")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let unassigned_days = sqlx::query_as::<_, UnassignedDay>(
+            r#"
+            SELECT t.occurred_at AS date, COUNT(*) AS transaction_count
+            FROM transaction t
+            WHERE t.user_id = $1
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM budget_period bp
+                    WHERE bp.user_id = t.user_id
+                        AND t.occurred_at >= bp.start_date
+                        AND t.occurred_at <= bp.end_date
+                )
+            GROUP BY t.occurred_at
+            ORDER BY t.occurred_at
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok((periods, unassigned_days))
+    }
+}
+
+/// Name of gap-filling periods when neither the request nor a schedule gives one.
+const GAP_PERIOD_NAME_PATTERN: &str = "{start_date} - {end_date}";
+
+/// A single gap fill may create at most this many periods.
+const MAX_GAP_FILL_PERIODS: usize = 500;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct UnassignedDay {
+    date: NaiveDate,
+    transaction_count: i64,
+}
+
+/// A reported gap together with the neighbouring periods that bound it, if any.
+#[derive(Debug, Clone)]
+struct BoundedGap {
+    gap: PeriodGap,
+    /// Day after the previous period ends
+    lower: Option<NaiveDate>,
+    /// Day before the next period starts
+    upper: Option<NaiveDate>,
+}
+
+/// Group unassigned days by the uncovered span they fall in. `periods` are sorted by start date and
+/// `unassigned_days` ascending; no unassigned day lies inside a period.
+fn find_period_gaps(periods: &[(NaiveDate, NaiveDate)], unassigned_days: &[UnassignedDay]) -> Vec<BoundedGap> {
+    let mut gaps: Vec<BoundedGap> = Vec::new();
+
+    for day in unassigned_days {
+        let lower = periods
+            .iter()
+            .filter(|(_, end)| *end < day.date)
+            .map(|(_, end)| *end)
+            .max()
+            .and_then(|end| end.succ_opt());
+        let upper = periods
+            .iter()
+            .filter(|(start, _)| *start > day.date)
+            .map(|(start, _)| *start)
+            .min()
+            .and_then(|start| start.pred_opt());
+
+        match gaps.last_mut() {
+            Some(gap) if gap.lower == lower && gap.upper == upper => {
+                gap.gap.transaction_count += day.transaction_count;
+                if upper.is_none() {
+                    gap.gap.end_date = day.date;
+                }
+            }
+            _ => gaps.push(BoundedGap {
+                gap: PeriodGap {
+                    start_date: lower.unwrap_or(day.date),
+                    end_date: upper.unwrap_or(day.date),
+                    transaction_count: day.transaction_count,
+                },
+                lower,
+                upper,
+            }),
+        }
+    }
+
+    gaps
+}
+
+/// Lay the schedule over each gap and keep the periods that hold unassigned days, trimmed to the gap's bounds.
+/// A day the schedule leaves uncovered, e.g. between a weekend-adjusted end and the next start, joins the period
+/// before it.
+fn plan_gap_periods<'a>(
+    gaps: &[BoundedGap],
+    unassigned_days: &[UnassignedDay],
+    holidays: &HolidayCalendar,
+    rule_for_gap: impl Fn(NaiveDate) -> Result<ScheduleRule<'a>, AppError>,
+) -> Result<Vec<PeriodPreview>, AppError> {
+    let mut planned: Vec<PeriodPreview> = Vec::new();
+
+    for gap in gaps {
+        let dates: Vec<NaiveDate> = unassigned_days
+            .iter()
+            .map(|day| day.date)
+            .filter(|date| *date >= gap.gap.start_date && *date <= gap.gap.end_date)
+            .collect();
+        let (Some(first), Some(last)) = (dates.first().copied(), dates.last().copied()) else {
+            continue;
+        };
+
+        let rule = rule_for_gap(first)?;
+        let mut count = 4;
+        let grid = loop {
+            let grid = plan_schedule_periods(&rule, holidays, None, first, count)?;
+            if grid.last().is_some_and(|period| period.end_date >= last) {
+                break grid;
+            }
+            if count >= MAX_GAP_FILL_PERIODS {
+                return Err(AppError::BadRequest(format!(
+                    "Filling gaps would need more than {MAX_GAP_FILL_PERIODS} periods"
+                )));
+            }
+            count = (count * 2).min(MAX_GAP_FILL_PERIODS);
+        };
+
+        let mut used: Vec<(usize, PeriodPreview)> = Vec::new();
+        for date in dates {
+            let index = grid.iter().rposition(|period| period.start_date <= date).unwrap_or(0);
+            match used.last_mut() {
+                Some((used_index, period)) if *used_index == index => period.end_date = period.end_date.max(date),
+                _ => {
+                    let mut period = grid[index].clone();
+                    period.start_date = period.start_date.min(date);
+                    period.end_date = period.end_date.max(date);
+                    used.push((index, period));
+                }
+            }
+        }
+
+        for (_, mut period) in used {
+            if let Some(lower) = gap.lower {
+                period.start_date = period.start_date.max(lower);
+            }
+            if let Some(upper) = gap.upper {
+                period.end_date = period.end_date.min(upper);
+            }
+            if let Some(previous) = planned.last()
+                && period.start_date <= previous.end_date
+            {
+                period.start_date = previous.end_date.succ_opt().unwrap_or(period.start_date);
+            }
+            period.name = render_period_name(rule.name_pattern, period.start_date, period.end_date);
+            planned.push(period);
+        }
+
+        if planned.len() > MAX_GAP_FILL_PERIODS {
+            return Err(AppError::BadRequest(format!(
+                "Filling gaps would need more than {MAX_GAP_FILL_PERIODS} periods"
+            )));
+        }
+    }
+
+    Ok(planned)
+}
+
+/// The fields of a schedule that decide where its periods fall.
+#[derive(Clone, Copy)]
+struct ScheduleRule<'a> {
+    kind: ScheduleKind,
+    start_day: i32,
+    second_day: Option<i32>,
+    anchor_date: Option<NaiveDate>,
+    duration_value: i32,
+    duration_unit: DurationUnit,
+    saturday_adjustment: WeekendAdjustment,
+    sunday_adjustment: WeekendAdjustment,
+    holiday_adjustment: HolidayAdjustment,
+    name_pattern: &'a str,
+}
+
+impl<'a> From<&'a PeriodSchedule> for ScheduleRule<'a> {
+    fn from(schedule: &'a PeriodSchedule) -> Self {
+        Self {
+            kind: schedule.schedule_kind,
+            start_day: schedule.start_day,
+            second_day: schedule.second_day,
+            anchor_date: schedule.anchor_date,
+            duration_value: schedule.duration_value,
+            duration_unit: schedule.duration_unit,
+            saturday_adjustment: schedule.saturday_adjustment,
+            sunday_adjustment: schedule.sunday_adjustment,
+            holiday_adjustment: schedule.holiday_adjustment,
+            name_pattern: &schedule.name_pattern,
+        }
+    }
+}
+
+impl<'a> From<&'a PeriodScheduleRequest> for ScheduleRule<'a> {
+    fn from(request: &'a PeriodScheduleRequest) -> Self {
+        Self {
+            kind: request.schedule_kind,
+            start_day: request.start_day,
+            second_day: request.second_day,
+            anchor_date: request.anchor_date,
+            duration_value: request.duration_value,
+            duration_unit: request.duration_unit,
+            saturday_adjustment: request.saturday_adjustment,
+            sunday_adjustment: request.sunday_adjustment,
+            holiday_adjustment: request.holiday_adjustment,
+            name_pattern: &request.name_pattern,
+        }
+    }
+}
+
+/// Plan the next `count` periods of a schedule, continuing after `last_end_date` when the user already has periods,
+/// or from the period covering `today` otherwise. Boundaries avoid the non-business days of `holidays`.
+fn plan_schedule_periods(
+    rule: &ScheduleRule,
+    holidays: &HolidayCalendar,
+    last_end_date: Option<NaiveDate>,
+    today: NaiveDate,
+    count: usize,
+) -> Result<Vec<PeriodPreview>, AppError> {
+    match rule.kind {
+        ScheduleKind::Interval => plan_interval_periods(rule, holidays, last_end_date, today, count),
+        _ => plan_boundary_periods(rule, holidays, last_end_date, today, count)
+            .ok_or_else(|| AppError::BadRequest("Unable to compute automatic periods from schedule".to_string())),
+    }
+}
+
+fn plan_interval_periods(
+    rule: &ScheduleRule,
+    holidays: &HolidayCalendar,
+    last_end_date: Option<NaiveDate>,
+    today: NaiveDate,
+    count: usize,
+) -> Result<Vec<PeriodPreview>, AppError> {
+    let mut anchor_start = if let Some(end_date) = last_end_date {
+        end_date
+            .checked_add_days(Days::new(1))
+            .ok_or_else(|| AppError::BadRequest("Invalid end date when generating automatic periods".to_string()))?
+    } else {
+        compute_initial_anchor_start(
+            today,
+            rule.start_day,
+            rule.duration_value,
+            &rule.duration_unit,
+            rule.saturday_adjustment,
+            rule.sunday_adjustment,
+        )
+        .ok_or_else(|| AppError::BadRequest("Unable to compute automatic period start from schedule".to_string()))?
+    };
+
+    let mut periods = Vec::with_capacity(count);
+    for _ in 0..count {
+        let start_date = apply_business_day_adjustment(anchor_start, rule, holidays)
+            .ok_or_else(|| AppError::BadRequest("Date overflow while applying weekend adjustment".to_string()))?;
+        let anchor_end_exclusive = add_duration(anchor_start, rule.duration_value, &rule.duration_unit)
+            .ok_or_else(|| AppError::BadRequest("Date overflow while generating automatic period".to_string()))?;
+        let raw_end_date = anchor_end_exclusive
+            .checked_sub_days(Days::new(1))
+            .ok_or_else(|| AppError::BadRequest("Invalid period end date during automatic generation".to_string()))?;
+        let end_date = apply_business_day_adjustment(raw_end_date, rule, holidays)
+            .ok_or_else(|| AppError::BadRequest("Date overflow while applying weekend adjustment".to_string()))?;
+
+        periods.push(PeriodPreview {
+            name: render_period_name(rule.name_pattern, start_date, end_date),
+            start_date,
+            end_date,
+        });
+        anchor_start = anchor_end_exclusive;
+    }
+
+    Ok(periods)
+}
+
+/// Schedules whose periods start on fixed boundaries run from one (adjusted) boundary to the day before the next,
+/// so consecutive periods never overlap or leave gaps.
+fn plan_boundary_periods(
+    rule: &ScheduleRule,
+    holidays: &HolidayCalendar,
+    last_end_date: Option<NaiveDate>,
+    today: NaiveDate,
+    count: usize,
+) -> Option<Vec<PeriodPreview>> {
+    let mut boundary = match last_end_date {
+        Some(end_date) => {
+            // Adjustment moves a boundary by a few days at most, even across a long weekend
+            let mut boundary = boundary_on_or_before(rule, holidays, end_date.checked_sub_days(Days::new(14))?)?;
+            while adjusted_boundary(rule, holidays, boundary)? <= end_date {
+                boundary = next_boundary(rule, holidays, boundary)?;
+            }
+            boundary
+        }
+        None => {
+            let boundary = boundary_on_or_before(rule, holidays, today)?;
+            if boundary <= today && adjusted_boundary(rule, holidays, boundary)? > today {
+                boundary_on_or_before(rule, holidays, boundary.checked_sub_days(Days::new(1))?)?
+            } else {
+                boundary
+            }
+        }
+    };
+
+    let mut periods = Vec::with_capacity(count);
+    for _ in 0..count {
+        let next = next_boundary(rule, holidays, boundary)?;
+        let start_date = adjusted_boundary(rule, holidays, boundary)?;
+        let end_date = adjusted_boundary(rule, holidays, next)?.checked_sub_days(Days::new(1))?;
+
+        periods.push(PeriodPreview {
+            name: render_period_name(rule.name_pattern, start_date, end_date),
+            start_date,
+            end_date,
+        });
+        boundary = next;
+    }
+
+    Some(periods)
+}
+
+fn adjusted_boundary(rule: &ScheduleRule, holidays: &HolidayCalendar, boundary: NaiveDate) -> Option<NaiveDate> {
+    match rule.kind {
+        ScheduleKind::LastBusinessDay => Some(boundary),
+        _ => apply_business_day_adjustment(boundary, rule, holidays),
+    }
+}
+
+/// Move a boundary off a weekend or weekday holiday as the schedule asks. A shifted boundary keeps moving in the
+/// same direction until it reaches a business day, so a Saturday moved to Friday skips a Friday holiday too.
+fn apply_business_day_adjustment(date: NaiveDate, rule: &ScheduleRule, holidays: &HolidayCalendar) -> Option<NaiveDate> {
+    let move_forward = match date.weekday() {
+        Weekday::Sat => weekend_direction(rule.saturday_adjustment),
+        Weekday::Sun => weekend_direction(rule.sunday_adjustment),
+        _ if holidays.is_holiday(date) => match rule.holiday_adjustment {
+            HolidayAdjustment::Keep => None,
+            HolidayAdjustment::Previous => Some(false),
+            HolidayAdjustment::Next => Some(true),
+        },
+        _ => None,
+    };
+
+    match move_forward {
+        None => Some(date),
+        Some(true) => holidays.next_business_day(date),
+        Some(false) => holidays.previous_business_day(date),
+    }
+}
+
+fn weekend_direction(adjustment: WeekendAdjustment) -> Option<bool> {
+    match adjustment {
+        WeekendAdjustment::Keep => None,
+        WeekendAdjustment::Friday => Some(false),
+        WeekendAdjustment::Monday => Some(true),
+    }
+}
+
+/// The latest unadjusted boundary on or before `date`; anchored schedules return their anchor while it lies ahead.
+fn boundary_on_or_before(rule: &ScheduleRule, holidays: &HolidayCalendar, date: NaiveDate) -> Option<NaiveDate> {
+    match rule.kind {
+        ScheduleKind::SemiMonthly => {
+            let previous_month = month_start(date)?.checked_sub_months(Months::new(1))?;
+            let second_day = rule.second_day?;
+            [
+                base_month_start_date(date.year(), date.month(), second_day)?,
+                base_month_start_date(date.year(), date.month(), rule.start_day)?,
+                base_month_start_date(previous_month.year(), previous_month.month(), second_day)?,
+            ]
+            .into_iter()
+            .find(|boundary| *boundary <= date)
+        }
+        ScheduleKind::Anchored => {
+            let anchor = rule.anchor_date?;
+            let mut steps = 0_i32;
+            while anchored_boundary(rule, anchor, steps + 1)? <= date {
+                steps += 1;
+                if steps > MAX_ANCHORED_STEPS {
+                    return None;
+                }
+            }
+            anchored_boundary(rule, anchor, steps)
+        }
+        ScheduleKind::LastBusinessDay => {
+            let this_month = last_business_day(holidays, date.year(), date.month())?;
+            if this_month <= date {
+                Some(this_month)
+            } else {
+                let previous_month = month_start(date)?.checked_sub_months(Months::new(1))?;
+                last_business_day(holidays, previous_month.year(), previous_month.month())
+            }
+        }
+        ScheduleKind::Interval => None,
+    }
+}
+
+/// The first unadjusted boundary strictly after `date`.
+fn next_boundary(rule: &ScheduleRule, holidays: &HolidayCalendar, date: NaiveDate) -> Option<NaiveDate> {
+    match rule.kind {
+        ScheduleKind::SemiMonthly => {
+            let next_month = month_start(date)?.checked_add_months(Months::new(1))?;
+            [
+                base_month_start_date(date.year(), date.month(), rule.start_day)?,
+                base_month_start_date(date.year(), date.month(), rule.second_day?)?,
+                base_month_start_date(next_month.year(), next_month.month(), rule.start_day)?,
+            ]
+            .into_iter()
+            .find(|boundary| *boundary > date)
+        }
+        ScheduleKind::Anchored => {
+            let anchor = rule.anchor_date?;
+            let mut steps = 0_i32;
+            loop {
+                let boundary = anchored_boundary(rule, anchor, steps)?;
+                if boundary > date {
+                    return Some(boundary);
+                }
+                steps += 1;
+                if steps > MAX_ANCHORED_STEPS {
+                    return None;
+                }
+            }
+        }
+        ScheduleKind::LastBusinessDay => {
+            let this_month = last_business_day(holidays, date.year(), date.month())?;
+            if this_month > date {
+                Some(this_month)
+            } else {
+                let next_month = month_start(date)?.checked_add_months(Months::new(1))?;
+                last_business_day(holidays, next_month.year(), next_month.month())
+            }
+        }
+        ScheduleKind::Interval => None,
+    }
+}
+
+const MAX_ANCHORED_STEPS: i32 = 10_000;
+
+/// Anchored boundaries are counted from the anchor itself, so month-based steps keep the anchor's day of month.
+fn anchored_boundary(rule: &ScheduleRule, anchor: NaiveDate, steps: i32) -> Option<NaiveDate> {
+    add_duration(anchor, rule.duration_value.checked_mul(steps)?, &rule.duration_unit)
+}
+
+fn month_start(date: NaiveDate) -> Option<NaiveDate> {
+    NaiveDate::from_ymd_opt(date.year(), date.month(), 1)
+}
+
+fn last_business_day(holidays: &HolidayCalendar, year: i32, month: u32) -> Option<NaiveDate> {
+    let last_day = NaiveDate::from_ymd_opt(year, month, 1)?
+        .checked_add_months(Months::new(1))?
+        .checked_sub_days(Days::new(1))?;
+    holidays.previous_business_day(last_day)
 }
 
 fn compute_initial_anchor_start(
@@ -607,7 +1209,7 @@ fn compute_initial_anchor_start(
     Some(candidate)
 }
 
-fn add_duration(date: NaiveDate, duration_value: i32, duration_unit: &DurationUnit) -> Option<NaiveDate> {
+pub(crate) fn add_duration(date: NaiveDate, duration_value: i32, duration_unit: &DurationUnit) -> Option<NaiveDate> {
     match duration_unit {
         DurationUnit::Days => date.checked_add_days(Days::new(duration_value as u64)),
         DurationUnit::Weeks => date.checked_add_days(Days::new((duration_value * 7) as u64)),
@@ -667,8 +1269,9 @@ fn render_period_name(pattern: &str, start_date: NaiveDate, end_date: NaiveDate)
 
 #[cfg(test)]
 mod tests {
-    use super::{apply_weekend_adjustment, render_period_name};
-    use crate::models::budget_period::WeekendAdjustment;
+    use super::{ScheduleRule, UnassignedDay, apply_weekend_adjustment, find_period_gaps, plan_gap_periods, plan_schedule_periods, render_period_name};
+    use crate::models::budget_period::{DurationUnit, HolidayAdjustment, PeriodGap, PeriodPreview, ScheduleKind, WeekendAdjustment};
+    use crate::models::holiday_calendar::{HolidayCalendar, HolidayRule};
     use chrono::NaiveDate;
 
     #[test]
@@ -693,4 +1296,207 @@ mod tests {
         let plain = render_period_name("Period", start, end);
         assert_eq!(plain, "Period 2026-02-01");
     }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    fn rule(kind: ScheduleKind) -> ScheduleRule<'static> {
+        ScheduleRule {
+            kind,
+            start_day: 1,
+            second_day: None,
+            anchor_date: None,
+            duration_value: 1,
+            duration_unit: DurationUnit::Months,
+            saturday_adjustment: WeekendAdjustment::Keep,
+            sunday_adjustment: WeekendAdjustment::Keep,
+            holiday_adjustment: HolidayAdjustment::Keep,
+            name_pattern: "{start_date}",
+        }
+    }
+
+    fn bounds(periods: &[PeriodPreview]) -> Vec<(NaiveDate, NaiveDate)> {
+        periods.iter().map(|period| (period.start_date, period.end_date)).collect()
+    }
+
+    #[test]
+    fn semi_monthly_periods_split_each_month() {
+        let schedule = ScheduleRule {
+            second_day: Some(15),
+            ..rule(ScheduleKind::SemiMonthly)
+        };
+
+        let periods = plan_schedule_periods(&schedule, &HolidayCalendar::default(), None, date(2026, 2, 20), 3).expect("planned");
+        assert_eq!(
+            bounds(&periods),
+            vec![
+                (date(2026, 2, 15), date(2026, 2, 28)),
+                (date(2026, 3, 1), date(2026, 3, 14)),
+                (date(2026, 3, 15), date(2026, 3, 31)),
+            ]
+        );
+    }
+
+    #[test]
+    fn semi_monthly_periods_continue_after_adjusted_end() {
+        let schedule = ScheduleRule {
+            second_day: Some(15),
+            saturday_adjustment: WeekendAdjustment::Friday,
+            sunday_adjustment: WeekendAdjustment::Friday,
+            ..rule(ScheduleKind::SemiMonthly)
+        };
+
+        // 2026-03-01 is a Sunday, so the previous period ended on Thursday 2026-02-26
+        let periods = plan_schedule_periods(&schedule, &HolidayCalendar::default(), Some(date(2026, 2, 26)), date(2026, 2, 20), 2).expect("planned");
+        assert_eq!(
+            bounds(&periods),
+            vec![(date(2026, 2, 27), date(2026, 3, 12)), (date(2026, 3, 13), date(2026, 3, 31))]
+        );
+    }
+
+    #[test]
+    fn anchored_periods_count_from_anchor_date() {
+        let schedule = ScheduleRule {
+            anchor_date: Some(date(2026, 1, 9)),
+            duration_value: 2,
+            duration_unit: DurationUnit::Weeks,
+            ..rule(ScheduleKind::Anchored)
+        };
+
+        let periods = plan_schedule_periods(&schedule, &HolidayCalendar::default(), None, date(2026, 2, 10), 2).expect("planned");
+        assert_eq!(
+            bounds(&periods),
+            vec![(date(2026, 2, 6), date(2026, 2, 19)), (date(2026, 2, 20), date(2026, 3, 5))]
+        );
+
+        let upcoming = plan_schedule_periods(&schedule, &HolidayCalendar::default(), None, date(2025, 12, 1), 1).expect("planned");
+        assert_eq!(bounds(&upcoming), vec![(date(2026, 1, 9), date(2026, 1, 22))]);
+    }
+
+    #[test]
+    fn last_business_day_periods_skip_weekends() {
+        let periods = plan_schedule_periods(&rule(ScheduleKind::LastBusinessDay), &HolidayCalendar::default(), None, date(2026, 2, 10), 2).expect("planned");
+        // 2026-01-31 is a Saturday and 2026-02-28 a Saturday
+        assert_eq!(
+            bounds(&periods),
+            vec![(date(2026, 1, 30), date(2026, 2, 26)), (date(2026, 2, 27), date(2026, 3, 30))]
+        );
+    }
+
+    #[test]
+    fn interval_periods_match_start_day_and_duration() {
+        let periods = plan_schedule_periods(
+            &rule(ScheduleKind::Interval),
+            &HolidayCalendar::default(),
+            Some(date(2026, 1, 31)),
+            date(2026, 1, 15),
+            2,
+        )
+        .expect("planned");
+        assert_eq!(
+            bounds(&periods),
+            vec![(date(2026, 2, 1), date(2026, 2, 28)), (date(2026, 3, 1), date(2026, 3, 31))]
+        );
+        assert_eq!(periods[0].name, "2026-02-01");
+    }
+
+    #[test]
+    fn holidays_move_boundaries_like_weekends() {
+        let holidays = HolidayCalendar::new(vec![
+            HolidayRule::Annual {
+                name: "New Year".to_string(),
+                month: 1,
+                day: 1,
+            },
+            HolidayRule::Annual {
+                name: "New Year's Eve".to_string(),
+                month: 12,
+                day: 31,
+            },
+        ]);
+
+        // 2027-01-01 is a Friday, so the period starts on Monday 2027-01-04
+        let schedule = ScheduleRule {
+            holiday_adjustment: HolidayAdjustment::Next,
+            ..rule(ScheduleKind::Interval)
+        };
+        let periods = plan_schedule_periods(&schedule, &holidays, Some(date(2026, 12, 31)), date(2026, 12, 15), 1).expect("planned");
+        assert_eq!(bounds(&periods), vec![(date(2027, 1, 4), date(2027, 1, 31))]);
+
+        // 2026-12-31 is a Thursday holiday, so the last business day of December is the 30th
+        let periods = plan_schedule_periods(&rule(ScheduleKind::LastBusinessDay), &holidays, None, date(2027, 1, 10), 1).expect("planned");
+        assert_eq!(bounds(&periods), vec![(date(2026, 12, 30), date(2027, 1, 28))]);
+    }
+
+    fn unassigned(days: &[(NaiveDate, i64)]) -> Vec<UnassignedDay> {
+        days.iter()
+            .map(|(date, transaction_count)| UnassignedDay {
+                date: *date,
+                transaction_count: *transaction_count,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn gaps_are_bounded_by_neighbouring_periods() {
+        let periods = vec![(date(2026, 2, 1), date(2026, 2, 28)), (date(2026, 4, 1), date(2026, 4, 30))];
+        let days = unassigned(&[(date(2026, 1, 10), 2), (date(2026, 1, 20), 1), (date(2026, 3, 5), 3), (date(2026, 5, 2), 1)]);
+
+        let gaps: Vec<PeriodGap> = find_period_gaps(&periods, &days).into_iter().map(|gap| gap.gap).collect();
+        assert_eq!(
+            gaps,
+            vec![
+                PeriodGap {
+                    start_date: date(2026, 1, 10),
+                    end_date: date(2026, 1, 31),
+                    transaction_count: 3
+                },
+                PeriodGap {
+                    start_date: date(2026, 3, 1),
+                    end_date: date(2026, 3, 31),
+                    transaction_count: 3
+                },
+                PeriodGap {
+                    start_date: date(2026, 5, 1),
+                    end_date: date(2026, 5, 2),
+                    transaction_count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn gap_fill_creates_only_periods_holding_transactions() {
+        // A monthly schedule starting on the 1st, with a hole from 2026-01-01 to 2026-04-14
+        let periods = vec![(date(2025, 12, 1), date(2025, 12, 31)), (date(2026, 4, 15), date(2026, 5, 14))];
+        let days = unassigned(&[(date(2026, 1, 3), 1), (date(2026, 1, 30), 1), (date(2026, 4, 2), 2)]);
+        let gaps = find_period_gaps(&periods, &days);
+
+        let planned = plan_gap_periods(&gaps, &days, &HolidayCalendar::default(), |_| Ok(rule(ScheduleKind::Interval))).expect("planned");
+        assert_eq!(
+            bounds(&planned),
+            vec![(date(2026, 1, 1), date(2026, 1, 31)), (date(2026, 4, 1), date(2026, 4, 14))]
+        );
+    }
+
+    #[test]
+    fn gap_fill_with_fixed_duration_starts_at_first_transaction() {
+        let days = unassigned(&[(date(2026, 3, 4), 1), (date(2026, 3, 20), 1)]);
+        let gaps = find_period_gaps(&[], &days);
+
+        let planned = plan_gap_periods(&gaps, &days, &HolidayCalendar::default(), |first_date| {
+            Ok(ScheduleRule {
+                anchor_date: Some(first_date),
+                duration_value: 2,
+                duration_unit: DurationUnit::Weeks,
+                ..rule(ScheduleKind::Anchored)
+            })
+        })
+        .expect("planned");
+        assert_eq!(
+            bounds(&planned),
+            vec![(date(2026, 3, 4), date(2026, 3, 17)), (date(2026, 3, 18), date(2026, 3, 31))]
+        );
+    }
 }
@@ -0,0 +1,325 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::budget_period_snapshot::{AccountSnapshot, BudgetPeriodSnapshot, BudgetPeriodSnapshotResponse, CategorySnapshot};
+use chrono::NaiveDate;
+use sqlx::PgConnection;
+use uuid::Uuid;
+
+/// Reject a merge that would move transactions of a category or vendor that lie in a closed period; their
+/// snapshot would no longer match. Locks the user's periods so none can close before `conn`'s transaction ends.
+pub(crate) async fn ensure_merge_leaves_closed_periods(
+    conn: &mut PgConnection,
+    category_id: Option<&Uuid>,
+    vendor_id: Option<&Uuid>,
+    user_id: &Uuid,
+) -> Result<(), AppError> {
+    sqlx::query("SELECT 1 FROM budget_period WHERE user_id = $1 FOR SHARE")
+        .bind(user_id)
+        .execute(&mut *conn)
+        .await?;
+
+    let closed_period: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT bp.name
+        FROM budget_period bp
+        WHERE bp.user_id = $1
+          AND bp.closed_at IS NOT NULL
+          AND EXISTS (
+              SELECT 1
+              FROM transaction t
+              WHERE t.user_id = $1
+                AND ($2::uuid IS NULL OR t.category_id = $2)
+                AND ($3::uuid IS NULL OR t.vendor_id = $3)
+                AND t.occurred_at >= bp.start_date
+                AND t.occurred_at <= bp.end_date
+          )
+        ORDER BY bp.start_date
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(category_id)
+    .bind(vendor_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match closed_period {
+        Some(name) => Err(AppError::BadRequest(format!(
+            "Budget period {name} is closed and holds transactions that would move; reopen it before merging"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Reject transaction writes dated inside a closed period. Locks the periods holding the dates so none can
+/// close before `conn`'s transaction ends.
+pub(crate) async fn ensure_dates_in_open_periods(conn: &mut PgConnection, dates: &[NaiveDate], user_id: &Uuid) -> Result<(), AppError> {
+    sqlx::query(
+        r#"
+        SELECT 1
+        FROM budget_period bp
+        WHERE bp.user_id = $1
+          AND EXISTS (SELECT 1 FROM UNNEST($2::date[]) AS d(day) WHERE d.day >= bp.start_date AND d.day <= bp.end_date)
+        FOR SHARE
+        "#,
+    )
+    .bind(user_id)
+    .bind(dates)
+    .execute(&mut *conn)
+    .await?;
+
+    let closed_period: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT bp.name
+        FROM budget_period bp
+        WHERE bp.user_id = $1
+          AND bp.closed_at IS NOT NULL
+          AND EXISTS (SELECT 1 FROM UNNEST($2::date[]) AS d(day) WHERE d.day >= bp.start_date AND d.day <= bp.end_date)
+        ORDER BY bp.start_date
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .bind(dates)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match closed_period {
+        Some(name) => Err(AppError::BadRequest(format!(
+            "Budget period {name} is closed; reopen it before changing its transactions"
+        ))),
+        None => Ok(()),
+    }
+}
+
+impl PostgresRepository {
+    /// Close an ended period: snapshot its totals, per-category target outcomes and closing account balances,
+    /// then lock its transactions.
+    pub async fn close_budget_period(&self, id: &Uuid, user_id: &Uuid) -> Result<BudgetPeriodSnapshotResponse, AppError> {
+        #[derive(sqlx::FromRow)]
+        struct PeriodState {
+            has_ended: bool,
+            is_closed: bool,
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let state = sqlx::query_as::<_, PeriodState>(
+            r#"
+            SELECT end_date < CURRENT_DATE AS has_ended, closed_at IS NOT NULL AS is_closed
+            FROM budget_period
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Budget period not found".to_string()))?;
+
+        if state.is_closed {
+            return Err(AppError::BadRequest("Budget period is already closed".to_string()));
+        }
+        if !state.has_ended {
+            return Err(AppError::BadRequest("Only periods that have ended can be closed".to_string()));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_period_snapshot (period_id, user_id, total_budgeted, total_spent, total_income, stability_spent, transaction_count)
+            SELECT
+                bp.id,
+                bp.user_id,
+                0,
+                COALESCE(SUM(t.amount) FILTER (WHERE c.category_type = 'Outgoing'), 0)::bigint,
+                COALESCE(SUM(t.amount) FILTER (WHERE c.category_type = 'Incoming'), 0)::bigint,
+                COALESCE(SUM(t.amount) FILTER (WHERE c.category_type = 'Outgoing'), 0)::bigint,
+                COUNT(t.id)
+            FROM budget_period bp
+            LEFT JOIN transaction t
+                ON t.user_id = bp.user_id
+               AND t.occurred_at >= bp.start_date
+               AND t.occurred_at <= bp.end_date
+            LEFT JOIN category c ON c.id = t.category_id
+            WHERE bp.id = $1
+            GROUP BY bp.id, bp.user_id
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_period_category_snapshot (
+                period_id, category_id, category_name, category_type, target, actual, is_excluded, within_horizon
+            )
+            SELECT
+                bp.id,
+                c.id,
+                c.name,
+                c.category_type::text,
+                budget_category_target_for_period(bc.id, bp.id, bp.start_date),
+                COALESCE(SUM(t.amount), 0)::bigint,
+                bc.is_excluded,
+                COALESCE(acc.spent_amount <= acc.horizon_amount, FALSE)
+            FROM budget_category bc
+            JOIN category c ON c.id = bc.category_id
+            JOIN budget_period bp ON bp.id = $1
+            LEFT JOIN LATERAL budget_category_accrual_for_period(bc.id, bp.id) acc ON TRUE
+            LEFT JOIN transaction t
+                ON t.user_id = $2
+               AND t.category_id = c.id
+               AND t.occurred_at >= bp.start_date
+               AND t.occurred_at <= bp.end_date
+            WHERE bc.user_id = $2
+            GROUP BY bp.id, bp.start_date, bc.id, bc.is_excluded, c.id, c.name, c.category_type, acc.spent_amount, acc.horizon_amount
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Same adjustment budget_stability makes for long-horizon categories paid within their horizon
+        sqlx::query(
+            r#"
+            UPDATE budget_period_snapshot s
+            SET total_budgeted = cs.total_budgeted,
+                stability_spent = s.total_spent - cs.horizon_adjustment
+            FROM (
+                SELECT
+                    COALESCE(SUM(target), 0)::bigint AS total_budgeted,
+                    COALESCE(SUM(actual - COALESCE(target, 0)) FILTER (WHERE category_type = 'Outgoing' AND within_horizon), 0)::bigint AS horizon_adjustment
+                FROM budget_period_category_snapshot
+                WHERE period_id = $1
+            ) cs
+            WHERE s.period_id = $1
+            "#,
+        )
+        .bind(id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_period_account_snapshot (period_id, account_id, account_name, account_type, closing_balance)
+            SELECT
+                bp.id,
+                a.id,
+                a.name,
+                a.account_type::text,
+                (a.balance + COALESCE(SUM(
+                    CASE
+                        WHEN cat.category_type = 'Incoming'                              THEN  t.amount::bigint
+                        WHEN cat.category_type = 'Outgoing'                              THEN -t.amount::bigint
+                        WHEN cat.category_type = 'Transfer' AND t.from_account_id = a.id THEN -t.amount::bigint
+                        WHEN cat.category_type = 'Transfer' AND t.to_account_id   = a.id THEN  t.amount::bigint
+                        ELSE 0
+                    END
+                ), 0))::bigint
+            FROM account a
+            JOIN budget_period bp ON bp.id = $1
+            LEFT JOIN transaction t
+                ON (t.from_account_id = a.id OR t.to_account_id = a.id)
+               AND t.user_id = $2
+               AND t.occurred_at <= bp.end_date
+            LEFT JOIN category cat ON cat.id = t.category_id
+            WHERE a.user_id = $2
+            GROUP BY bp.id, a.id, a.name, a.account_type, a.balance
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE budget_period SET closed_at = now() WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        self.get_budget_period_snapshot(id, user_id).await
+    }
+
+    /// Drop the snapshot of a closed period and unlock its transactions.
+    pub async fn reopen_budget_period(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let closed: Option<bool> = sqlx::query_scalar("SELECT closed_at IS NOT NULL FROM budget_period WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        match closed {
+            None => return Err(AppError::NotFound("Budget period not found".to_string())),
+            Some(false) => return Err(AppError::BadRequest("Budget period is not closed".to_string())),
+            Some(true) => {}
+        }
+
+        sqlx::query("DELETE FROM budget_period_snapshot WHERE period_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE budget_period SET closed_at = NULL WHERE id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_budget_period_snapshot(&self, id: &Uuid, user_id: &Uuid) -> Result<BudgetPeriodSnapshotResponse, AppError> {
+        let snapshot = sqlx::query_as::<_, BudgetPeriodSnapshot>(
+            r#"
+            SELECT period_id, total_budgeted, total_spent, total_income, transaction_count, created_at
+            FROM budget_period_snapshot
+            WHERE period_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Budget period is not closed".to_string()))?;
+
+        let categories = self.list_category_snapshots(id, user_id).await?;
+        let accounts = sqlx::query_as::<_, AccountSnapshot>(
+            r#"
+            SELECT account_id, account_name, account_type, closing_balance
+            FROM budget_period_account_snapshot
+            WHERE period_id = $1
+            ORDER BY account_name ASC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(BudgetPeriodSnapshotResponse::new(&snapshot, &categories, &accounts))
+    }
+
+    /// Category outcomes of a closed period; empty when the period is open.
+    pub async fn list_category_snapshots(&self, period_id: &Uuid, user_id: &Uuid) -> Result<Vec<CategorySnapshot>, AppError> {
+        let categories = sqlx::query_as::<_, CategorySnapshot>(
+            r#"
+            SELECT cs.category_id, cs.category_name, cs.category_type, cs.target, cs.actual, cs.is_excluded, cs.within_horizon
+            FROM budget_period_category_snapshot cs
+            JOIN budget_period_snapshot s ON s.period_id = cs.period_id
+            WHERE cs.period_id = $1 AND s.user_id = $2
+            ORDER BY cs.category_name ASC
+            "#,
+        )
+        .bind(period_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(categories)
+    }
+}
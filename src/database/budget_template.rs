@@ -0,0 +1,297 @@
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
+use crate::error::app_error::AppError;
+use crate::models::budget_template::{BudgetTemplate, BudgetTemplateEntry, BudgetTemplateFromPeriodRequest, BudgetTemplateRequest};
+use crate::models::category_target::{TargetPreviewResponse, TargetPreviewRow};
+use sqlx::{Postgres, Transaction};
+use uuid::Uuid;
+
+const BUDGET_TEMPLATE_ENTRY_SELECT: &str = r#"
+    SELECT
+        e.template_id,
+        e.category_id,
+        c.name AS category_name,
+        e.budgeted_value,
+        e.is_excluded
+    FROM budget_template_entry e
+    JOIN budget_template t ON t.id = e.template_id
+    JOIN category c ON c.id = e.category_id
+"#;
+
+impl PostgresRepository {
+    async fn validate_template_categories(&self, category_ids: &[Uuid], user_id: &Uuid) -> Result<(), AppError> {
+        let valid: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(DISTINCT id)
+            FROM category
+            WHERE id = ANY($1) AND user_id = $2 AND category_type != 'Transfer'
+            "#,
+        )
+        .bind(category_ids)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let mut requested = category_ids.to_vec();
+        requested.sort_unstable();
+        requested.dedup();
+        if requested.len() != category_ids.len() {
+            return Err(AppError::BadRequest("A category can only appear once in a template".to_string()));
+        }
+        if valid != requested.len() as i64 {
+            return Err(AppError::BadRequest("Invalid category_ids for current user".to_string()));
+        }
+
+        Ok(())
+    }
+
+    async fn insert_budget_template(
+        tx: &mut Transaction<'_, Postgres>,
+        name: &str,
+        description: &Option<String>,
+        user_id: &Uuid,
+    ) -> Result<BudgetTemplate, AppError> {
+        let template = sqlx::query_as::<_, BudgetTemplate>(
+            r#"
+            INSERT INTO budget_template (user_id, name, description)
+            VALUES ($1, $2, $3)
+            RETURNING id, name, description, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(description)
+        .fetch_one(&mut **tx)
+        .await;
+
+        match template {
+            Ok(template) => Ok(template),
+            Err(err) if is_unique_violation(&err) => Err(AppError::BadRequest("Budget template name already exists".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    async fn insert_budget_template_entries(tx: &mut Transaction<'_, Postgres>, template_id: &Uuid, request: &BudgetTemplateRequest) -> Result<(), AppError> {
+        let category_ids: Vec<Uuid> = request.entries.iter().map(|entry| entry.category_id).collect();
+        let values: Vec<i32> = request.entries.iter().map(|entry| entry.budgeted_value).collect();
+        let exclusions: Vec<bool> = request.entries.iter().map(|entry| entry.is_excluded).collect();
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_template_entry (template_id, category_id, budgeted_value, is_excluded)
+            SELECT $1, i.category_id, i.budgeted_value, i.is_excluded
+            FROM UNNEST($2::uuid[], $3::integer[], $4::boolean[]) AS i(category_id, budgeted_value, is_excluded)
+            "#,
+        )
+        .bind(template_id)
+        .bind(&category_ids)
+        .bind(&values)
+        .bind(&exclusions)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_budget_template(
+        &self,
+        request: &BudgetTemplateRequest,
+        user_id: &Uuid,
+    ) -> Result<(BudgetTemplate, Vec<BudgetTemplateEntry>), AppError> {
+        let category_ids: Vec<Uuid> = request.entries.iter().map(|entry| entry.category_id).collect();
+        self.validate_template_categories(&category_ids, user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let template = Self::insert_budget_template(&mut tx, &request.name, &request.description, user_id).await?;
+        Self::insert_budget_template_entries(&mut tx, &template.id, request).await?;
+        tx.commit().await?;
+
+        let entries = self.list_budget_template_entries(Some(&template.id), user_id).await?;
+        Ok((template, entries))
+    }
+
+    /// Save the targets in effect for a period (overrides included) and the current exclusions as a template.
+    pub async fn create_budget_template_from_period(
+        &self,
+        request: &BudgetTemplateFromPeriodRequest,
+        user_id: &Uuid,
+    ) -> Result<(BudgetTemplate, Vec<BudgetTemplateEntry>), AppError> {
+        let period_exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM budget_period WHERE id = $1 AND user_id = $2)")
+            .bind(request.period_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !period_exists {
+            return Err(AppError::NotFound("Budget period not found".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let template = Self::insert_budget_template(&mut tx, &request.name, &request.description, user_id).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_template_entry (template_id, category_id, budgeted_value, is_excluded)
+            SELECT
+                $1,
+                bc.category_id,
+                COALESCE(budget_category_target_for_period(bc.id, bp.id, bp.start_date), 0),
+                bc.is_excluded
+            FROM budget_category bc
+            JOIN category c ON c.id = bc.category_id
+            JOIN budget_period bp ON bp.id = $2
+            WHERE bc.user_id = $3
+              AND c.is_archived = FALSE
+              AND c.category_type != 'Transfer'
+              AND (bc.is_excluded OR budget_category_target_for_period(bc.id, bp.id, bp.start_date) IS NOT NULL)
+            "#,
+        )
+        .bind(template.id)
+        .bind(request.period_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        let entries = self.list_budget_template_entries(Some(&template.id), user_id).await?;
+        Ok((template, entries))
+    }
+
+    /// Entries of one template, or of every template of the user when `template_id` is `None`.
+    pub async fn list_budget_template_entries(&self, template_id: Option<&Uuid>, user_id: &Uuid) -> Result<Vec<BudgetTemplateEntry>, AppError> {
+        let query = format!(
+            "{} WHERE t.user_id = $1 AND ($2::uuid IS NULL OR t.id = $2) ORDER BY c.name ASC",
+            BUDGET_TEMPLATE_ENTRY_SELECT
+        );
+        let entries = sqlx::query_as::<_, BudgetTemplateEntry>(&query)
+            .bind(user_id)
+            .bind(template_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn list_budget_templates(&self, user_id: &Uuid) -> Result<(Vec<BudgetTemplate>, Vec<BudgetTemplateEntry>), AppError> {
+        let templates = sqlx::query_as::<_, BudgetTemplate>(
+            r#"
+            SELECT id, name, description, created_at
+            FROM budget_template
+            WHERE user_id = $1
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        let entries = self.list_budget_template_entries(None, user_id).await?;
+
+        Ok((templates, entries))
+    }
+
+    pub async fn get_budget_template(&self, id: &Uuid, user_id: &Uuid) -> Result<(BudgetTemplate, Vec<BudgetTemplateEntry>), AppError> {
+        let template = sqlx::query_as::<_, BudgetTemplate>("SELECT id, name, description, created_at FROM budget_template WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Budget template not found".to_string()))?;
+        let entries = self.list_budget_template_entries(Some(id), user_id).await?;
+
+        Ok((template, entries))
+    }
+
+    pub async fn update_budget_template(
+        &self,
+        id: &Uuid,
+        request: &BudgetTemplateRequest,
+        user_id: &Uuid,
+    ) -> Result<(BudgetTemplate, Vec<BudgetTemplateEntry>), AppError> {
+        let category_ids: Vec<Uuid> = request.entries.iter().map(|entry| entry.category_id).collect();
+        self.validate_template_categories(&category_ids, user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE budget_template
+            SET name = $1, description = $2, updated_at = now()
+            WHERE id = $3 AND user_id = $4
+            "#,
+        )
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await;
+
+        match result {
+            Ok(result) if result.rows_affected() == 0 => return Err(AppError::NotFound("Budget template not found".to_string())),
+            Ok(_) => {}
+            Err(err) if is_unique_violation(&err) => return Err(AppError::BadRequest("Budget template name already exists".to_string())),
+            Err(err) => return Err(err.into()),
+        }
+
+        sqlx::query("DELETE FROM budget_template_entry WHERE template_id = $1")
+            .bind(id)
+            .execute(&mut *tx)
+            .await?;
+        Self::insert_budget_template_entries(&mut tx, id, request).await?;
+        tx.commit().await?;
+
+        self.get_budget_template(id, user_id).await
+    }
+
+    pub async fn delete_budget_template(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM budget_template WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// What applying a template would write to each period, next to the targets currently in effect.
+    pub async fn preview_budget_template(&self, id: &Uuid, period_ids: &[Uuid], user_id: &Uuid) -> Result<Vec<TargetPreviewResponse>, AppError> {
+        self.get_budget_template(id, user_id).await?;
+
+        let mut previews = Vec::with_capacity(period_ids.len());
+        for period_id in period_ids {
+            let start_date = self.open_period_start(period_id, user_id).await?;
+            let rows = sqlx::query_as::<_, TargetPreviewRow>(
+                r#"
+                SELECT
+                    c.id AS category_id,
+                    c.name AS category_name,
+                    budget_category_target_for_period(bc.id, $3, $4) AS current_target,
+                    e.budgeted_value AS proposed_target,
+                    e.is_excluded
+                FROM budget_template_entry e
+                JOIN category c ON c.id = e.category_id
+                LEFT JOIN budget_category bc ON bc.category_id = c.id AND bc.user_id = $2
+                WHERE e.template_id = $1
+                  AND c.is_archived = FALSE
+                ORDER BY c.name ASC
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .bind(period_id)
+            .bind(start_date)
+            .fetch_all(&self.pool)
+            .await?;
+
+            previews.push(TargetPreviewResponse { period_id: *period_id, rows });
+        }
+
+        Ok(previews)
+    }
+
+    pub async fn apply_budget_template(&self, id: &Uuid, period_ids: &[Uuid], user_id: &Uuid) -> Result<Vec<TargetPreviewResponse>, AppError> {
+        let previews = self.preview_budget_template(id, period_ids, user_id).await?;
+        for preview in &previews {
+            self.apply_period_targets(&preview.period_id, &preview.rows, user_id).await?;
+        }
+
+        Ok(previews)
+    }
+}
@@ -0,0 +1,258 @@
+use crate::database::allowance_funding::interval_run_dates;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::allowance_funding::FundingCadence;
+use crate::models::cash_flow::{
+    CashFlowAccount, CashFlowEvent, CashFlowEventSource, CashFlowForecastResponse, DISCRETIONARY_LOOKBACK_DAYS, RECURRING_LOOKBACK_MONTHS,
+    RecurringCashFlowItem, project_cash_flow, recurring_occurrences,
+};
+use chrono::{Duration, NaiveDate};
+use std::collections::HashSet;
+use uuid::Uuid;
+
+const ALLOWANCE_TRANSFER_DESCRIPTION: &str = "Planned allowance top-up";
+
+// Signed effect of every transaction on each projected account. Credit cards are left out: they are debt, not cash.
+// $1 is the user.
+const ACCOUNT_FLOWS_CTE: &str = r#"
+    account_flows AS (
+        SELECT
+            a.id AS account_id,
+            t.vendor_id,
+            t.category_id,
+            c.category_type::text AS category_type,
+            t.occurred_at,
+            COALESCE(NULLIF(t.description, ''), c.name) AS description,
+            (CASE
+                WHEN c.category_type = 'Incoming' THEN t.amount
+                WHEN c.category_type = 'Outgoing' THEN -t.amount
+                WHEN t.from_account_id = a.id THEN -t.amount
+                ELSE t.amount
+            END)::bigint AS amount
+        FROM transaction t
+        JOIN category c ON c.id = t.category_id
+        JOIN account a ON a.id = t.from_account_id OR a.id = t.to_account_id
+        WHERE t.user_id = $1
+          AND a.user_id = $1
+          AND a.is_archived = FALSE
+          AND a.account_type <> 'CreditCard'
+    )
+"#;
+
+// Vendor + category pairs seen on an account in each of the $3 months before the month of $2 (today).
+const RECURRING_ITEMS_CTE: &str = r#"
+    recurring_items AS (
+        SELECT
+            af.account_id,
+            af.vendor_id,
+            af.category_id,
+            (SUM(af.amount) / $3)::bigint AS amount,
+            (percentile_disc(0.5) WITHIN GROUP (ORDER BY EXTRACT(DAY FROM af.occurred_at)::integer))::integer AS day_of_month
+        FROM account_flows af
+        WHERE af.vendor_id IS NOT NULL
+          AND af.occurred_at >= (date_trunc('month', $2::timestamp) - make_interval(months => $3))::date
+          AND af.occurred_at < date_trunc('month', $2::timestamp)::date
+        GROUP BY af.account_id, af.vendor_id, af.category_id
+        HAVING COUNT(DISTINCT date_trunc('month', af.occurred_at::timestamp)) = $3
+    )
+"#;
+
+#[derive(sqlx::FromRow)]
+struct ScheduledFlowRow {
+    account_id: Uuid,
+    date: NaiveDate,
+    amount: i64,
+    description: String,
+    vendor_id: Option<Uuid>,
+    category_id: Uuid,
+}
+
+impl PostgresRepository {
+    /// Project daily balances of the user's cash accounts for the next `days` days.
+    pub async fn cash_flow_forecast(&self, today: NaiveDate, days: i64, floor: i64, user_id: &Uuid) -> Result<CashFlowForecastResponse, AppError> {
+        let until = today + Duration::days(days);
+
+        let accounts_query = format!(
+            r#"
+            WITH {ACCOUNT_FLOWS_CTE}, {RECURRING_ITEMS_CTE}
+            SELECT
+                a.id AS account_id,
+                a.name AS account_name,
+                a.account_type::text AS account_type,
+                (a.balance + COALESCE((
+                    SELECT SUM(af.amount) FROM account_flows af WHERE af.account_id = a.id AND af.occurred_at <= $2
+                ), 0))::bigint AS current_balance,
+                COALESCE((
+                    SELECT -SUM(af.amount) / $4
+                    FROM account_flows af
+                    WHERE af.account_id = a.id
+                      AND af.category_type = 'Outgoing'
+                      AND af.occurred_at > $2 - $4
+                      AND af.occurred_at <= $2
+                      AND NOT EXISTS (
+                          SELECT 1 FROM recurring_items ri
+                          WHERE ri.account_id = af.account_id AND ri.vendor_id = af.vendor_id AND ri.category_id = af.category_id
+                      )
+                ), 0)::bigint AS daily_discretionary_spend
+            FROM account a
+            WHERE a.user_id = $1
+              AND a.is_archived = FALSE
+              AND a.account_type <> 'CreditCard'
+            ORDER BY a.name ASC
+            "#
+        );
+        let accounts = sqlx::query_as::<_, CashFlowAccount>(&accounts_query)
+            .bind(user_id)
+            .bind(today)
+            .bind(RECURRING_LOOKBACK_MONTHS)
+            .bind(DISCRETIONARY_LOOKBACK_DAYS as i32)
+            .fetch_all(&self.pool)
+            .await?;
+        let account_ids: HashSet<Uuid> = accounts.iter().map(|account| account.account_id).collect();
+
+        let scheduled_query = format!(
+            r#"
+            WITH {ACCOUNT_FLOWS_CTE}
+            SELECT af.account_id, af.occurred_at AS date, af.amount, af.description, af.vendor_id, af.category_id
+            FROM account_flows af
+            WHERE af.occurred_at > $2 AND af.occurred_at <= $3
+            "#
+        );
+        let mut events: Vec<CashFlowEvent> = sqlx::query_as::<_, ScheduledFlowRow>(&scheduled_query)
+            .bind(user_id)
+            .bind(today)
+            .bind(until)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .map(|row| CashFlowEvent {
+                account_id: row.account_id,
+                date: row.date,
+                amount: row.amount,
+                description: row.description,
+                source: CashFlowEventSource::Scheduled,
+                vendor_id: row.vendor_id,
+                category_id: Some(row.category_id),
+            })
+            .collect();
+
+        let recurring_query = format!(
+            r#"
+            WITH {ACCOUNT_FLOWS_CTE}, {RECURRING_ITEMS_CTE}
+            SELECT
+                ri.account_id,
+                ri.vendor_id,
+                ri.category_id,
+                v.name AS description,
+                ri.amount,
+                ri.day_of_month,
+                (
+                    SELECT MAX(af.occurred_at)
+                    FROM account_flows af
+                    WHERE af.account_id = ri.account_id
+                      AND af.vendor_id = ri.vendor_id
+                      AND af.category_id = ri.category_id
+                      AND af.occurred_at <= $2
+                ) AS last_occurred_on
+            FROM recurring_items ri
+            JOIN vendor v ON v.id = ri.vendor_id
+            "#
+        );
+        let recurring_items = sqlx::query_as::<_, RecurringCashFlowItem>(&recurring_query)
+            .bind(user_id)
+            .bind(today)
+            .bind(RECURRING_LOOKBACK_MONTHS)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let holidays = self.get_holiday_calendar(user_id).await?;
+        let mut recurring_events = Vec::new();
+        for item in &recurring_items {
+            for date in recurring_occurrences(item, today, until, &events, &holidays) {
+                recurring_events.push(CashFlowEvent {
+                    account_id: item.account_id,
+                    date,
+                    amount: item.amount,
+                    description: item.description.clone(),
+                    source: CashFlowEventSource::Recurring,
+                    vendor_id: Some(item.vendor_id),
+                    category_id: Some(item.category_id),
+                });
+            }
+        }
+        events.extend(recurring_events);
+        events.extend(self.planned_allowance_transfers(today, until, user_id).await?);
+        events.retain(|event| account_ids.contains(&event.account_id));
+
+        Ok(project_cash_flow(&accounts, &events, today, days, floor))
+    }
+
+    /// Upcoming allowance funding runs as a debit on the source account and a credit on the allowance account.
+    /// Runs that are already due land tomorrow, after the daily funding job has picked them up.
+    async fn planned_allowance_transfers(&self, today: NaiveDate, until: NaiveDate, user_id: &Uuid) -> Result<Vec<CashFlowEvent>, AppError> {
+        let rules = self.list_allowance_funding_rules(user_id).await?;
+        let tomorrow = today + Duration::days(1);
+        let mut events = Vec::new();
+
+        for rule in rules.iter().filter(|rule| rule.is_active) {
+            let dates = match rule.cadence {
+                FundingCadence::Period => {
+                    sqlx::query_scalar::<_, NaiveDate>(
+                        r#"
+                        SELECT bp.start_date
+                        FROM budget_period bp
+                        WHERE bp.user_id = $1
+                          AND bp.start_date >= $2
+                          AND bp.start_date <= $3
+                          AND NOT EXISTS (
+                              SELECT 1 FROM allowance_funding_run fr
+                              WHERE fr.rule_id = $4 AND fr.scheduled_for = bp.start_date
+                          )
+                        ORDER BY bp.start_date
+                        "#,
+                    )
+                    .bind(user_id)
+                    .bind(rule.starts_on)
+                    .bind(until)
+                    .bind(rule.id)
+                    .fetch_all(&self.pool)
+                    .await?
+                }
+                FundingCadence::Interval => {
+                    let Some(next_run_date) = rule.next_run_date else {
+                        continue;
+                    };
+                    interval_run_dates(rule)
+                        .skip_while(|date| *date < next_run_date)
+                        .take_while(|date| *date <= until)
+                        .collect()
+                }
+            };
+
+            for (index, date) in dates.into_iter().map(|date| date.max(tomorrow)).enumerate() {
+                let amount = if index == 0 {
+                    if rule.skip_next_run { None } else { rule.next_run_amount() }
+                } else {
+                    rule.next_transfer_amount
+                };
+                let Some(amount) = amount.filter(|amount| *amount > 0) else {
+                    continue;
+                };
+
+                for (account_id, signed_amount) in [(rule.source_account_id, -amount), (rule.allowance_account_id, amount)] {
+                    events.push(CashFlowEvent {
+                        account_id,
+                        date,
+                        amount: signed_amount,
+                        description: ALLOWANCE_TRANSFER_DESCRIPTION.to_string(),
+                        source: CashFlowEventSource::PlannedTransfer,
+                        vendor_id: None,
+                        category_id: Some(rule.category_id),
+                    });
+                }
+            }
+        }
+
+        Ok(events)
+    }
+}
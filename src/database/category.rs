@@ -3,7 +3,7 @@ use crate::error::app_error::AppError;
 use crate::models::budget_period::BudgetPeriod;
 use crate::models::category::{
     Category, CategoryBudgetedDiagnosticsRow, CategoryManagementRow, CategoryRequest, CategoryStats, CategoryType, CategoryUnbudgetedDiagnosticsRow,
-    CategoryWithStats, difference_vs_average_percentage, progress_basis_points, share_of_total_basis_points, variance_value,
+    CategoryWithStats, available_value, difference_vs_average_percentage, progress_basis_points, share_of_total_basis_points, variance_value,
 };
 use crate::models::dashboard::BudgetStabilityPeriodResponse;
 use crate::models::pagination::CursorParams;
@@ -67,6 +67,7 @@ struct BudgetedCategoryDiagnosticsDbRow {
     description: Option<String>,
     budgeted_value: i32,
     actual_value: i64,
+    rollover_enabled: bool,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -86,7 +87,9 @@ struct UnbudgetedCategoryDiagnosticsDbRow {
 struct BudgetedCategoryClosedPeriodDbRow {
     category_id: Uuid,
     period_id: Uuid,
+    budgeted_value: i32,
     actual_value: i64,
+    within_horizon: bool,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -161,6 +164,10 @@ impl From<BudgetedCategoryDiagnosticsDbRow> for CategoryBudgetedDiagnosticsRow {
             actual_value: row.actual_value,
             variance_value: variance_value(row.actual_value, row.budgeted_value),
             progress_basis_points: progress_basis_points(row.actual_value, row.budgeted_value),
+            rollover_enabled: row.rollover_enabled,
+            rolled_over_value: 0,
+            available_value: available_value(row.budgeted_value, 0, row.actual_value),
+            accrual: None,
             recent_closed_periods: Vec::new(),
         }
     }
@@ -168,7 +175,7 @@ impl From<BudgetedCategoryDiagnosticsDbRow> for CategoryBudgetedDiagnosticsRow {
 
 impl PostgresRepository {
     pub async fn create_category(&self, request: &CategoryRequest, user_id: &Uuid) -> Result<Category, AppError> {
-        // Validate max depth = 1 (cannot set parent_id to a category that already has a parent)
+        // Validate the parent if one is set
         if let Some(parent_id) = request.parent_id {
             let parent: Option<CategoryRow> = sqlx::query_as(
                 r#"
@@ -191,11 +198,6 @@ impl PostgresRepository {
             .await?;
 
             if let Some(parent) = parent {
-                if parent.parent_id.is_some() {
-                    return Err(AppError::BadRequest(
-                        "Cannot create a subcategory under another subcategory. Maximum depth is 1.".to_string(),
-                    ));
-                }
                 // Verify type matches parent
                 let parent_type = category_type_from_db(&parent.category_type);
                 if parent_type != request.category_type {
@@ -468,8 +470,9 @@ SELECT
     c.category_type::text as category_type,
     c.is_archived,
     c.description,
-    bc.budgeted_value,
-    COALESCE(sps.actual_value, 0) AS actual_value
+    COALESCE(budget_category_target_for_period(bc.id, $4, $2), 0) AS budgeted_value,
+    COALESCE(sps.actual_value, 0) AS actual_value,
+    bc.rollover_enabled
 FROM budget_category bc
 JOIN category c
   ON c.id = bc.category_id
@@ -484,6 +487,7 @@ ORDER BY bc.created_at DESC, bc.id DESC
         .bind(user_id)
         .bind(period.start_date)
         .bind(period.end_date)
+        .bind(period.id)
         .fetch_all(&self.pool)
         .await?;
 
@@ -510,7 +514,8 @@ WITH recent_closed_periods AS (
     SELECT
         bp.id,
         bp.start_date,
-        bp.end_date
+        bp.end_date,
+        bp.closed_at
     FROM budget_period bp
     WHERE bp.user_id = $1
       AND bp.end_date < CURRENT_DATE
@@ -520,20 +525,36 @@ WITH recent_closed_periods AS (
 SELECT
     bc.category_id,
     rcp.id AS period_id,
-    COALESCE(SUM(t.amount), 0)::bigint AS actual_value
+    CASE
+        WHEN rcp.closed_at IS NOT NULL THEN COALESCE(cs.target, 0)
+        ELSE COALESCE(budget_category_target_for_period(bc.id, rcp.id, rcp.start_date), 0)
+    END AS budgeted_value,
+    CASE
+        WHEN rcp.closed_at IS NOT NULL THEN COALESCE(cs.actual, 0)
+        ELSE COALESCE(SUM(t.amount), 0)
+    END::bigint AS actual_value,
+    CASE
+        WHEN rcp.closed_at IS NOT NULL THEN COALESCE(cs.within_horizon, FALSE)
+        ELSE COALESCE(acc.spent_amount <= acc.horizon_amount, FALSE)
+    END AS within_horizon
 FROM budget_category bc
 JOIN category c
   ON c.id = bc.category_id
  AND c.user_id = $1
  AND c.category_type = 'Outgoing'
 CROSS JOIN recent_closed_periods rcp
+LEFT JOIN LATERAL budget_category_accrual_for_period(bc.id, rcp.id) acc ON TRUE
+LEFT JOIN budget_period_category_snapshot cs
+  ON cs.period_id = rcp.id
+ AND cs.category_id = bc.category_id
 LEFT JOIN transaction t
   ON t.user_id = $1
  AND t.category_id = bc.category_id
  AND t.occurred_at >= rcp.start_date
  AND t.occurred_at <= rcp.end_date
 WHERE bc.user_id = $1
-GROUP BY bc.category_id, rcp.id, rcp.end_date
+GROUP BY bc.id, bc.category_id, rcp.id, rcp.start_date, rcp.end_date, rcp.closed_at, acc.spent_amount, acc.horizon_amount,
+         cs.target, cs.actual, cs.within_horizon
 ORDER BY bc.category_id, rcp.end_date DESC
             "#,
         )
@@ -541,13 +562,15 @@ ORDER BY bc.category_id, rcp.end_date DESC
         .fetch_all(&self.pool)
         .await?;
 
-        let budget_by_category: HashMap<Uuid, i32> = diagnostics.iter().map(|row| (row.category.id, row.budgeted_value)).collect();
+        // Each closed period is judged against the target that applied to it, not today's target
         let mut stability_by_category: HashMap<Uuid, Vec<BudgetStabilityPeriodResponse>> = HashMap::new();
         for row in closed_period_rows {
-            let budgeted_value = i64::from(*budget_by_category.get(&row.category_id).unwrap_or(&0));
+            let budgeted_value = i64::from(row.budgeted_value);
+            // Paying a long-horizon target in its due period is planned, so only overspending the horizon counts
+            let actual_value = if row.within_horizon { budgeted_value } else { row.actual_value };
             stability_by_category.entry(row.category_id).or_default().push(BudgetStabilityPeriodResponse {
                 period_id: row.period_id.to_string(),
-                is_outside_tolerance: is_outside_tolerance(row.actual_value, budgeted_value, tolerance_basis_points),
+                is_outside_tolerance: is_outside_tolerance(actual_value, budgeted_value, tolerance_basis_points),
             });
         }
 
@@ -555,8 +578,16 @@ ORDER BY bc.category_id, rcp.end_date DESC
             periods.reverse();
         }
 
+        let rollovers = self.list_category_rollovers(period.start_date, user_id).await?;
+        let mut accruals = self.list_category_accruals(&period.id, user_id).await?;
+
         for row in &mut diagnostics {
             row.recent_closed_periods = stability_by_category.remove(&row.category.id).unwrap_or_default();
+            row.accrual = accruals.remove(&row.category.id);
+            if let Some(rolled_over) = rollovers.get(&row.category.id) {
+                row.rolled_over_value = *rolled_over;
+                row.available_value = available_value(row.budgeted_value, *rolled_over, row.actual_value);
+            }
         }
 
         Ok(diagnostics)
@@ -698,12 +729,8 @@ ORDER BY actual_value DESC, c.name
             .await?;
 
             if let Some(parent) = parent {
-                // Max depth = 1: parent cannot already have a parent
-                if parent.parent_id.is_some() {
-                    return Err(AppError::BadRequest(
-                        "Cannot create a subcategory under another subcategory. Maximum depth is 1.".to_string(),
-                    ));
-                }
+                // Parent cannot be one of the category's own subcategories
+                self.ensure_no_category_cycle(id, &parent_id, user_id).await?;
                 // Parent must be same type
                 let parent_type = category_type_from_db(&parent.category_type);
                 if parent_type != request.category_type {
@@ -737,6 +764,47 @@ ORDER BY actual_value DESC, c.name
             return Err(AppError::BadRequest("Category name already exists".to_string()));
         }
 
+        let previous: Option<(String, Option<Uuid>)> = sqlx::query_as("SELECT category_type::text, parent_id FROM category WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        let previous_type = previous.as_ref().map(|(category_type, _)| category_type.clone());
+        let parent_changed = previous.as_ref().is_some_and(|(_, parent_id)| *parent_id != request.parent_id);
+
+        // Income-based targets are resolved from the targets of Incoming categories, so an Incoming category
+        // with one, even in its history, would make that resolution recurse
+        if request.category_type == CategoryType::Incoming && previous_type.as_deref().is_some_and(|category_type| category_type != "Incoming") {
+            let has_income_target: bool = sqlx::query_scalar(
+                r#"
+                SELECT EXISTS (
+                    SELECT 1
+                    FROM budget_category bc
+                    LEFT JOIN budget_category_target_history h ON h.budget_category_id = bc.id
+                    WHERE bc.category_id = $1
+                      AND bc.user_id = $2
+                      AND (bc.target_income_basis_points IS NOT NULL OR h.target_income_basis_points IS NOT NULL)
+                )
+                "#,
+            )
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+            if has_income_target {
+                return Err(AppError::BadRequest(
+                    "Categories with a percentage-of-income target cannot become Incoming".to_string(),
+                ));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let guard = if parent_changed {
+            Some(Self::guard_parent_targets(&mut tx, None, user_id).await?)
+        } else {
+            None
+        };
+
         let row = sqlx::query_as::<_, CategoryRow>(
             r#"
             UPDATE category
@@ -761,7 +829,7 @@ ORDER BY actual_value DESC, c.name
         .bind(&request.description)
         .bind(id)
         .bind(user_id)
-        .fetch_one(&self.pool)
+        .fetch_one(&mut *tx)
         .await;
 
         let row = match row {
@@ -772,6 +840,17 @@ ORDER BY actual_value DESC, c.name
             Err(err) => return Err(err.into()),
         };
 
+        // A new parent has to cover the targets of the subtree moving under it
+        if let Some(guard) = guard {
+            Self::ensure_parent_targets_fit(&mut tx, guard, &[*id], user_id).await?;
+        }
+        tx.commit().await?;
+
+        // Direction rules of overlays depend on the category type
+        if previous_type.as_deref() != Some(row.category_type.as_str()) {
+            self.refresh_rule_overlay_membership_after_commit(user_id).await;
+        }
+
         Ok(Category::from(row))
     }
 
@@ -1087,6 +1166,7 @@ mod tests {
             description: None,
             budgeted_value: 0,
             actual_value: 4200,
+            rollover_enabled: false,
         };
 
         let converted = CategoryBudgetedDiagnosticsRow::from(row);
@@ -1109,10 +1189,13 @@ mod tests {
             description: None,
             budgeted_value: 1000,
             actual_value: -250,
+            rollover_enabled: true,
         };
 
         let converted = CategoryBudgetedDiagnosticsRow::from(row);
         assert_eq!(converted.variance_value, -1250);
         assert_eq!(converted.progress_basis_points, 0);
+        assert_eq!(converted.rolled_over_value, 0);
+        assert_eq!(converted.available_value, 1250);
     }
 }
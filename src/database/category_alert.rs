@@ -0,0 +1,177 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::category_alert::{CategoryAlertSetting, CategoryAlertSettingRequest, CategorySpendInPeriod};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+// Global thresholds come from settings; users without a settings row get the column default.
+const CATEGORY_ALERT_SETTING_SELECT: &str = r#"
+    SELECT
+        c.id AS category_id,
+        c.name AS category_name,
+        COALESCE(cas.thresholds, s.category_alert_thresholds, '{8000,10000}') AS thresholds,
+        cas.id IS NOT NULL AS is_custom,
+        COALESCE(cas.is_enabled, TRUE) AS is_enabled
+    FROM category c
+    LEFT JOIN category_alert_setting cas ON cas.category_id = c.id
+    LEFT JOIN settings s ON s.user_id = c.user_id
+"#;
+
+impl PostgresRepository {
+    pub async fn list_category_alert_settings(&self, user_id: &Uuid) -> Result<Vec<CategoryAlertSetting>, AppError> {
+        let query = format!(
+            "{} WHERE c.user_id = $1 AND c.category_type = 'Outgoing' AND c.is_archived = FALSE ORDER BY c.name ASC",
+            CATEGORY_ALERT_SETTING_SELECT
+        );
+        let settings = sqlx::query_as::<_, CategoryAlertSetting>(&query).bind(user_id).fetch_all(&self.pool).await?;
+
+        Ok(settings)
+    }
+
+    pub async fn upsert_category_alert_setting(
+        &self,
+        category_id: &Uuid,
+        request: &CategoryAlertSettingRequest,
+        user_id: &Uuid,
+    ) -> Result<CategoryAlertSetting, AppError> {
+        self.outgoing_category_exists(category_id, user_id).await?;
+
+        let mut thresholds = request.thresholds.clone();
+        thresholds.sort_unstable();
+        thresholds.dedup();
+
+        sqlx::query(
+            r#"
+            INSERT INTO category_alert_setting (user_id, category_id, thresholds, is_enabled)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (category_id)
+            DO UPDATE SET thresholds = EXCLUDED.thresholds, is_enabled = EXCLUDED.is_enabled, updated_at = now()
+            "#,
+        )
+        .bind(user_id)
+        .bind(category_id)
+        .bind(&thresholds)
+        .bind(request.is_enabled)
+        .execute(&self.pool)
+        .await?;
+
+        self.get_category_alert_setting(category_id, user_id).await
+    }
+
+    /// Drop the override so the category follows the global thresholds again.
+    pub async fn reset_category_alert_setting(&self, category_id: &Uuid, user_id: &Uuid) -> Result<CategoryAlertSetting, AppError> {
+        self.outgoing_category_exists(category_id, user_id).await?;
+
+        sqlx::query("DELETE FROM category_alert_setting WHERE category_id = $1 AND user_id = $2")
+            .bind(category_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        self.get_category_alert_setting(category_id, user_id).await
+    }
+
+    async fn get_category_alert_setting(&self, category_id: &Uuid, user_id: &Uuid) -> Result<CategoryAlertSetting, AppError> {
+        let query = format!("{} WHERE c.id = $1 AND c.user_id = $2", CATEGORY_ALERT_SETTING_SELECT);
+        let setting = sqlx::query_as::<_, CategoryAlertSetting>(&query)
+            .bind(category_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(setting)
+    }
+
+    async fn outgoing_category_exists(&self, category_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let category_type: Option<String> = sqlx::query_scalar("SELECT category_type::text FROM category WHERE id = $1 AND user_id = $2")
+            .bind(category_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match category_type.as_deref() {
+            None => Err(AppError::NotFound("Category not found".to_string())),
+            Some("Outgoing") => Ok(()),
+            Some(_) => Err(AppError::BadRequest("Alerts can only be set on Outgoing categories".to_string())),
+        }
+    }
+
+    /// Spending against the target of every alerting Outgoing category in the period covering `date`.
+    /// Categories without a target in that period are left out.
+    pub async fn list_category_spend_for_date(&self, user_id: &Uuid, date: NaiveDate) -> Result<Vec<CategorySpendInPeriod>, AppError> {
+        let rows = sqlx::query_as::<_, CategorySpendInPeriod>(
+            r#"
+            WITH period AS (
+                SELECT id, name, start_date, end_date
+                FROM budget_period
+                WHERE user_id = $1 AND start_date <= $2 AND end_date >= $2
+                ORDER BY start_date DESC
+                LIMIT 1
+            ),
+            targets AS (
+                SELECT
+                    c.id AS category_id,
+                    c.name AS category_name,
+                    budget_category_target_for_period(bc.id, p.id, p.start_date) AS target,
+                    COALESCE(cas.thresholds, s.category_alert_thresholds, '{8000,10000}') AS thresholds,
+                    p.id AS period_id,
+                    p.name AS period_name,
+                    p.start_date,
+                    p.end_date
+                FROM category c
+                CROSS JOIN period p
+                JOIN budget_category bc ON bc.category_id = c.id AND bc.user_id = $1 AND bc.is_excluded = FALSE
+                LEFT JOIN category_alert_setting cas ON cas.category_id = c.id
+                LEFT JOIN settings s ON s.user_id = $1
+                WHERE c.user_id = $1
+                  AND c.category_type = 'Outgoing'
+                  AND c.is_archived = FALSE
+                  AND COALESCE(cas.is_enabled, TRUE)
+            )
+            SELECT
+                tg.category_id,
+                tg.category_name,
+                tg.target,
+                COALESCE((
+                    SELECT SUM(t.amount)
+                    FROM transaction t
+                    WHERE t.user_id = $1
+                      AND t.category_id = tg.category_id
+                      AND t.occurred_at >= tg.start_date
+                      AND t.occurred_at <= tg.end_date
+                ), 0)::bigint AS spent,
+                tg.thresholds,
+                COALESCE(cur.symbol, '') AS currency_symbol,
+                COALESCE(cur.decimal_places, 2) AS currency_decimal_places,
+                tg.period_id,
+                tg.period_name
+            FROM targets tg
+            LEFT JOIN settings s ON s.user_id = $1
+            LEFT JOIN currency cur ON cur.id = s.default_currency_id
+            WHERE tg.target IS NOT NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Users with a budget period or an overlay covering `date`, i.e. those alerts can be raised for.
+    pub async fn list_users_with_period_on(&self, date: NaiveDate) -> Result<Vec<Uuid>, AppError> {
+        let user_ids = sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM budget_period WHERE start_date <= $1 AND end_date >= $1
+            UNION
+            SELECT user_id FROM overlays WHERE start_date <= $1 AND end_date >= $1
+            "#,
+        )
+        .bind(date)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(user_ids)
+    }
+}
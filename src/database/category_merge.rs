@@ -0,0 +1,388 @@
+use crate::database::budget_period_snapshot::ensure_merge_leaves_closed_periods;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::category::{Category, CategoryResponse};
+use crate::models::category_merge::{CategoryMerge, CategoryMergeCounts, CategoryMergePreviewResponse};
+use crate::models::category_tree::creates_category_cycle;
+use crate::models::overlay::{OverlayCategoryCap, OverlayRule, replace_cap_category_id};
+use sqlx::PgConnection;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const CATEGORY_MERGE_SELECT: &str = r#"
+    SELECT id, source_category_id, source_category_name, destination_category_id, destination_category_name, counts, created_at
+    FROM category_merge
+"#;
+
+#[derive(Debug, sqlx::FromRow)]
+struct MergeReferenceCounts {
+    transactions: i64,
+    subcategories: i64,
+    budget_targets: i64,
+    budget_targets_dropped: i64,
+    overlay_caps: i64,
+    overlay_caps_dropped: i64,
+    allowance_funding_rules: i64,
+    budget_template_entries: i64,
+    budget_template_entries_dropped: i64,
+    target_moves: i64,
+    target_moves_dropped: i64,
+    alert_settings: i64,
+    alert_settings_dropped: i64,
+}
+
+/// New rules of the overlays whose rules name the source category.
+async fn overlay_rule_rewrites(conn: &mut PgConnection, source_id: &Uuid, destination_id: &Uuid, user_id: &Uuid) -> Result<Vec<(Uuid, OverlayRule)>, AppError> {
+    let rows = sqlx::query_as::<_, (Uuid, sqlx::types::Json<OverlayRule>)>("SELECT id, rules FROM overlays WHERE user_id = $1 AND rules IS NOT NULL")
+        .bind(user_id)
+        .fetch_all(conn)
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, mut rules)| rules.0.replace_category_id(source_id, destination_id).then_some((id, rules.0)))
+        .collect())
+}
+
+/// New rules and caps of the overlay templates that name the source category in either.
+async fn overlay_template_rewrites(
+    conn: &mut PgConnection,
+    source_id: &Uuid,
+    destination_id: &Uuid,
+    user_id: &Uuid,
+) -> Result<Vec<(Uuid, Option<OverlayRule>, Vec<OverlayCategoryCap>)>, AppError> {
+    let rows = sqlx::query_as::<_, (Uuid, Option<sqlx::types::Json<OverlayRule>>, sqlx::types::Json<Vec<OverlayCategoryCap>>)>(
+        "SELECT id, rules, category_caps FROM overlay_template WHERE user_id = $1",
+    )
+    .bind(user_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .filter_map(|(id, rules, caps)| {
+            let mut rules = rules.map(|rules| rules.0);
+            let mut caps = caps.0;
+            let rules_changed = rules.as_mut().is_some_and(|rules| rules.replace_category_id(source_id, destination_id));
+            let caps_changed = replace_cap_category_id(&mut caps, source_id, destination_id);
+            (rules_changed || caps_changed).then_some((id, rules, caps))
+        })
+        .collect())
+}
+
+async fn merge_counts(conn: &mut PgConnection, source_id: &Uuid, destination_id: &Uuid, user_id: &Uuid) -> Result<CategoryMergeCounts, AppError> {
+    // Settings the destination already has are not moved: the source's copy is dropped with the source
+    let counts = sqlx::query_as::<_, MergeReferenceCounts>(
+        r#"
+        WITH destination AS (
+            SELECT
+                EXISTS (SELECT 1 FROM budget_category WHERE category_id = $2 AND user_id = $3) AS has_budget_target,
+                EXISTS (SELECT 1 FROM category_alert_setting WHERE category_id = $2 AND user_id = $3) AS has_alert_setting
+        ),
+        source_caps AS (
+            SELECT EXISTS (SELECT 1 FROM overlay_category_caps existing WHERE existing.overlay_id = occ.overlay_id AND existing.category_id = $2) AS conflicts
+            FROM overlay_category_caps occ
+            JOIN overlays o ON o.id = occ.overlay_id
+            WHERE occ.category_id = $1 AND o.user_id = $3
+        ),
+        source_template_entries AS (
+            SELECT EXISTS (
+                SELECT 1 FROM budget_template_entry existing WHERE existing.template_id = bte.template_id AND existing.category_id = $2
+            ) AS conflicts
+            FROM budget_template_entry bte
+            JOIN budget_template bt ON bt.id = bte.template_id
+            WHERE bte.category_id = $1 AND bt.user_id = $3
+        ),
+        source_moves AS (
+            SELECT (from_category_id = $2 OR to_category_id = $2) AS conflicts
+            FROM category_target_move
+            WHERE (from_category_id = $1 OR to_category_id = $1) AND user_id = $3
+        ),
+        source_settings AS (
+            SELECT
+                (SELECT COUNT(*) FROM budget_category WHERE category_id = $1 AND user_id = $3)::bigint AS budget_targets,
+                (SELECT COUNT(*) FROM category_alert_setting WHERE category_id = $1 AND user_id = $3)::bigint AS alert_settings
+        )
+        SELECT
+            (SELECT COUNT(*) FROM transaction WHERE category_id = $1 AND user_id = $3)::bigint AS transactions,
+            (SELECT COUNT(*) FROM category WHERE parent_id = $1 AND user_id = $3)::bigint AS subcategories,
+            CASE WHEN d.has_budget_target THEN 0 ELSE ss.budget_targets END AS budget_targets,
+            CASE WHEN d.has_budget_target THEN ss.budget_targets ELSE 0 END AS budget_targets_dropped,
+            (SELECT COUNT(*) FROM source_caps WHERE NOT conflicts)::bigint AS overlay_caps,
+            (SELECT COUNT(*) FROM source_caps WHERE conflicts)::bigint AS overlay_caps_dropped,
+            (SELECT COUNT(*) FROM allowance_funding_rule WHERE category_id = $1 AND user_id = $3)::bigint AS allowance_funding_rules,
+            (SELECT COUNT(*) FROM source_template_entries WHERE NOT conflicts)::bigint AS budget_template_entries,
+            (SELECT COUNT(*) FROM source_template_entries WHERE conflicts)::bigint AS budget_template_entries_dropped,
+            (SELECT COUNT(*) FROM source_moves WHERE NOT conflicts)::bigint AS target_moves,
+            (SELECT COUNT(*) FROM source_moves WHERE conflicts)::bigint AS target_moves_dropped,
+            CASE WHEN d.has_alert_setting THEN 0 ELSE ss.alert_settings END AS alert_settings,
+            CASE WHEN d.has_alert_setting THEN ss.alert_settings ELSE 0 END AS alert_settings_dropped
+        FROM destination d
+        CROSS JOIN source_settings ss
+        "#,
+    )
+    .bind(source_id)
+    .bind(destination_id)
+    .bind(user_id)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let overlay_rules = overlay_rule_rewrites(conn, source_id, destination_id, user_id).await?.len() as i64;
+    let overlay_templates = overlay_template_rewrites(conn, source_id, destination_id, user_id).await?.len() as i64;
+
+    Ok(CategoryMergeCounts {
+        transactions: counts.transactions,
+        subcategories: counts.subcategories,
+        budget_targets: counts.budget_targets,
+        budget_targets_dropped: counts.budget_targets_dropped,
+        overlay_caps: counts.overlay_caps,
+        overlay_caps_dropped: counts.overlay_caps_dropped,
+        overlay_rules,
+        overlay_templates,
+        allowance_funding_rules: counts.allowance_funding_rules,
+        budget_template_entries: counts.budget_template_entries,
+        budget_template_entries_dropped: counts.budget_template_entries_dropped,
+        target_moves: counts.target_moves,
+        target_moves_dropped: counts.target_moves_dropped,
+        alert_settings: counts.alert_settings,
+        alert_settings_dropped: counts.alert_settings_dropped,
+    })
+}
+
+#[allow(clippy::result_large_err)]
+fn ensure_mergeable(source: &Category, destination: &Category, parents: &HashMap<Uuid, Option<Uuid>>) -> Result<(), AppError> {
+    if source.id == destination.id {
+        return Err(AppError::BadRequest("A category cannot be merged into itself.".to_string()));
+    }
+    if source.category_type != destination.category_type {
+        return Err(AppError::BadRequest("Only categories of the same type can be merged.".to_string()));
+    }
+    if destination.is_archived {
+        return Err(AppError::BadRequest("Cannot merge into an archived category.".to_string()));
+    }
+    // The source's subcategories move under the destination, which must not be one of them
+    if creates_category_cycle(source.id, destination.id, parents) {
+        return Err(AppError::BadRequest("A category cannot be merged into one of its subcategories.".to_string()));
+    }
+    Ok(())
+}
+
+impl PostgresRepository {
+    async fn get_merge_categories(&self, source_id: &Uuid, destination_id: &Uuid, user_id: &Uuid) -> Result<(Category, Category), AppError> {
+        let source = self
+            .get_category_by_id(source_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Category not found".to_string()))?;
+        let destination = self
+            .get_category_by_id(destination_id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Destination category not found".to_string()))?;
+        Ok((source, destination))
+    }
+
+    /// What merging `source_id` into `destination_id` would move, without changing anything.
+    pub async fn get_category_merge_preview(&self, source_id: &Uuid, destination_id: &Uuid, user_id: &Uuid) -> Result<CategoryMergePreviewResponse, AppError> {
+        let (source, destination) = self.get_merge_categories(source_id, destination_id, user_id).await?;
+
+        let mut conn = self.pool.acquire().await?;
+        let parents: HashMap<Uuid, Option<Uuid>> = sqlx::query_as::<_, (Uuid, Option<Uuid>)>("SELECT id, parent_id FROM category WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&mut *conn)
+            .await?
+            .into_iter()
+            .collect();
+        ensure_mergeable(&source, &destination, &parents)?;
+        ensure_merge_leaves_closed_periods(&mut conn, Some(source_id), None, user_id).await?;
+
+        let counts = merge_counts(&mut conn, source_id, destination_id, user_id).await?;
+
+        Ok(CategoryMergePreviewResponse {
+            source: CategoryResponse::from(&source),
+            destination: CategoryResponse::from(&destination),
+            counts,
+        })
+    }
+
+    /// Move everything that refers to `source_id` over to `destination_id`, delete the source category and
+    /// record the merge. Settings both categories have keep the destination's value.
+    pub async fn merge_categories(&self, source_id: &Uuid, destination_id: &Uuid, user_id: &Uuid) -> Result<CategoryMerge, AppError> {
+        let (source, destination) = self.get_merge_categories(source_id, destination_id, user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the user's categories so a concurrent move cannot put the destination under the source
+        let parents: HashMap<Uuid, Option<Uuid>> =
+            sqlx::query_as::<_, (Uuid, Option<Uuid>)>("SELECT id, parent_id FROM category WHERE user_id = $1 FOR UPDATE")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+        if !parents.contains_key(source_id) {
+            return Err(AppError::NotFound("Category not found".to_string()));
+        }
+        ensure_mergeable(&source, &destination, &parents)?;
+        // Closed periods keep their snapshots, so their transactions cannot change category
+        ensure_merge_leaves_closed_periods(&mut tx, Some(source_id), None, user_id).await?;
+
+        let counts = merge_counts(&mut tx, source_id, destination_id, user_id).await?;
+        let overlay_rules = overlay_rule_rewrites(&mut tx, source_id, destination_id, user_id).await?;
+        let overlay_templates = overlay_template_rewrites(&mut tx, source_id, destination_id, user_id).await?;
+
+        let guard = Self::guard_parent_targets(&mut tx, None, user_id).await?;
+
+        sqlx::query("UPDATE category SET parent_id = $1 WHERE parent_id = $2 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query("UPDATE transaction SET category_id = $1 WHERE category_id = $2 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        // The source's target, with its history and overrides, only survives when the destination has none
+        sqlx::query(
+            r#"
+            UPDATE budget_category
+            SET category_id = $1
+            WHERE category_id = $2 AND user_id = $3
+              AND NOT EXISTS (SELECT 1 FROM budget_category WHERE category_id = $1 AND user_id = $3)
+            "#,
+        )
+        .bind(destination_id)
+        .bind(source_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE overlay_category_caps occ
+            SET category_id = $1
+            FROM overlays o
+            WHERE o.id = occ.overlay_id AND o.user_id = $3 AND occ.category_id = $2
+              AND NOT EXISTS (SELECT 1 FROM overlay_category_caps existing WHERE existing.overlay_id = occ.overlay_id AND existing.category_id = $1)
+            "#,
+        )
+        .bind(destination_id)
+        .bind(source_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        for (overlay_id, rules) in &overlay_rules {
+            sqlx::query("UPDATE overlays SET rules = $1, updated_at = now() WHERE id = $2 AND user_id = $3")
+                .bind(sqlx::types::Json(rules))
+                .bind(overlay_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        for (template_id, rules, caps) in &overlay_templates {
+            sqlx::query("UPDATE overlay_template SET rules = $1, category_caps = $2, updated_at = now() WHERE id = $3 AND user_id = $4")
+                .bind(rules.as_ref().map(sqlx::types::Json))
+                .bind(sqlx::types::Json(caps))
+                .bind(template_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("UPDATE allowance_funding_rule SET category_id = $1, updated_at = now() WHERE category_id = $2 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE budget_template_entry bte
+            SET category_id = $1
+            FROM budget_template bt
+            WHERE bt.id = bte.template_id AND bt.user_id = $3 AND bte.category_id = $2
+              AND NOT EXISTS (SELECT 1 FROM budget_template_entry existing WHERE existing.template_id = bte.template_id AND existing.category_id = $1)
+            "#,
+        )
+        .bind(destination_id)
+        .bind(source_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Moves between the two categories become moves within one category and go away with the source
+        sqlx::query("UPDATE category_target_move SET from_category_id = $1 WHERE from_category_id = $2 AND to_category_id <> $1 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE category_target_move SET to_category_id = $1 WHERE to_category_id = $2 AND from_category_id <> $1 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            UPDATE category_alert_setting
+            SET category_id = $1, updated_at = now()
+            WHERE category_id = $2 AND user_id = $3
+              AND NOT EXISTS (SELECT 1 FROM category_alert_setting WHERE category_id = $1)
+            "#,
+        )
+        .bind(destination_id)
+        .bind(source_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        // Whatever was not moved is a duplicate of a destination setting and cascades with the source
+        sqlx::query("DELETE FROM category WHERE id = $1 AND user_id = $2")
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let merge = sqlx::query_as::<_, CategoryMerge>(
+            r#"
+            INSERT INTO category_merge (user_id, source_category_id, source_category_name, destination_category_id, destination_category_name, counts)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, source_category_id, source_category_name, destination_category_id, destination_category_name, counts, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(source_id)
+        .bind(&source.name)
+        .bind(destination_id)
+        .bind(&destination.name)
+        .bind(sqlx::types::Json(&counts))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::ensure_parent_targets_fit(&mut tx, guard, &[*destination_id], user_id).await?;
+
+        tx.commit().await?;
+
+        // Rules may look at the category of the moved transactions
+        self.refresh_rule_overlay_membership_after_commit(user_id).await;
+
+        Ok(merge)
+    }
+
+    /// Past category merges, newest first.
+    pub async fn list_category_merges(&self, user_id: &Uuid) -> Result<Vec<CategoryMerge>, AppError> {
+        let merges = sqlx::query_as::<_, CategoryMerge>(&format!("{CATEGORY_MERGE_SELECT} WHERE user_id = $1 ORDER BY created_at DESC, id DESC"))
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+        Ok(merges)
+    }
+}
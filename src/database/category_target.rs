@@ -1,6 +1,14 @@
 use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
-use crate::models::category_target::{CategoryTargetRow, CategoryTargetsResponse, TargetEntry};
+use crate::models::category::available_value;
+use crate::models::category_target::{
+    CategoryTargetHistoryResponse, CategoryTargetMove, CategoryTargetRow, CategoryTargetsResponse, MoveTargetRequest, TargetAccrual, TargetDefaultVersion,
+    TargetEntry, TargetIncomeSource, TargetOverrideEntry, TargetOverrideRequest, TargetPrefillRequest, TargetPrefillSource, TargetPreviewResponse,
+    TargetPreviewRow,
+};
+use crate::models::settings::BudgetingMode;
+use chrono::NaiveDate;
+use std::collections::HashMap;
 use uuid::Uuid;
 
 #[derive(Debug, sqlx::FromRow)]
@@ -12,11 +20,33 @@ struct RawTargetRow {
     category_color: String,
     is_archived: bool,
     is_parent: bool,
+    parent_category_id: Option<Uuid>,
     parent_category_name: Option<String>,
     current_target: Option<i32>,
+    default_target: Option<i32>,
+    target_income_basis_points: Option<i32>,
+    target_income_source: Option<TargetIncomeSource>,
+    target_horizon_months: Option<i32>,
+    target_due_month: Option<i32>,
+    is_overridden: bool,
     previous_target: Option<i32>,
     is_excluded: bool,
     projected_variance_basis_points: Option<i32>,
+    rollover_enabled: bool,
+    spent_amount: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CategoryAccrualRow {
+    category_id: Uuid,
+    #[sqlx(flatten)]
+    accrual: TargetAccrual,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CategoryRolloverRow {
+    category_id: Uuid,
+    rolled_over: i64,
 }
 
 impl From<RawTargetRow> for CategoryTargetRow {
@@ -40,23 +70,50 @@ impl From<RawTargetRow> for CategoryTargetRow {
             category_color: row.category_color,
             is_archived: row.is_archived,
             is_parent: row.is_parent,
+            parent_category_id: row.parent_category_id,
             parent_category_name: row.parent_category_name,
             current_target: row.current_target,
+            default_target: row.default_target,
+            target_income_basis_points: row.target_income_basis_points,
+            target_income_source: row.target_income_source,
+            target_horizon_months: row.target_horizon_months,
+            target_due_month: row.target_due_month,
+            accrual: None,
+            is_overridden: row.is_overridden,
             previous_target: row.previous_target,
             is_excluded: row.is_excluded,
             exclusion_reason,
             projected_variance_basis_points: row.projected_variance_basis_points,
+            rollover_enabled: row.rollover_enabled,
+            rolled_over_amount: 0,
+            spent_amount: row.spent_amount,
+            available_amount: row.current_target.map(|target| available_value(target, 0, row.spent_amount)),
         }
     }
 }
 
+/// Number of closed periods averaged when a prefill request does not say otherwise.
+const DEFAULT_PREFILL_PERIODS: i32 = 3;
+
+// Sets the period override of a category's target to the target currently in effect plus `$4`.
+const ADJUST_TARGET_OVERRIDE_SQL: &str = r#"
+    INSERT INTO budget_category_period_override (user_id, budget_category_id, period_id, budgeted_value)
+    SELECT $1, bc.id, bp.id, COALESCE(budget_category_target_for_period(bc.id, bp.id, bp.start_date), 0) + $4
+    FROM budget_category bc
+    JOIN budget_period bp ON bp.id = $3 AND bp.user_id = $1
+    WHERE bc.user_id = $1 AND bc.category_id = $2
+    ON CONFLICT (budget_category_id, period_id)
+    DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value, updated_at = now()
+"#;
+
 impl PostgresRepository {
     /// Fetch all category targets for a given period.
     ///
     /// For each non-archived, non-Transfer category:
-    /// - current_target: the budgeted_value from budget_category (if it exists and is_excluded=false)
-    /// - previous_target: budgeted_value from the immediately preceding period
+    /// - current_target: the target in effect for this period (override or versioned default)
+    /// - previous_target: the target that applied to the immediately preceding period
     /// - projected_variance: basis points showing how much the actual spend deviates from target
+    /// - rolled_over/available: carry-over from earlier periods for categories with rollover enabled
     pub async fn get_category_targets(&self, period_id: &Uuid, user_id: &Uuid) -> Result<CategoryTargetsResponse, AppError> {
         // 1. Get period info
         #[derive(sqlx::FromRow)]
@@ -87,18 +144,7 @@ impl PostgresRepository {
         let progress_percent = ((elapsed_days * 100) / total_days) as i32;
 
         // 2. Find the previous period (the one with end_date <= this period's start_date)
-        let previous_period_id: Option<Uuid> = sqlx::query_scalar(
-            r#"
-            SELECT id FROM budget_period
-            WHERE user_id = $1 AND end_date <= $2
-            ORDER BY end_date DESC
-            LIMIT 1
-            "#,
-        )
-        .bind(user_id)
-        .bind(period.start_date)
-        .fetch_optional(&self.pool)
-        .await?;
+        let previous_period_id = self.previous_period_id(period.start_date, user_id).await?;
 
         // 3. Get all non-archived categories with their target data
         let rows = sqlx::query_as::<_, RawTargetRow>(
@@ -110,24 +156,34 @@ impl PostgresRepository {
                 FROM transaction t
                 WHERE t.user_id = $1
                     AND t.occurred_at >= $3
-                    AND t.occurred_at < $4
+                    AND t.occurred_at <= $4
                 GROUP BY t.category_id
             ),
             current_targets AS (
                 SELECT
                     bc.category_id,
-                    bc.budgeted_value,
-                    bc.is_excluded
+                    CASE WHEN bc.target_income_basis_points IS NULL THEN bc.budgeted_value END as default_target,
+                    bc.target_income_basis_points,
+                    bc.target_income_source,
+                    bc.target_horizon_months,
+                    bc.target_due_month,
+                    budget_category_target_for_period(bc.id, $2, $3) as budgeted_value,
+                    EXISTS (
+                        SELECT 1 FROM budget_category_period_override o
+                        WHERE o.budget_category_id = bc.id AND o.period_id = $2
+                    ) as is_overridden,
+                    bc.is_excluded,
+                    bc.rollover_enabled
                 FROM budget_category bc
                 WHERE bc.user_id = $1
             ),
             previous_targets AS (
                 SELECT
                     bc.category_id,
-                    bc.budgeted_value
+                    budget_category_target_for_period(bc.id, pp.id, pp.start_date) as budgeted_value
                 FROM budget_category bc
+                JOIN budget_period pp ON pp.id = $5::uuid
                 WHERE bc.user_id = $1
-                    AND ($5::uuid IS NOT NULL)
             )
             SELECT
                 c.id as category_id,
@@ -136,16 +192,25 @@ impl PostgresRepository {
                 COALESCE(c.icon, '') as category_icon,
                 COALESCE(c.color, '') as category_color,
                 c.is_archived,
-                (c.parent_id IS NULL) as is_parent,
+                EXISTS (SELECT 1 FROM category child WHERE child.parent_id = c.id) as is_parent,
+                c.parent_id as parent_category_id,
                 parent.name as parent_category_name,
                 ct.budgeted_value as current_target,
+                ct.default_target,
+                ct.target_income_basis_points,
+                ct.target_income_source,
+                ct.target_horizon_months,
+                ct.target_due_month,
+                COALESCE(ct.is_overridden, FALSE) as is_overridden,
                 pt.budgeted_value as previous_target,
                 COALESCE(ct.is_excluded, FALSE) as is_excluded,
                 CASE
                     WHEN ct.budgeted_value IS NOT NULL AND ct.budgeted_value > 0 THEN
                         ((COALESCE(ptx.total_amount, 0) * 10000) / ct.budgeted_value)::integer
                     ELSE NULL
-                END as projected_variance_basis_points
+                END as projected_variance_basis_points,
+                COALESCE(ct.rollover_enabled, FALSE) as rollover_enabled,
+                COALESCE(ptx.total_amount, 0)::bigint as spent_amount
             FROM category c
             LEFT JOIN category parent ON c.parent_id = parent.id
             LEFT JOIN current_targets ct ON ct.category_id = c.id
@@ -164,15 +229,35 @@ impl PostgresRepository {
         .fetch_all(&self.pool)
         .await?;
 
+        let rollovers = self.list_category_rollovers(period.start_date, user_id).await?;
+        let mut accruals = self.list_category_accruals(&period.id, user_id).await?;
+        let budgeting_mode = sqlx::query_scalar::<_, BudgetingMode>("SELECT budgeting_mode FROM settings WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .unwrap_or_default();
+
         let mut outgoing: Vec<CategoryTargetRow> = Vec::new();
         let mut incoming: Vec<CategoryTargetRow> = Vec::new();
         let mut excluded: Vec<CategoryTargetRow> = Vec::new();
         let mut total_targeted: i64 = 0;
         let mut targeted_count = 0;
+        let mut period_income: i64 = 0;
+        let mut total_assigned: i64 = 0;
         let total_categories = rows.len() as i32;
 
         for raw in rows {
-            let row = CategoryTargetRow::from(raw);
+            let mut row = CategoryTargetRow::from(raw);
+            row.accrual = accruals.remove(&row.category_id);
+            if let Some(rolled_over) = rollovers.get(&row.category_id) {
+                row.rolled_over_amount = *rolled_over;
+                row.available_amount = row.current_target.map(|target| available_value(target, *rolled_over, row.spent_amount));
+            }
+
+            // Income counts towards "ready to assign" even when the category is excluded from targets
+            if row.category_type == "Incoming" {
+                period_income += row.spent_amount;
+            }
 
             if row.is_excluded {
                 excluded.push(row);
@@ -180,6 +265,9 @@ impl PostgresRepository {
                 if let Some(target) = row.current_target {
                     total_targeted += target as i64;
                     targeted_count += 1;
+                    if row.category_type == "Outgoing" {
+                        total_assigned += target as i64;
+                    }
                 }
                 match row.category_type.as_str() {
                     "Outgoing" => outgoing.push(row),
@@ -189,6 +277,8 @@ impl PostgresRepository {
             }
         }
 
+        let ready_to_assign = (budgeting_mode == BudgetingMode::ZeroBased).then_some(period_income - total_assigned);
+
         Ok(CategoryTargetsResponse {
             period_id: period.id,
             period_name: period.name,
@@ -198,32 +288,71 @@ impl PostgresRepository {
             total_targeted,
             total_categories,
             targeted_categories: targeted_count,
+            is_over_assigned: ready_to_assign.is_some_and(|amount| amount < 0),
+            budgeting_mode,
+            period_income,
+            total_assigned,
+            ready_to_assign,
             outgoing_targets: outgoing,
             incoming_targets: incoming,
             excluded_categories: excluded,
         })
     }
 
-    /// Batch upsert category targets: for each target entry, create or update the budget_category row
+    /// Batch upsert category targets: for each target entry, create or update the budget_category row.
+    /// Percentage-of-income targets are resolved per period, so they are not available to Incoming categories.
     pub async fn batch_upsert_targets(&self, targets: &[TargetEntry], user_id: &Uuid) -> Result<(), AppError> {
+        let income_based: Vec<Uuid> = targets
+            .iter()
+            .filter(|entry| entry.income_basis_points.is_some())
+            .map(|entry| entry.category_id)
+            .collect();
+        if !income_based.is_empty() {
+            let incoming: bool =
+                sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM category WHERE id = ANY($1) AND user_id = $2 AND category_type = 'Incoming')")
+                    .bind(&income_based)
+                    .bind(user_id)
+                    .fetch_one(&self.pool)
+                    .await?;
+            if incoming {
+                return Err(AppError::BadRequest(
+                    "Incoming categories cannot have a percentage-of-income target".to_string(),
+                ));
+            }
+        }
+
         let mut tx = self.pool.begin().await?;
+        let guard = Self::guard_parent_targets(&mut tx, None, user_id).await?;
 
         for entry in targets {
             sqlx::query(
                 r#"
-                INSERT INTO budget_category (user_id, category_id, budgeted_value, is_excluded)
-                VALUES ($1, $2, $3, FALSE)
+                INSERT INTO budget_category (
+                    user_id, category_id, budgeted_value, is_excluded,
+                    target_income_basis_points, target_income_source, target_horizon_months, target_due_month
+                )
+                VALUES ($1, $2, $3, FALSE, $4, $5, $6, $7)
                 ON CONFLICT (user_id, category_id)
-                DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value
+                DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value,
+                              target_income_basis_points = EXCLUDED.target_income_basis_points,
+                              target_income_source = EXCLUDED.target_income_source,
+                              target_horizon_months = EXCLUDED.target_horizon_months,
+                              target_due_month = EXCLUDED.target_due_month
                 "#,
             )
             .bind(user_id)
             .bind(entry.category_id)
             .bind(entry.budgeted_value)
+            .bind(entry.income_basis_points)
+            .bind(entry.effective_income_source())
+            .bind(entry.horizon_months)
+            .bind(entry.due_month)
             .execute(&mut *tx)
             .await?;
         }
 
+        let touched: Vec<Uuid> = targets.iter().map(|entry| entry.category_id).collect();
+        Self::ensure_parent_targets_fit(&mut tx, guard, &touched, user_id).await?;
         tx.commit().await?;
         Ok(())
     }
@@ -262,4 +391,524 @@ impl PostgresRepository {
 
         Ok(())
     }
+
+    /// Net carry-over into the period starting on `period_start` for each Outgoing category with rollover enabled.
+    ///
+    /// Every period that ended before `period_start` (and not before rollover was switched on) contributes
+    /// `budgeted - spent`, so both leftovers and overspending carry forward.
+    pub async fn list_category_rollovers(&self, period_start: NaiveDate, user_id: &Uuid) -> Result<HashMap<Uuid, i64>, AppError> {
+        let rows = sqlx::query_as::<_, CategoryRolloverRow>(
+            r#"
+            SELECT
+                bc.category_id,
+                COALESCE(SUM(
+                    COALESCE(budget_category_target_for_period(bc.id, bp.id, bp.start_date), 0)::bigint - COALESCE(spend.actual_value, 0)
+                ), 0)::bigint AS rolled_over
+            FROM budget_category bc
+            JOIN category c
+              ON c.id = bc.category_id
+             AND c.user_id = $1
+             AND c.category_type = 'Outgoing'
+            JOIN budget_period bp
+              ON bp.user_id = $1
+             AND bp.end_date < $2
+             AND bp.end_date >= bc.rollover_started_on
+            LEFT JOIN LATERAL (
+                SELECT SUM(t.amount)::bigint AS actual_value
+                FROM transaction t
+                WHERE t.user_id = $1
+                  AND t.category_id = bc.category_id
+                  AND t.occurred_at >= bp.start_date
+                  AND t.occurred_at <= bp.end_date
+            ) spend ON TRUE
+            WHERE bc.user_id = $1
+              AND bc.rollover_enabled = TRUE
+              AND bc.is_excluded = FALSE
+            GROUP BY bc.category_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_start)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.category_id, row.rolled_over)).collect())
+    }
+
+    /// Accrued vs. spent in the current horizon cycle for each category whose target for the period has a long horizon.
+    pub async fn list_category_accruals(&self, period_id: &Uuid, user_id: &Uuid) -> Result<HashMap<Uuid, TargetAccrual>, AppError> {
+        let rows = sqlx::query_as::<_, CategoryAccrualRow>(
+            r#"
+            SELECT bc.category_id, acc.*
+            FROM budget_category bc
+            CROSS JOIN LATERAL budget_category_accrual_for_period(bc.id, $2) acc
+            WHERE bc.user_id = $1
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.category_id, row.accrual)).collect())
+    }
+
+    /// Turn on rollover for an Outgoing category that has a target. Carry-over starts with the period that
+    /// contains today.
+    pub async fn enable_category_rollover(&self, category_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let category_type: Option<String> = sqlx::query_scalar("SELECT category_type::text FROM category WHERE id = $1 AND user_id = $2")
+            .bind(category_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        match category_type.as_deref() {
+            None => return Err(AppError::NotFound("Category not found".to_string())),
+            Some("Outgoing") => {}
+            Some(_) => return Err(AppError::BadRequest("Rollover is only available for Outgoing categories".to_string())),
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE budget_category
+            SET rollover_enabled = TRUE,
+                rollover_started_on = COALESCE(rollover_started_on, CURRENT_DATE)
+            WHERE user_id = $1 AND category_id = $2 AND is_excluded = FALSE
+            "#,
+        )
+        .bind(user_id)
+        .bind(category_id)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::BadRequest("Set a target for the category before enabling rollover".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Turn off rollover for a category; any accumulated carry-over is dropped.
+    pub async fn disable_category_rollover(&self, category_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE budget_category
+            SET rollover_enabled = FALSE,
+                rollover_started_on = NULL
+            WHERE category_id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(category_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Move part of one Outgoing category's target to another and record the move for the period.
+    pub async fn move_category_target(&self, request: &MoveTargetRequest, user_id: &Uuid) -> Result<CategoryTargetMove, AppError> {
+        self.open_period_start(&request.period_id, user_id).await?;
+
+        let outgoing_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM category WHERE id IN ($1, $2) AND user_id = $3 AND category_type = 'Outgoing'")
+            .bind(request.from_category_id)
+            .bind(request.to_category_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if outgoing_count != 2 {
+            return Err(AppError::BadRequest(
+                "Targets can only be moved between Outgoing categories of the current user".to_string(),
+            ));
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let guard = Self::guard_parent_targets(&mut tx, Some(&request.period_id), user_id).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_category (user_id, category_id, budgeted_value, is_excluded)
+            VALUES ($1, $2, 0, FALSE)
+            ON CONFLICT (user_id, category_id)
+            DO UPDATE SET is_excluded = FALSE
+            "#,
+        )
+        .bind(user_id)
+        .bind(request.to_category_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let from_target: Option<i32> = sqlx::query_scalar(
+            r#"
+            SELECT budget_category_target_for_period(bc.id, bp.id, bp.start_date)
+            FROM budget_category bc
+            JOIN budget_period bp ON bp.id = $3
+            WHERE bc.category_id = $1 AND bc.user_id = $2 AND bc.is_excluded = FALSE
+            FOR UPDATE OF bc
+            "#,
+        )
+        .bind(request.from_category_id)
+        .bind(user_id)
+        .bind(request.period_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .flatten();
+        if from_target.unwrap_or(0) < request.amount {
+            return Err(AppError::BadRequest("Cannot move more than the source category's target".to_string()));
+        }
+
+        // Moves only touch this period: both sides become period overrides and the defaults stay as they are
+        for (category_id, delta) in [(request.from_category_id, -request.amount), (request.to_category_id, request.amount)] {
+            sqlx::query(ADJUST_TARGET_OVERRIDE_SQL)
+                .bind(user_id)
+                .bind(category_id)
+                .bind(request.period_id)
+                .bind(delta)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let target_move = sqlx::query_as::<_, CategoryTargetMove>(
+            r#"
+            INSERT INTO category_target_move (user_id, period_id, from_category_id, to_category_id, amount, note)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, period_id, from_category_id, to_category_id, amount, note, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(request.period_id)
+        .bind(request.from_category_id)
+        .bind(request.to_category_id)
+        .bind(request.amount)
+        .bind(&request.note)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        Self::ensure_parent_targets_fit(&mut tx, guard, &[request.from_category_id, request.to_category_id], user_id).await?;
+        tx.commit().await?;
+        Ok(target_move)
+    }
+
+    pub async fn list_category_target_moves(&self, period_id: &Uuid, user_id: &Uuid) -> Result<Vec<CategoryTargetMove>, AppError> {
+        let moves = sqlx::query_as::<_, CategoryTargetMove>(
+            r#"
+            SELECT id, period_id, from_category_id, to_category_id, amount, note, created_at
+            FROM category_target_move
+            WHERE period_id = $1 AND user_id = $2
+            ORDER BY created_at DESC, id DESC
+            "#,
+        )
+        .bind(period_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(moves)
+    }
+
+    /// Override a category's target for one period. Categories without a target get a default of zero.
+    pub async fn set_category_target_override(&self, category_id: &Uuid, request: &TargetOverrideRequest, user_id: &Uuid) -> Result<(), AppError> {
+        self.open_period_start(&request.period_id, user_id).await?;
+
+        let category_type: Option<String> = sqlx::query_scalar("SELECT category_type::text FROM category WHERE id = $1 AND user_id = $2")
+            .bind(category_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        match category_type.as_deref() {
+            None => return Err(AppError::NotFound("Category not found".to_string())),
+            Some("Transfer") => return Err(AppError::BadRequest("Transfer categories do not have targets".to_string())),
+            Some(_) => {}
+        }
+
+        let mut tx = self.pool.begin().await?;
+        let guard = Self::guard_parent_targets(&mut tx, Some(&request.period_id), user_id).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_category (user_id, category_id, budgeted_value, is_excluded)
+            VALUES ($1, $2, 0, FALSE)
+            ON CONFLICT (user_id, category_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(category_id)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_category_period_override (user_id, budget_category_id, period_id, budgeted_value)
+            SELECT $1, bc.id, $3, $4
+            FROM budget_category bc
+            WHERE bc.user_id = $1 AND bc.category_id = $2
+            ON CONFLICT (budget_category_id, period_id)
+            DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value, updated_at = now()
+            "#,
+        )
+        .bind(user_id)
+        .bind(category_id)
+        .bind(request.period_id)
+        .bind(request.budgeted_value)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::ensure_parent_targets_fit(&mut tx, guard, &[*category_id], user_id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Remove a period override so the period falls back to the default target.
+    pub async fn delete_category_target_override(&self, category_id: &Uuid, period_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        self.open_period_start(period_id, user_id).await?;
+
+        sqlx::query(
+            r#"
+            DELETE FROM budget_category_period_override o
+            USING budget_category bc
+            WHERE o.budget_category_id = bc.id
+              AND bc.category_id = $1
+              AND o.period_id = $2
+              AND o.user_id = $3
+            "#,
+        )
+        .bind(category_id)
+        .bind(period_id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_category_target_history(&self, category_id: &Uuid, user_id: &Uuid) -> Result<CategoryTargetHistoryResponse, AppError> {
+        #[derive(sqlx::FromRow)]
+        struct BudgetCategoryRef {
+            id: Uuid,
+            budgeted_value: i32,
+        }
+
+        let budget_category = sqlx::query_as::<_, BudgetCategoryRef>("SELECT id, budgeted_value FROM budget_category WHERE category_id = $1 AND user_id = $2")
+            .bind(category_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Category has no target".to_string()))?;
+
+        let defaults = sqlx::query_as::<_, TargetDefaultVersion>(
+            r#"
+            SELECT
+                budgeted_value,
+                target_income_basis_points,
+                target_income_source,
+                target_horizon_months,
+                target_due_month,
+                CASE WHEN isfinite(effective_from) THEN effective_from END AS effective_from
+            FROM budget_category_target_history
+            WHERE budget_category_id = $1
+            ORDER BY effective_from DESC
+            "#,
+        )
+        .bind(budget_category.id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let overrides = sqlx::query_as::<_, TargetOverrideEntry>(
+            r#"
+            SELECT
+                bp.id AS period_id,
+                bp.name AS period_name,
+                bp.start_date AS period_start_date,
+                o.budgeted_value
+            FROM budget_category_period_override o
+            JOIN budget_period bp ON bp.id = o.period_id
+            WHERE o.budget_category_id = $1
+            ORDER BY bp.start_date DESC
+            "#,
+        )
+        .bind(budget_category.id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(CategoryTargetHistoryResponse {
+            category_id: *category_id,
+            default_target: budget_category.budgeted_value,
+            defaults,
+            overrides,
+        })
+    }
+
+    async fn previous_period_id(&self, start_date: NaiveDate, user_id: &Uuid) -> Result<Option<Uuid>, AppError> {
+        let previous_period_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM budget_period
+            WHERE user_id = $1 AND end_date <= $2
+            ORDER BY end_date DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(start_date)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(previous_period_id)
+    }
+
+    /// Start date of a period that targets may still be written to. Closed periods keep their targets.
+    pub async fn open_period_start(&self, period_id: &Uuid, user_id: &Uuid) -> Result<NaiveDate, AppError> {
+        #[derive(sqlx::FromRow)]
+        struct PeriodState {
+            start_date: NaiveDate,
+            is_closed: bool,
+        }
+
+        let period =
+            sqlx::query_as::<_, PeriodState>("SELECT start_date, closed_at IS NOT NULL AS is_closed FROM budget_period WHERE id = $1 AND user_id = $2")
+                .bind(period_id)
+                .bind(user_id)
+                .fetch_optional(&self.pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Budget period not found".to_string()))?;
+
+        if period.is_closed {
+            return Err(AppError::BadRequest("Targets of closed periods cannot be changed".to_string()));
+        }
+
+        Ok(period.start_date)
+    }
+
+    /// Proposed targets for a period, computed from the previous period's targets or recent actuals. Nothing is saved.
+    pub async fn preview_target_prefill(&self, request: &TargetPrefillRequest, user_id: &Uuid) -> Result<TargetPreviewResponse, AppError> {
+        let start_date = self.open_period_start(&request.period_id, user_id).await?;
+
+        let rows = match request.source {
+            TargetPrefillSource::PreviousPeriod => {
+                let Some(previous_period_id) = self.previous_period_id(start_date, user_id).await? else {
+                    return Err(AppError::BadRequest("There is no previous period to copy targets from".to_string()));
+                };
+
+                sqlx::query_as::<_, TargetPreviewRow>(
+                    r#"
+                    SELECT
+                        c.id AS category_id,
+                        c.name AS category_name,
+                        budget_category_target_for_period(bc.id, $2, $3) AS current_target,
+                        budget_category_target_for_period(bc.id, pp.id, pp.start_date) AS proposed_target,
+                        FALSE AS is_excluded
+                    FROM budget_category bc
+                    JOIN category c ON c.id = bc.category_id
+                    JOIN budget_period pp ON pp.id = $4
+                    WHERE bc.user_id = $1
+                      AND bc.is_excluded = FALSE
+                      AND c.is_archived = FALSE
+                      AND c.category_type != 'Transfer'
+                      AND budget_category_target_for_period(bc.id, pp.id, pp.start_date) IS NOT NULL
+                    ORDER BY c.name ASC
+                    "#,
+                )
+                .bind(user_id)
+                .bind(request.period_id)
+                .bind(start_date)
+                .bind(previous_period_id)
+                .fetch_all(&self.pool)
+                .await?
+            }
+            TargetPrefillSource::AverageActuals => {
+                sqlx::query_as::<_, TargetPreviewRow>(
+                    r#"
+                    WITH source_periods AS (
+                        SELECT bp.start_date, bp.end_date
+                        FROM budget_period bp
+                        WHERE bp.user_id = $1
+                          AND bp.end_date < $3
+                          AND bp.end_date < CURRENT_DATE
+                        ORDER BY bp.end_date DESC
+                        LIMIT $4
+                    ),
+                    actuals AS (
+                        SELECT t.category_id, SUM(t.amount)::bigint AS total
+                        FROM transaction t
+                        JOIN source_periods sp ON t.occurred_at >= sp.start_date AND t.occurred_at <= sp.end_date
+                        WHERE t.user_id = $1
+                        GROUP BY t.category_id
+                    )
+                    SELECT
+                        c.id AS category_id,
+                        c.name AS category_name,
+                        budget_category_target_for_period(bc.id, $2, $3) AS current_target,
+                        ROUND(a.total::numeric / (SELECT COUNT(*) FROM source_periods))::integer AS proposed_target,
+                        FALSE AS is_excluded
+                    FROM actuals a
+                    JOIN category c ON c.id = a.category_id
+                    LEFT JOIN budget_category bc ON bc.category_id = c.id AND bc.user_id = $1
+                    WHERE c.user_id = $1
+                      AND c.is_archived = FALSE
+                      AND c.category_type != 'Transfer'
+                      AND COALESCE(bc.is_excluded, FALSE) = FALSE
+                      AND a.total > 0
+                    ORDER BY c.name ASC
+                    "#,
+                )
+                .bind(user_id)
+                .bind(request.period_id)
+                .bind(start_date)
+                .bind(i64::from(request.periods.unwrap_or(DEFAULT_PREFILL_PERIODS)))
+                .fetch_all(&self.pool)
+                .await?
+            }
+        };
+
+        Ok(TargetPreviewResponse {
+            period_id: request.period_id,
+            rows,
+        })
+    }
+
+    /// Write the given targets as overrides for one period. Categories without a target get the value as their default
+    /// too, and each category's exclusion flag is updated to match the row.
+    pub async fn apply_period_targets(&self, period_id: &Uuid, rows: &[TargetPreviewRow], user_id: &Uuid) -> Result<(), AppError> {
+        let category_ids: Vec<Uuid> = rows.iter().map(|row| row.category_id).collect();
+        let values: Vec<i32> = rows.iter().map(|row| row.proposed_target).collect();
+        let exclusions: Vec<bool> = rows.iter().map(|row| row.is_excluded).collect();
+
+        let mut tx = self.pool.begin().await?;
+        let guard = Self::guard_parent_targets(&mut tx, Some(period_id), user_id).await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_category (user_id, category_id, budgeted_value, is_excluded)
+            SELECT $1, i.category_id, i.budgeted_value, i.is_excluded
+            FROM UNNEST($2::uuid[], $3::integer[], $4::boolean[]) AS i(category_id, budgeted_value, is_excluded)
+            ON CONFLICT (user_id, category_id)
+            DO UPDATE SET is_excluded = EXCLUDED.is_excluded
+            "#,
+        )
+        .bind(user_id)
+        .bind(&category_ids)
+        .bind(&values)
+        .bind(&exclusions)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO budget_category_period_override (user_id, budget_category_id, period_id, budgeted_value)
+            SELECT $1, bc.id, $4, i.budgeted_value
+            FROM UNNEST($2::uuid[], $3::integer[]) AS i(category_id, budgeted_value)
+            JOIN budget_category bc ON bc.category_id = i.category_id AND bc.user_id = $1
+            ON CONFLICT (budget_category_id, period_id)
+            DO UPDATE SET budgeted_value = EXCLUDED.budgeted_value, updated_at = now()
+            "#,
+        )
+        .bind(user_id)
+        .bind(&category_ids)
+        .bind(&values)
+        .bind(period_id)
+        .execute(&mut *tx)
+        .await?;
+
+        Self::ensure_parent_targets_fit(&mut tx, guard, &category_ids, user_id).await?;
+        tx.commit().await?;
+        Ok(())
+    }
 }
@@ -0,0 +1,244 @@
+use crate::database::category::category_type_from_db;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::category::Category;
+use crate::models::category_tree::{
+    CategoryTreeEntry, CategoryTreeResponse, OverAllocatedCategoryResponse, build_category_tree, creates_category_cycle, newly_over_allocated,
+    over_allocated_categories, over_allocations,
+};
+use sqlx::{PgConnection, Postgres, Transaction};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+const CATEGORY_CYCLE_MESSAGE: &str = "A category cannot be moved under itself or one of its subcategories.";
+
+/// Over-allocation of parent targets before a write; see `PostgresRepository::guard_parent_targets`.
+pub(crate) struct ParentTargetGuard {
+    period_id: Option<Uuid>,
+    before: HashMap<Uuid, i64>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CategoryTreeRow {
+    id: Uuid,
+    name: String,
+    color: String,
+    icon: String,
+    parent_id: Option<Uuid>,
+    category_type: String,
+    is_archived: bool,
+    description: Option<String>,
+    spent_amount: i64,
+    transaction_count: i64,
+    target: Option<i32>,
+}
+
+impl From<CategoryTreeRow> for CategoryTreeEntry {
+    fn from(row: CategoryTreeRow) -> Self {
+        Self {
+            category: Category {
+                id: row.id,
+                name: row.name,
+                color: row.color,
+                icon: row.icon,
+                parent_id: row.parent_id,
+                category_type: category_type_from_db(&row.category_type),
+                is_archived: row.is_archived,
+                description: row.description,
+            },
+            spent_amount: row.spent_amount,
+            transaction_count: row.transaction_count,
+            target: row.target,
+        }
+    }
+}
+
+/// Every category of a user with its spend and target in a period. Without a period there is no spend and
+/// targets are the fixed default targets.
+async fn list_category_tree_entries(conn: &mut PgConnection, period_id: Option<&Uuid>, user_id: &Uuid) -> Result<Vec<CategoryTreeEntry>, AppError> {
+    let rows = sqlx::query_as::<_, CategoryTreeRow>(
+        r#"
+        WITH period AS (
+            SELECT id, start_date, end_date
+            FROM budget_period
+            WHERE id = $2 AND user_id = $1
+        ),
+        period_spend AS (
+            SELECT t.category_id, SUM(t.amount)::bigint AS spent_amount, COUNT(*)::bigint AS transaction_count
+            FROM transaction t
+            JOIN period p ON t.occurred_at >= p.start_date AND t.occurred_at <= p.end_date
+            WHERE t.user_id = $1
+            GROUP BY t.category_id
+        )
+        SELECT
+            c.id,
+            c.name,
+            COALESCE(c.color, '') AS color,
+            COALESCE(c.icon, '') AS icon,
+            c.parent_id,
+            c.category_type::text AS category_type,
+            c.is_archived,
+            c.description,
+            COALESCE(ps.spent_amount, 0) AS spent_amount,
+            COALESCE(ps.transaction_count, 0) AS transaction_count,
+            CASE
+                WHEN bc.id IS NULL OR bc.is_excluded THEN NULL
+                WHEN p.id IS NOT NULL THEN budget_category_target_for_period(bc.id, p.id, p.start_date)
+                WHEN bc.target_income_basis_points IS NULL AND bc.target_horizon_months IS NULL THEN bc.budgeted_value
+            END AS target
+        FROM category c
+        LEFT JOIN budget_category bc ON bc.category_id = c.id AND bc.user_id = $1
+        LEFT JOIN period_spend ps ON ps.category_id = c.id
+        LEFT JOIN period p ON TRUE
+        WHERE c.user_id = $1
+        "#,
+    )
+    .bind(user_id)
+    .bind(period_id)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows.into_iter().map(CategoryTreeEntry::from).collect())
+}
+
+impl PostgresRepository {
+    /// All categories nested under their parents, with spend, transaction counts and targets of the period
+    /// rolled up through every level.
+    pub async fn get_category_tree(&self, period_id: &Uuid, user_id: &Uuid) -> Result<CategoryTreeResponse, AppError> {
+        let period_exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM budget_period WHERE id = $1 AND user_id = $2)")
+            .bind(period_id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+        if !period_exists {
+            return Err(AppError::NotFound("Budget period not found".to_string()));
+        }
+
+        let mut conn = self.pool.acquire().await?;
+        let entries = list_category_tree_entries(&mut conn, Some(period_id), user_id).await?;
+
+        Ok(CategoryTreeResponse {
+            period_id: *period_id,
+            categories: build_category_tree(entries),
+        })
+    }
+
+    /// Record how over-allocated parent targets are before a write, for `ensure_parent_targets_fit` to compare
+    /// against. Uses the period's targets when given, otherwise the default targets.
+    pub(crate) async fn guard_parent_targets(
+        tx: &mut Transaction<'_, Postgres>,
+        period_id: Option<&Uuid>,
+        user_id: &Uuid,
+    ) -> Result<ParentTargetGuard, AppError> {
+        let entries = list_category_tree_entries(tx, period_id, user_id).await?;
+        Ok(ParentTargetGuard {
+            period_id: period_id.copied(),
+            before: over_allocations(&build_category_tree(entries)),
+        })
+    }
+
+    /// Fail when a write to the `touched` categories left one of them, or a category above them, with a target
+    /// that covers less of its subcategories' targets than before. Runs inside `tx` so pending changes count.
+    pub(crate) async fn ensure_parent_targets_fit(
+        tx: &mut Transaction<'_, Postgres>,
+        guard: ParentTargetGuard,
+        touched: &[Uuid],
+        user_id: &Uuid,
+    ) -> Result<(), AppError> {
+        let entries = list_category_tree_entries(tx, guard.period_id.as_ref(), user_id).await?;
+        let tree = build_category_tree(entries);
+        let touched: HashSet<Uuid> = touched.iter().copied().collect();
+
+        if let Some(node) = newly_over_allocated(&tree, &touched, &guard.before) {
+            return Err(AppError::BadRequest(format!(
+                "The subcategory targets of {} add up to {}, more than its target of {}",
+                node.category.name,
+                node.children_target_total.unwrap_or(0),
+                node.target.unwrap_or(0)
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Parents whose target does not cover their subcategories' targets, in a period or by default targets.
+    pub async fn list_over_allocated_categories(&self, period_id: Option<&Uuid>, user_id: &Uuid) -> Result<Vec<OverAllocatedCategoryResponse>, AppError> {
+        if let Some(period_id) = period_id {
+            self.get_budget_period(period_id, user_id).await?;
+        }
+
+        let mut conn = self.pool.acquire().await?;
+        let entries = list_category_tree_entries(&mut conn, period_id, user_id).await?;
+        let tree = build_category_tree(entries);
+        Ok(over_allocated_categories(&tree).into_iter().map(OverAllocatedCategoryResponse::from).collect())
+    }
+
+    /// Move a category and its subcategories under a new parent, or to the top level.
+    pub async fn move_category(&self, id: &Uuid, parent_id: Option<&Uuid>, user_id: &Uuid) -> Result<Category, AppError> {
+        let category = self
+            .get_category_by_id(id, user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Category not found".to_string()))?;
+
+        if let Some(parent_id) = parent_id {
+            let parent = self
+                .get_category_by_id(parent_id, user_id)
+                .await?
+                .ok_or_else(|| AppError::NotFound("Parent category not found".to_string()))?;
+            if parent.category_type != category.category_type {
+                return Err(AppError::BadRequest("Subcategory must have the same type as its parent.".to_string()));
+            }
+            if parent.is_archived {
+                return Err(AppError::BadRequest("Cannot set parent to an archived category.".to_string()));
+            }
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the user's categories so concurrent moves cannot combine into a cycle
+        let parents: HashMap<Uuid, Option<Uuid>> =
+            sqlx::query_as::<_, (Uuid, Option<Uuid>)>("SELECT id, parent_id FROM category WHERE user_id = $1 FOR UPDATE")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+        if let Some(parent_id) = parent_id
+            && creates_category_cycle(*id, *parent_id, &parents)
+        {
+            return Err(AppError::BadRequest(CATEGORY_CYCLE_MESSAGE.to_string()));
+        }
+        let guard = Self::guard_parent_targets(&mut tx, None, user_id).await?;
+
+        sqlx::query("UPDATE category SET parent_id = $1 WHERE id = $2 AND user_id = $3")
+            .bind(parent_id)
+            .bind(id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        Self::ensure_parent_targets_fit(&mut tx, guard, &[*id], user_id).await?;
+
+        tx.commit().await?;
+
+        Ok(Category {
+            parent_id: parent_id.copied(),
+            ..category
+        })
+    }
+
+    /// Reject a new parent for `id` that is `id` itself or one of its subcategories.
+    pub(crate) async fn ensure_no_category_cycle(&self, id: &Uuid, parent_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let parents: HashMap<Uuid, Option<Uuid>> = sqlx::query_as::<_, (Uuid, Option<Uuid>)>("SELECT id, parent_id FROM category WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .collect();
+        if creates_category_cycle(*id, *parent_id, &parents) {
+            return Err(AppError::BadRequest(CATEGORY_CYCLE_MESSAGE.to_string()));
+        }
+
+        Ok(())
+    }
+}
@@ -115,8 +115,20 @@ ORDER BY a.name, d.day
             amount_spent: i64,
         }
 
-        let rows = sqlx::query_as::<_, SpentPerCategoryRow>(
-            r#"
+        let snapshots = self.list_category_snapshots(budget_period_id, user_id).await?;
+        let rows = if !snapshots.is_empty() {
+            snapshots
+                .into_iter()
+                .filter(|category| category.category_type == "Outgoing")
+                .map(|category| SpentPerCategoryRow {
+                    category_name: category.category_name,
+                    budgeted_value: category.target.map(i64::from).unwrap_or(0),
+                    amount_spent: category.actual,
+                })
+                .collect()
+        } else {
+            sqlx::query_as::<_, SpentPerCategoryRow>(
+                r#"
 WITH period_transactions AS (
     SELECT t.category_id, t.amount
     FROM transaction t
@@ -128,20 +140,22 @@ WITH period_transactions AS (
       AND t.occurred_at <= bp.end_date
 )
 SELECT c.name                                AS category_name,
-       bc.budgeted_value::bigint             AS budgeted_value,
+       COALESCE(budget_category_target_for_period(bc.id, bp.id, bp.start_date), 0)::bigint AS budgeted_value,
        COALESCE(SUM(pt.amount), 0)::bigint   AS amount_spent
 FROM budget_category bc
 JOIN  category c                ON c.id  = bc.category_id
+JOIN  budget_period bp          ON bp.id = $1 AND bp.user_id = $2
 LEFT JOIN period_transactions pt ON c.id = pt.category_id
 WHERE bc.user_id = $2
   AND c.category_type = 'Outgoing'
-GROUP BY c.name, bc.budgeted_value
+GROUP BY c.name, bc.id, bp.id, bp.start_date
             "#,
-        )
-        .bind(budget_period_id)
-        .bind(user_id)
-        .fetch_all(&self.pool)
-        .await?;
+            )
+            .bind(budget_period_id)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?
+        };
 
         Ok(rows
             .into_iter()
@@ -167,8 +181,9 @@ GROUP BY c.name, bc.budgeted_value
         let row = sqlx::query_as::<_, MonthlyBurnInResponse>(
             r#"
 WITH total_budget AS (
-    SELECT COALESCE(SUM(bc.budgeted_value), 0)::bigint AS value
+    SELECT COALESCE(SUM(budget_category_target_for_period(bc.id, bp.id, bp.start_date)), 0)::bigint AS value
     FROM budget_category bc
+    JOIN budget_period bp ON bp.id = $1 AND bp.user_id = $2
     WHERE bc.user_id = $2
 ),
 spent_budget AS (
@@ -316,22 +331,40 @@ GROUP BY aib.balance_total
 
         let closed_period_rows = sqlx::query_as::<_, ClosedPeriodRow>(
             r#"
-            WITH total_budget AS (
-                SELECT COALESCE(SUM(budgeted_value), 0)::bigint AS value
-                FROM budget_category
-                WHERE user_id = $1
-            )
             SELECT
                 bp.id::text AS period_id,
-                tb.value AS total_budget,
-                COALESCE(SUM(
+                -- Closed periods are judged by their closing snapshot
+                COALESCE(s.total_budgeted, tb.value) AS total_budget,
+                COALESCE(s.stability_spent, COALESCE(SUM(
                     CASE
                         WHEN c.category_type = 'Outgoing' THEN t.amount
                         ELSE 0
                     END
-                ), 0)::bigint AS spent_budget
+                ), 0)::bigint - ha.value) AS spent_budget
             FROM budget_period bp
-            CROSS JOIN total_budget tb
+            LEFT JOIN budget_period_snapshot s ON s.period_id = bp.id
+            CROSS JOIN LATERAL (
+                -- Targets as they were for this period, so later changes don't rewrite closed periods
+                SELECT COALESCE(SUM(budget_category_target_for_period(bc.id, bp.id, bp.start_date)), 0)::bigint AS value
+                FROM budget_category bc
+                WHERE bc.user_id = $1
+            ) tb
+            CROSS JOIN LATERAL (
+                -- Long-horizon targets paid within their horizon amount count as their accrual, not the lump sum
+                SELECT COALESCE(SUM(hs.actual_value - COALESCE(budget_category_target_for_period(bc.id, bp.id, bp.start_date), 0)), 0)::bigint AS value
+                FROM budget_category bc
+                JOIN category hc ON hc.id = bc.category_id AND hc.category_type = 'Outgoing'
+                JOIN LATERAL budget_category_accrual_for_period(bc.id, bp.id) acc ON acc.spent_amount <= acc.horizon_amount
+                CROSS JOIN LATERAL (
+                    SELECT COALESCE(SUM(ht.amount), 0)::bigint AS actual_value
+                    FROM transaction ht
+                    WHERE ht.user_id = $1
+                      AND ht.category_id = bc.category_id
+                      AND ht.occurred_at >= bp.start_date
+                      AND ht.occurred_at <= bp.end_date
+                ) hs
+                WHERE bc.user_id = $1
+            ) ha
             LEFT JOIN transaction t
                 ON t.user_id = $1
                 AND t.occurred_at >= bp.start_date
@@ -340,7 +373,7 @@ GROUP BY aib.balance_total
                 ON c.id = t.category_id
             WHERE bp.user_id = $1
                 AND bp.end_date < CURRENT_DATE
-            GROUP BY bp.id, bp.end_date, tb.value
+            GROUP BY bp.id, bp.end_date, tb.value, ha.value, s.total_budgeted, s.stability_spent
             ORDER BY bp.end_date DESC
             "#,
         )
@@ -354,11 +387,7 @@ GROUP BY aib.balance_total
             .filter(|row| !is_outside_tolerance(row.spent_budget, row.total_budget, tolerance_basis_points))
             .count() as u32;
 
-        let within_tolerance_percentage = if total_closed_periods == 0 {
-            0
-        } else {
-            (periods_within_tolerance * 100) / total_closed_periods
-        };
+        let within_tolerance_percentage = (periods_within_tolerance * 100).checked_div(total_closed_periods).unwrap_or(0);
 
         let mut recent_closed_periods: Vec<BudgetStabilityPeriodResponse> = closed_period_rows
             .iter()
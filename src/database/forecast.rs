@@ -0,0 +1,155 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::forecast::{
+    CategoryForecastResponse, CategorySpendSnapshot, FORECAST_HISTORY_PERIODS, HistoricalCategorySpend, RECURRING_ITEM_PERIODS, SpendForecastResponse,
+    elapsed_basis_points, project_spend,
+};
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+impl PostgresRepository {
+    /// Project end-of-period spending per Outgoing category from the burn rate so far, the spending curves of
+    /// recent closed periods and recurring items that are still to come.
+    pub async fn spend_forecast(&self, budget_period_id: &Uuid, today: NaiveDate, user_id: &Uuid) -> Result<SpendForecastResponse, AppError> {
+        let period = self.get_budget_period(budget_period_id, user_id).await?;
+        let elapsed = elapsed_basis_points(period.start_date, period.end_date, today);
+
+        let snapshots = sqlx::query_as::<_, CategorySpendSnapshot>(
+            r#"
+            WITH recent_closed_periods AS (
+                SELECT bp.id, bp.start_date, bp.end_date
+                FROM budget_period bp
+                WHERE bp.user_id = $1
+                  AND bp.end_date < $3
+                  AND bp.end_date < $5
+                ORDER BY bp.end_date DESC
+                LIMIT $6
+            ),
+            recurring_items AS (
+                -- Vendors paid from the category in every one of the recent closed periods
+                SELECT t.category_id, t.vendor_id, SUM(t.amount)::bigint / $6 AS expected_amount
+                FROM transaction t
+                JOIN recent_closed_periods rcp ON t.occurred_at >= rcp.start_date AND t.occurred_at <= rcp.end_date
+                WHERE t.user_id = $1
+                  AND t.vendor_id IS NOT NULL
+                GROUP BY t.category_id, t.vendor_id
+                HAVING COUNT(DISTINCT rcp.id) = $6
+            )
+            SELECT
+                c.id AS category_id,
+                c.name AS category_name,
+                budget_category_target_for_period(bc.id, $2, $3) AS target,
+                COALESCE((
+                    SELECT SUM(t.amount)
+                    FROM transaction t
+                    WHERE t.user_id = $1
+                      AND t.category_id = c.id
+                      AND t.occurred_at >= $3
+                      AND t.occurred_at <= LEAST($4, $5)
+                ), 0)::bigint AS spent_to_date,
+                COALESCE((
+                    SELECT SUM(ri.expected_amount)
+                    FROM recurring_items ri
+                    WHERE ri.category_id = c.id
+                      AND NOT EXISTS (
+                          SELECT 1
+                          FROM transaction t
+                          WHERE t.user_id = $1
+                            AND t.category_id = ri.category_id
+                            AND t.vendor_id = ri.vendor_id
+                            AND t.occurred_at >= $3
+                            AND t.occurred_at <= $4
+                      )
+                ), 0)::bigint AS recurring_pending
+            FROM category c
+            LEFT JOIN budget_category bc ON bc.category_id = c.id AND bc.user_id = $1 AND bc.is_excluded = FALSE
+            WHERE c.user_id = $1
+              AND c.category_type = 'Outgoing'
+              AND c.is_archived = FALSE
+            ORDER BY c.name ASC
+            "#,
+        )
+        .bind(user_id)
+        .bind(period.id)
+        .bind(period.start_date)
+        .bind(period.end_date)
+        .bind(today)
+        .bind(RECURRING_ITEM_PERIODS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        // Each closed period is split at the same share of its length as has elapsed in this one
+        let history = sqlx::query_as::<_, HistoricalCategorySpend>(
+            r#"
+            WITH history_periods AS (
+                SELECT
+                    bp.id,
+                    bp.start_date,
+                    bp.end_date,
+                    bp.start_date + ((bp.end_date - bp.start_date + 1) * $4 / 10000)::integer AS split_date
+                FROM budget_period bp
+                WHERE bp.user_id = $1
+                  AND bp.end_date < $2
+                  AND bp.end_date < $3
+                ORDER BY bp.end_date DESC
+                LIMIT $5
+            )
+            SELECT
+                t.category_id,
+                COALESCE(SUM(t.amount) FILTER (WHERE t.occurred_at < hp.split_date), 0)::bigint AS spent_by_point,
+                COALESCE(SUM(t.amount), 0)::bigint AS spent_total
+            FROM history_periods hp
+            JOIN transaction t
+              ON t.user_id = $1
+             AND t.occurred_at >= hp.start_date
+             AND t.occurred_at <= hp.end_date
+            JOIN category c ON c.id = t.category_id AND c.category_type = 'Outgoing'
+            GROUP BY hp.id, t.category_id
+            "#,
+        )
+        .bind(user_id)
+        .bind(period.start_date)
+        .bind(today)
+        .bind(elapsed)
+        .bind(FORECAST_HISTORY_PERIODS)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let history_periods: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*)
+            FROM (
+                SELECT 1 FROM budget_period
+                WHERE user_id = $1 AND end_date < $2 AND end_date < $3
+                LIMIT $4
+            ) hp
+            "#,
+        )
+        .bind(user_id)
+        .bind(period.start_date)
+        .bind(today)
+        .bind(FORECAST_HISTORY_PERIODS)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let mut remaining_by_category: HashMap<Uuid, Vec<i64>> = HashMap::new();
+        for row in &history {
+            remaining_by_category.entry(row.category_id).or_default().push(row.remaining());
+        }
+
+        let mut categories: Vec<CategoryForecastResponse> = snapshots
+            .iter()
+            .map(|snapshot| {
+                // Periods without any spending in the category still count: nothing was spent after the split
+                let mut remaining = remaining_by_category.remove(&snapshot.category_id).unwrap_or_default();
+                remaining.resize(history_periods.max(remaining.len() as i64) as usize, 0);
+                let projection = project_spend(snapshot.spent_to_date, elapsed, &remaining, snapshot.recurring_pending);
+                CategoryForecastResponse::new(snapshot, projection)
+            })
+            .collect();
+        categories.sort_by(|a, b| b.projected_spend.cmp(&a.projected_spend).then_with(|| a.category_name.cmp(&b.category_name)));
+
+        Ok(SpendForecastResponse::new(period.id, today, elapsed, history_periods, categories))
+    }
+}
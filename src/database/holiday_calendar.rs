@@ -0,0 +1,138 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::holiday_calendar::{HolidayCalendar, HolidayCalendarSelection, HolidayRule, UploadedHolidayCalendar, bundled_holiday_calendar};
+use uuid::Uuid;
+
+impl PostgresRepository {
+    pub async fn list_holiday_calendars(&self, user_id: &Uuid) -> Result<Vec<UploadedHolidayCalendar>, AppError> {
+        let calendars = sqlx::query_as::<_, UploadedHolidayCalendar>(
+            r#"
+            SELECT id, name, rules
+            FROM holiday_calendar
+            WHERE user_id = $1
+            ORDER BY name ASC, created_at ASC
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(calendars)
+    }
+
+    pub async fn create_holiday_calendar(&self, name: &str, rules: &[HolidayRule], user_id: &Uuid) -> Result<UploadedHolidayCalendar, AppError> {
+        let calendar = sqlx::query_as::<_, UploadedHolidayCalendar>(
+            r#"
+            INSERT INTO holiday_calendar (user_id, name, rules)
+            VALUES ($1, $2, $3)
+            RETURNING id, name, rules
+            "#,
+        )
+        .bind(user_id)
+        .bind(name)
+        .bind(sqlx::types::Json(rules))
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(calendar)
+    }
+
+    /// Deleting the selected calendar clears the selection.
+    pub async fn delete_holiday_calendar(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM holiday_calendar WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Holiday calendar not found".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn get_holiday_calendar_selection(&self, user_id: &Uuid) -> Result<HolidayCalendarSelection, AppError> {
+        let selection = sqlx::query_as::<_, HolidayCalendarSelection>("SELECT holiday_calendar_code, holiday_calendar_id FROM settings WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(selection.unwrap_or_default())
+    }
+
+    /// Select a bundled calendar by code or an uploaded one by id; `None` goes back to weekends only.
+    pub async fn select_holiday_calendar(&self, calendar: Option<&str>, user_id: &Uuid) -> Result<(), AppError> {
+        let selection = match calendar {
+            None => HolidayCalendarSelection::default(),
+            Some(reference) => {
+                if let Some(bundled) = bundled_holiday_calendar(reference) {
+                    HolidayCalendarSelection {
+                        code: Some(bundled.code.clone()),
+                        calendar_id: None,
+                    }
+                } else {
+                    let id = Uuid::parse_str(reference).map_err(|_| AppError::BadRequest(format!("Unknown holiday calendar {reference}")))?;
+                    self.get_uploaded_holiday_calendar(&id, user_id).await?;
+                    HolidayCalendarSelection {
+                        code: None,
+                        calendar_id: Some(id),
+                    }
+                }
+            }
+        };
+
+        let result = sqlx::query("UPDATE settings SET holiday_calendar_code = $1, holiday_calendar_id = $2, updated_at = now() WHERE user_id = $3")
+            .bind(&selection.code)
+            .bind(selection.calendar_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Settings not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Holidays of a bundled calendar by code or an uploaded one by id.
+    pub async fn resolve_holiday_calendar(&self, reference: &str, user_id: &Uuid) -> Result<HolidayCalendar, AppError> {
+        if let Some(bundled) = bundled_holiday_calendar(reference) {
+            return Ok(HolidayCalendar::new(bundled.holidays.clone()));
+        }
+
+        let id = Uuid::parse_str(reference).map_err(|_| AppError::NotFound("Holiday calendar not found".to_string()))?;
+        let calendar = self.get_uploaded_holiday_calendar(&id, user_id).await?;
+        Ok(HolidayCalendar::new(calendar.rules.0))
+    }
+
+    /// The holidays of the user's selected calendar; empty when none is selected.
+    pub async fn get_holiday_calendar(&self, user_id: &Uuid) -> Result<HolidayCalendar, AppError> {
+        let selection = self.get_holiday_calendar_selection(user_id).await?;
+
+        if let Some(code) = selection.code.as_deref() {
+            return Ok(bundled_holiday_calendar(code)
+                .map(|bundled| HolidayCalendar::new(bundled.holidays.clone()))
+                .unwrap_or_default());
+        }
+        if let Some(id) = selection.calendar_id {
+            let calendar = self.get_uploaded_holiday_calendar(&id, user_id).await?;
+            return Ok(HolidayCalendar::new(calendar.rules.0));
+        }
+        Ok(HolidayCalendar::default())
+    }
+
+    async fn get_uploaded_holiday_calendar(&self, id: &Uuid, user_id: &Uuid) -> Result<UploadedHolidayCalendar, AppError> {
+        sqlx::query_as::<_, UploadedHolidayCalendar>(
+            r#"
+            SELECT id, name, rules
+            FROM holiday_calendar
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id)
+        .bind(user_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Holiday calendar not found".to_string()))
+    }
+}
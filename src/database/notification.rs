@@ -0,0 +1,102 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::notification::{NewNotification, Notification};
+use crate::models::pagination::CursorParams;
+use uuid::Uuid;
+
+impl PostgresRepository {
+    /// Stores a notification unless one with the same dedupe key already exists.
+    /// Returns `true` when a new notification was created.
+    pub async fn create_notification(&self, notification: &NewNotification, user_id: &Uuid) -> Result<bool, AppError> {
+        let id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO notification (user_id, kind, dedupe_key, title, message, metadata)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id, dedupe_key) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(notification.kind)
+        .bind(&notification.dedupe_key)
+        .bind(&notification.title)
+        .bind(&notification.message)
+        .bind(&notification.metadata)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(id.is_some())
+    }
+
+    pub async fn list_notifications(&self, params: &CursorParams, unread_only: bool, user_id: &Uuid) -> Result<Vec<Notification>, AppError> {
+        let notifications = if let Some(cursor) = params.cursor {
+            sqlx::query_as::<_, Notification>(
+                r#"
+                SELECT id, kind, title, message, metadata, is_read, created_at
+                FROM notification
+                WHERE user_id = $1
+                  AND ($2 = FALSE OR is_read = FALSE)
+                  AND (created_at, id) < (SELECT created_at, id FROM notification WHERE id = $3)
+                ORDER BY created_at DESC, id DESC
+                LIMIT $4
+                "#,
+            )
+            .bind(user_id)
+            .bind(unread_only)
+            .bind(cursor)
+            .bind(params.fetch_limit())
+            .fetch_all(&self.pool)
+            .await?
+        } else {
+            sqlx::query_as::<_, Notification>(
+                r#"
+                SELECT id, kind, title, message, metadata, is_read, created_at
+                FROM notification
+                WHERE user_id = $1
+                  AND ($2 = FALSE OR is_read = FALSE)
+                ORDER BY created_at DESC, id DESC
+                LIMIT $3
+                "#,
+            )
+            .bind(user_id)
+            .bind(unread_only)
+            .bind(params.fetch_limit())
+            .fetch_all(&self.pool)
+            .await?
+        };
+
+        Ok(notifications)
+    }
+
+    pub async fn count_unread_notifications(&self, user_id: &Uuid) -> Result<i64, AppError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM notification WHERE user_id = $1 AND is_read = FALSE")
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    pub async fn mark_notification_read(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("UPDATE notification SET is_read = TRUE WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Notification not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn mark_all_notifications_read(&self, user_id: &Uuid) -> Result<(), AppError> {
+        sqlx::query("UPDATE notification SET is_read = TRUE WHERE user_id = $1 AND is_read = FALSE")
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,29 +1,103 @@
-use crate::database::postgres_repository::PostgresRepository;
+use crate::database::category::category_type_from_db;
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
 use crate::error::app_error::AppError;
-use crate::models::account::AccountResponse;
-use crate::models::category::CategoryResponse;
 use crate::models::overlay::{
-    InclusionMode, InclusionSource, Overlay, OverlayCategoryCap, OverlayRequest, OverlayRules, OverlayWithMetrics, TransactionMembership,
-    TransactionWithMembership,
+    InclusionMode, InclusionSource, Overlay, OverlayCapSpend, OverlayCategoryCap, OverlayReportResponse, OverlayRequest, OverlayRule, OverlayRuleSubject,
+    OverlayWithMetrics, TransactionMembership, TransactionWithMembership, overlay_report,
 };
+use crate::models::pagination::CursorParams;
 use crate::models::transaction::TransactionResponse;
-use crate::models::vendor::VendorResponse;
 use chrono::{DateTime, NaiveDate, Utc};
+use std::collections::HashMap;
 use uuid::Uuid;
 
-// Helper struct to group a few transaction fields so we don't exceed the function
-// parameter limit when checking overlay inclusion rules.
-struct SimpleTransactionRef<'a> {
-    id: &'a Uuid,
-    category_id: &'a Uuid,
-    from_account_id: &'a Uuid,
-    vendor_id: &'a Option<Uuid>,
+#[derive(sqlx::FromRow)]
+struct OverlayRow {
+    id: Uuid,
+    user_id: Uuid,
+    name: String,
+    icon: Option<String>,
+    start_date: NaiveDate,
+    end_date: NaiveDate,
+    inclusion_mode: InclusionMode,
+    total_cap_amount: Option<i64>,
+    alert_thresholds: Vec<i32>,
+    rules: Option<sqlx::types::Json<OverlayRule>>,
+    template_id: Option<Uuid>,
+    membership_refreshed_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl OverlayRow {
+    fn into_overlay(self) -> Overlay {
+        Overlay {
+            id: self.id,
+            user_id: self.user_id,
+            name: self.name,
+            icon: self.icon,
+            start_date: self.start_date,
+            end_date: self.end_date,
+            inclusion_mode: self.inclusion_mode,
+            total_cap_amount: self.total_cap_amount,
+            alert_thresholds: self.alert_thresholds,
+            template_id: self.template_id,
+            rules: self.rules.map(|rules| rules.0),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+/// The transaction fields overlay rules look at.
+#[derive(sqlx::FromRow)]
+struct MembershipSubjectRow {
+    id: Uuid,
+    amount: i64,
+    description: String,
+    occurred_at: NaiveDate,
+    category_id: Uuid,
+    category_type: String,
+    from_account_id: Uuid,
+    vendor_id: Option<Uuid>,
+}
+
+impl MembershipSubjectRow {
+    fn subject(&self) -> OverlayRuleSubject<'_> {
+        OverlayRuleSubject {
+            category_id: &self.category_id,
+            vendor_id: self.vendor_id.as_ref(),
+            from_account_id: &self.from_account_id,
+            amount: self.amount,
+            description: &self.description,
+            occurred_at: self.occurred_at,
+            direction: category_type_from_db(&self.category_type),
+        }
+    }
+}
+
+const MEMBERSHIP_SUBJECT_SELECT: &str = r#"
+    SELECT t.id, t.amount, t.description, t.occurred_at, t.category_id, c.category_type::text AS category_type, t.from_account_id, t.vendor_id
+    FROM transaction t
+    JOIN category c ON c.id = t.category_id
+"#;
+
+fn normalized_thresholds(thresholds: &[i32]) -> Vec<i32> {
+    let mut thresholds = thresholds.to_vec();
+    thresholds.sort_unstable();
+    thresholds.dedup();
+    thresholds
 }
 
 impl PostgresRepository {
     // ===== Create Overlay =====
 
     pub async fn create_overlay(&self, request: &OverlayRequest, user_id: &Uuid) -> Result<OverlayWithMetrics, AppError> {
+        self.create_overlay_instance(request, None, user_id).await
+    }
+
+    /// Create an overlay, optionally as an instance of a template. A template has at most one instance per start date.
+    pub async fn create_overlay_instance(&self, request: &OverlayRequest, template_id: Option<&Uuid>, user_id: &Uuid) -> Result<OverlayWithMetrics, AppError> {
         // Start a transaction
         let mut tx = self.pool.begin().await?;
 
@@ -35,8 +109,8 @@ impl PostgresRepository {
 
         let overlay_row = sqlx::query_as::<_, OverlayRow>(
             r#"
-            INSERT INTO overlays (user_id, name, icon, start_date, end_date, inclusion_mode, total_cap_amount, rules)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO overlays (user_id, name, icon, start_date, end_date, inclusion_mode, total_cap_amount, alert_thresholds, rules, template_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
             RETURNING id
             "#,
         )
@@ -47,9 +121,18 @@ impl PostgresRepository {
         .bind(request.end_date)
         .bind(request.inclusion_mode)
         .bind(request.total_cap_amount)
-        .bind(sqlx::types::Json(&request.rules))
+        .bind(normalized_thresholds(&request.alert_thresholds))
+        .bind(request.rules.as_ref().map(sqlx::types::Json))
+        .bind(template_id)
         .fetch_one(&mut *tx)
-        .await?;
+        .await
+        .map_err(|err| {
+            if is_unique_violation(&err) {
+                AppError::BadRequest(format!("The template already has an overlay starting on {}", request.start_date))
+            } else {
+                AppError::from(err)
+            }
+        })?;
 
         // Insert category caps
         for cap in &request.category_caps {
@@ -67,6 +150,7 @@ impl PostgresRepository {
         }
 
         tx.commit().await?;
+        self.refresh_overlay_membership_after_commit(&overlay_row.id, user_id).await;
 
         // Fetch the overlay with metrics
         self.get_overlay(&overlay_row.id, user_id).await
@@ -75,24 +159,10 @@ impl PostgresRepository {
     // ===== Get Overlay =====
 
     pub async fn get_overlay(&self, overlay_id: &Uuid, user_id: &Uuid) -> Result<OverlayWithMetrics, AppError> {
-        #[derive(sqlx::FromRow)]
-        struct OverlayRow {
-            id: Uuid,
-            user_id: Uuid,
-            name: String,
-            icon: Option<String>,
-            start_date: NaiveDate,
-            end_date: NaiveDate,
-            inclusion_mode: InclusionMode,
-            total_cap_amount: Option<i64>,
-            rules: sqlx::types::Json<OverlayRules>,
-            created_at: DateTime<Utc>,
-            updated_at: DateTime<Utc>,
-        }
-
         let overlay_row = sqlx::query_as::<_, OverlayRow>(
             r#"
-            SELECT id, user_id, name, icon, start_date, end_date, inclusion_mode, total_cap_amount, rules, created_at, updated_at
+            SELECT id, user_id, name, icon, start_date, end_date, inclusion_mode, total_cap_amount, alert_thresholds, rules, template_id,
+                   membership_refreshed_at, created_at, updated_at
             FROM overlays
             WHERE id = $1 AND user_id = $2
             "#,
@@ -102,85 +172,19 @@ impl PostgresRepository {
         .fetch_one(&self.pool)
         .await?;
 
-        // Fetch category caps
-        #[derive(sqlx::FromRow)]
-        struct CapRow {
-            category_id: Uuid,
-            cap_amount: i64,
-        }
-
-        let cap_rows = sqlx::query_as::<_, CapRow>(
-            r#"
-            SELECT category_id, cap_amount
-            FROM overlay_category_caps
-            WHERE overlay_id = $1
-            "#,
-        )
-        .bind(overlay_id)
-        .fetch_all(&self.pool)
-        .await?;
-
-        let category_caps: Vec<OverlayCategoryCap> = cap_rows
-            .iter()
-            .map(|row| OverlayCategoryCap {
-                category_id: row.category_id,
-                cap_amount: row.cap_amount,
-            })
-            .collect();
-
-        // Calculate spent amount and transaction count
-        let (spent_amount, transaction_count) = self
-            .calculate_overlay_metrics(
-                overlay_id,
-                &overlay_row.inclusion_mode,
-                &overlay_row.start_date,
-                &overlay_row.end_date,
-                &overlay_row.rules.0,
-                user_id,
-            )
-            .await?;
-
-        Ok(OverlayWithMetrics {
-            overlay: Overlay {
-                id: overlay_row.id,
-                user_id: overlay_row.user_id,
-                name: overlay_row.name,
-                icon: overlay_row.icon,
-                start_date: overlay_row.start_date,
-                end_date: overlay_row.end_date,
-                inclusion_mode: overlay_row.inclusion_mode,
-                total_cap_amount: overlay_row.total_cap_amount,
-                rules: overlay_row.rules.0,
-                created_at: overlay_row.created_at,
-                updated_at: overlay_row.updated_at,
-            },
-            spent_amount,
-            transaction_count,
-            category_caps,
-        })
+        self.with_metrics(vec![overlay_row], user_id)
+            .await?
+            .pop()
+            .ok_or_else(|| AppError::NotFound("Overlay not found".to_string()))
     }
 
     // ===== List Overlays =====
 
     pub async fn list_overlays(&self, user_id: &Uuid) -> Result<Vec<OverlayWithMetrics>, AppError> {
-        #[derive(sqlx::FromRow)]
-        struct OverlayRow {
-            id: Uuid,
-            user_id: Uuid,
-            name: String,
-            icon: Option<String>,
-            start_date: NaiveDate,
-            end_date: NaiveDate,
-            inclusion_mode: InclusionMode,
-            total_cap_amount: Option<i64>,
-            rules: sqlx::types::Json<OverlayRules>,
-            created_at: DateTime<Utc>,
-            updated_at: DateTime<Utc>,
-        }
-
         let overlay_rows = sqlx::query_as::<_, OverlayRow>(
             r#"
-            SELECT id, user_id, name, icon, start_date, end_date, inclusion_mode, total_cap_amount, rules, created_at, updated_at
+            SELECT id, user_id, name, icon, start_date, end_date, inclusion_mode, total_cap_amount, alert_thresholds, rules, template_id,
+                   membership_refreshed_at, created_at, updated_at
             FROM overlays
             WHERE user_id = $1
             ORDER BY created_at DESC
@@ -190,66 +194,7 @@ impl PostgresRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        let mut overlays_with_metrics = Vec::new();
-
-        for overlay_row in overlay_rows {
-            #[derive(sqlx::FromRow)]
-            struct CapRow {
-                category_id: Uuid,
-                cap_amount: i64,
-            }
-
-            let cap_rows = sqlx::query_as::<_, CapRow>(
-                r#"
-                SELECT category_id, cap_amount
-                FROM overlay_category_caps
-                WHERE overlay_id = $1
-                "#,
-            )
-            .bind(overlay_row.id)
-            .fetch_all(&self.pool)
-            .await?;
-
-            let category_caps: Vec<OverlayCategoryCap> = cap_rows
-                .iter()
-                .map(|row| OverlayCategoryCap {
-                    category_id: row.category_id,
-                    cap_amount: row.cap_amount,
-                })
-                .collect();
-
-            let (spent_amount, transaction_count) = self
-                .calculate_overlay_metrics(
-                    &overlay_row.id,
-                    &overlay_row.inclusion_mode,
-                    &overlay_row.start_date,
-                    &overlay_row.end_date,
-                    &overlay_row.rules.0,
-                    user_id,
-                )
-                .await?;
-
-            overlays_with_metrics.push(OverlayWithMetrics {
-                overlay: Overlay {
-                    id: overlay_row.id,
-                    user_id: overlay_row.user_id,
-                    name: overlay_row.name.clone(),
-                    icon: overlay_row.icon.clone(),
-                    start_date: overlay_row.start_date,
-                    end_date: overlay_row.end_date,
-                    inclusion_mode: overlay_row.inclusion_mode,
-                    total_cap_amount: overlay_row.total_cap_amount,
-                    rules: overlay_row.rules.0.clone(),
-                    created_at: overlay_row.created_at,
-                    updated_at: overlay_row.updated_at,
-                },
-                spent_amount,
-                transaction_count,
-                category_caps,
-            });
-        }
-
-        Ok(overlays_with_metrics)
+        self.with_metrics(overlay_rows, user_id).await
     }
 
     // ===== Update Overlay =====
@@ -261,8 +206,8 @@ impl PostgresRepository {
             r#"
             UPDATE overlays
             SET name = $1, icon = $2, start_date = $3, end_date = $4,
-                inclusion_mode = $5, total_cap_amount = $6, rules = $7, updated_at = now()
-            WHERE id = $8 AND user_id = $9
+                inclusion_mode = $5, total_cap_amount = $6, alert_thresholds = $7, rules = $8, updated_at = now()
+            WHERE id = $9 AND user_id = $10
             RETURNING id
             "#,
         )
@@ -272,7 +217,8 @@ impl PostgresRepository {
         .bind(request.end_date)
         .bind(request.inclusion_mode)
         .bind(request.total_cap_amount)
-        .bind(sqlx::types::Json(&request.rules))
+        .bind(normalized_thresholds(&request.alert_thresholds))
+        .bind(request.rules.as_ref().map(sqlx::types::Json))
         .bind(overlay_id)
         .bind(user_id)
         .fetch_one(&mut *tx)
@@ -300,6 +246,7 @@ impl PostgresRepository {
         }
 
         tx.commit().await?;
+        self.refresh_overlay_membership_after_commit(overlay_id, user_id).await;
 
         // Fetch the overlay with metrics
         self.get_overlay(overlay_id, user_id).await
@@ -319,67 +266,80 @@ impl PostgresRepository {
 
     // ===== Get Overlay Transactions with Membership =====
 
-    pub async fn get_overlay_transactions(&self, overlay_id: &Uuid, user_id: &Uuid) -> Result<Vec<TransactionWithMembership>, AppError> {
-        // First, get the overlay to determine inclusion mode and rules
-        #[derive(sqlx::FromRow)]
-        struct OverlayInfo {
-            inclusion_mode: InclusionMode,
-            start_date: NaiveDate,
-            end_date: NaiveDate,
-            rules: sqlx::types::Json<OverlayRules>,
-        }
+    /// A page of the transactions in the overlay's date range, newest first, with their membership.
+    pub async fn get_overlay_transactions(&self, overlay_id: &Uuid, params: &CursorParams, user_id: &Uuid) -> Result<Vec<TransactionWithMembership>, AppError> {
+        let overlay = self.get_overlay(overlay_id, user_id).await?;
 
-        let overlay_info = sqlx::query_as::<_, OverlayInfo>(
-            r#"
-            SELECT inclusion_mode, start_date, end_date, rules
-            FROM overlays
-            WHERE id = $1 AND user_id = $2
-            "#,
-        )
-        .bind(overlay_id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
-        .await?;
-
-        // Get all transactions in the date range with full details
         let transactions = self
-            .list_transactions_in_date_range(user_id, &overlay_info.start_date, &overlay_info.end_date)
+            .list_transactions_in_date_range(user_id, overlay.overlay.start_date, overlay.overlay.end_date, params)
             .await?;
+        let transaction_ids: Vec<Uuid> = transactions.iter().map(|transaction| transaction.id).collect();
 
-        // Get manual inclusions/exclusions
-        #[derive(sqlx::FromRow)]
-        struct InclusionRow {
-            transaction_id: Uuid,
-            is_included: bool,
-        }
-
-        let inclusion_rows = sqlx::query_as::<_, InclusionRow>(
+        let sources: HashMap<Uuid, InclusionSource> = sqlx::query_as::<_, (Uuid, InclusionSource)>(
             r#"
-            SELECT transaction_id, is_included
-            FROM overlay_transaction_inclusions
-            WHERE overlay_id = $1
+            SELECT transaction_id, inclusion_source
+            FROM overlay_transaction_membership
+            WHERE overlay_id = $1 AND transaction_id = ANY($2)
             "#,
         )
         .bind(overlay_id)
+        .bind(&transaction_ids)
         .fetch_all(&self.pool)
-        .await?;
+        .await?
+        .into_iter()
+        .collect();
 
-        let manual_map: std::collections::HashMap<Uuid, bool> = inclusion_rows.iter().map(|row| (row.transaction_id, row.is_included)).collect();
+        Ok(transactions
+            .iter()
+            .map(|transaction| {
+                let inclusion_source = sources.get(&transaction.id).copied();
+                TransactionWithMembership {
+                    transaction: TransactionResponse::from(transaction),
+                    membership: TransactionMembership {
+                        is_included: inclusion_source.is_some(),
+                        inclusion_source,
+                    },
+                }
+            })
+            .collect())
+    }
 
-        // Build transactions with membership
-        let mut result = Vec::new();
+    // ===== Overlay Report =====
 
-        for tx in transactions {
-            let (is_included, inclusion_source) =
-                self.determine_transaction_membership(&tx.id, &overlay_info.inclusion_mode, &overlay_info.rules.0, &tx, &manual_map);
+    pub async fn get_overlay_report(&self, overlay_id: &Uuid, user_id: &Uuid) -> Result<OverlayReportResponse, AppError> {
+        let overlay = self.get_overlay(overlay_id, user_id).await?;
 
-            result.push(TransactionWithMembership {
-                transaction: tx,
-                membership: TransactionMembership { is_included, inclusion_source },
-            });
-        }
+        let sources: HashMap<Uuid, InclusionSource> =
+            sqlx::query_as::<_, (Uuid, InclusionSource)>("SELECT transaction_id, inclusion_source FROM overlay_transaction_membership WHERE overlay_id = $1")
+                .bind(overlay_id)
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .collect();
+        let transaction_ids: Vec<Uuid> = sources.keys().copied().collect();
+        let transactions: Vec<TransactionWithMembership> = self
+            .list_transactions_by_ids(&transaction_ids, user_id)
+            .await?
+            .iter()
+            .map(|transaction| TransactionWithMembership {
+                transaction: TransactionResponse::from(transaction),
+                membership: TransactionMembership {
+                    is_included: true,
+                    inclusion_source: sources.get(&transaction.id).copied(),
+                },
+            })
+            .collect();
+
+        let capped_ids: Vec<Uuid> = overlay.category_caps.iter().map(|cap| cap.category_id).collect();
+        let category_names: HashMap<Uuid, String> = sqlx::query_as::<_, (Uuid, String)>("SELECT id, name FROM category WHERE id = ANY($1) AND user_id = $2")
+            .bind(&capped_ids)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?
+            .into_iter()
+            .collect();
 
-        Ok(result)
+        Ok(overlay_report(&overlay, &transactions, &category_names))
     }
 
     // ===== Manual Include Transaction =====
@@ -400,6 +360,7 @@ impl PostgresRepository {
         .bind(transaction_id)
         .execute(&self.pool)
         .await?;
+        self.refresh_transaction_membership_after_commit(transaction_id, user_id).await;
 
         Ok(())
     }
@@ -415,326 +376,421 @@ impl PostgresRepository {
             .bind(transaction_id)
             .execute(&self.pool)
             .await?;
+        self.refresh_transaction_membership_after_commit(transaction_id, user_id).await;
 
         Ok(())
     }
 
-    // ===== Helper Methods =====
-
-    async fn calculate_overlay_metrics(
-        &self,
-        overlay_id: &Uuid,
-        inclusion_mode: &InclusionMode,
-        start_date: &NaiveDate,
-        end_date: &NaiveDate,
-        rules: &OverlayRules,
-        user_id: &Uuid,
-    ) -> Result<(i64, i64), AppError> {
-        // Get manual inclusions/exclusions
-        #[derive(sqlx::FromRow)]
-        struct InclusionRow {
-            transaction_id: Uuid,
-            is_included: bool,
-        }
+    // ===== Cap Alerts =====
 
-        let inclusion_rows = sqlx::query_as::<_, InclusionRow>(
+    /// Every cap of the overlays covering `date`, with its spend so far, for alerting.
+    pub async fn list_overlay_cap_spend_for_date(&self, user_id: &Uuid, date: NaiveDate) -> Result<Vec<OverlayCapSpend>, AppError> {
+        let overlay_ids: Vec<Uuid> = sqlx::query_scalar(
             r#"
-            SELECT transaction_id, is_included
-            FROM overlay_transaction_inclusions
-            WHERE overlay_id = $1
+            SELECT o.id
+            FROM overlays o
+            WHERE o.user_id = $1
+              AND o.start_date <= $2
+              AND o.end_date >= $2
+              AND (o.total_cap_amount IS NOT NULL OR EXISTS (SELECT 1 FROM overlay_category_caps occ WHERE occ.overlay_id = o.id))
             "#,
         )
-        .bind(overlay_id)
+        .bind(user_id)
+        .bind(date)
         .fetch_all(&self.pool)
         .await?;
 
-        let manual_map: std::collections::HashMap<Uuid, bool> = inclusion_rows.iter().map(|row| (row.transaction_id, row.is_included)).collect();
+        if overlay_ids.is_empty() {
+            return Ok(Vec::new());
+        }
 
-        // Get transactions in date range
         #[derive(sqlx::FromRow)]
-        struct TransactionRow {
-            id: Uuid,
-            amount: i64,
-            category_id: Uuid,
-            from_account_id: Uuid,
-            vendor_id: Option<Uuid>,
+        struct CurrencyRow {
+            symbol: String,
+            decimal_places: i32,
         }
 
-        let transactions = sqlx::query_as::<_, TransactionRow>(
+        let currency = sqlx::query_as::<_, CurrencyRow>(
             r#"
-            SELECT id, amount, category_id, from_account_id, vendor_id
-            FROM transaction
-            WHERE user_id = $1
-                AND occurred_at >= $2
-                AND occurred_at <= $3
+            SELECT cur.symbol, cur.decimal_places
+            FROM settings s
+            JOIN currency cur ON cur.id = s.default_currency_id
+            WHERE s.user_id = $1
             "#,
         )
         .bind(user_id)
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_all(&self.pool)
+        .fetch_optional(&self.pool)
         .await?;
-
-        let mut spent_amount = 0i64;
-        let mut transaction_count = 0i64;
-
-        for tx in transactions {
-            // Build a small reference struct to avoid passing many parameters
-            let simple_tx = SimpleTransactionRef {
-                id: &tx.id,
-                category_id: &tx.category_id,
-                from_account_id: &tx.from_account_id,
-                vendor_id: &tx.vendor_id,
+        let (currency_symbol, currency_decimal_places) = currency.map_or((String::new(), 2), |currency| (currency.symbol, currency.decimal_places));
+
+        let mut caps = Vec::new();
+        for overlay_id in &overlay_ids {
+            let overlay = self.get_overlay(overlay_id, user_id).await?;
+            let cap_spend = |category_id: Option<Uuid>, category_name: Option<String>, cap_amount: i64, spent: i64| OverlayCapSpend {
+                overlay_id: overlay.overlay.id,
+                overlay_name: overlay.overlay.name.clone(),
+                category_id,
+                category_name,
+                cap_amount,
+                spent,
+                thresholds: overlay.overlay.alert_thresholds.clone(),
+                currency_symbol: currency_symbol.clone(),
+                currency_decimal_places,
             };
 
-            let (is_included, _) = self.determine_transaction_membership_simple(&simple_tx, inclusion_mode, rules, &manual_map);
-
-            if is_included {
-                spent_amount += tx.amount;
-                transaction_count += 1;
+            if let Some(cap_amount) = overlay.overlay.total_cap_amount {
+                caps.push(cap_spend(None, None, cap_amount, overlay.spent_amount));
+            }
+            for cap in &overlay.category_caps {
+                let category_name = self.get_category_by_id(&cap.category_id, user_id).await?.map(|category| category.name);
+                let spent = overlay.spent_per_category.get(&cap.category_id).copied().unwrap_or(0);
+                caps.push(cap_spend(Some(cap.category_id), category_name, cap.cap_amount, spent));
             }
         }
 
-        Ok((spent_amount, transaction_count))
+        Ok(caps)
     }
 
-    fn determine_transaction_membership(
-        &self,
-        transaction_id: &Uuid,
-        inclusion_mode: &InclusionMode,
-        rules: &OverlayRules,
-        tx: &TransactionResponse,
-        manual_map: &std::collections::HashMap<Uuid, bool>,
-    ) -> (bool, Option<InclusionSource>) {
-        // Check manual override first
-        if let Some(&is_manually_included) = manual_map.get(transaction_id) {
-            if is_manually_included {
-                return (true, Some(InclusionSource::Manual));
-            } else {
-                return (false, None);
-            }
-        }
+    // ===== Persisted Membership =====
 
-        // Apply inclusion mode logic
-        match inclusion_mode {
-            InclusionMode::Manual => (false, None),
-            InclusionMode::All => (true, Some(InclusionSource::All)),
-            InclusionMode::Rules => {
-                let matches_rules = self.transaction_matches_rules_response(tx, rules);
-                if matches_rules { (true, Some(InclusionSource::Rules)) } else { (false, None) }
-            }
+    /// Recompute which transactions belong to an overlay, after the overlay itself changed. The overlay stays
+    /// locked until the new membership is stored, so a concurrent transaction refresh cannot interleave with it.
+    pub async fn refresh_overlay_membership(&self, overlay_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        #[derive(sqlx::FromRow)]
+        struct OverlayInfo {
+            inclusion_mode: InclusionMode,
+            start_date: NaiveDate,
+            end_date: NaiveDate,
+            rules: Option<sqlx::types::Json<OverlayRule>>,
         }
-    }
 
-    fn determine_transaction_membership_simple(
-        &self,
-        tx: &SimpleTransactionRef,
-        inclusion_mode: &InclusionMode,
-        rules: &OverlayRules,
-        manual_map: &std::collections::HashMap<Uuid, bool>,
-    ) -> (bool, Option<InclusionSource>) {
-        // Check manual override first
-        if let Some(&is_manually_included) = manual_map.get(tx.id) {
-            if is_manually_included {
-                return (true, Some(InclusionSource::Manual));
-            } else {
-                return (false, None);
-            }
-        }
+        let mut tx = self.pool.begin().await?;
 
-        // Apply inclusion mode logic
-        match inclusion_mode {
-            InclusionMode::Manual => (false, None),
-            InclusionMode::All => (true, Some(InclusionSource::All)),
-            InclusionMode::Rules => {
-                let matches_rules = self.transaction_matches_rules_simple(tx.category_id, tx.from_account_id, tx.vendor_id, rules);
-                if matches_rules { (true, Some(InclusionSource::Rules)) } else { (false, None) }
+        let overlay_info = sqlx::query_as::<_, OverlayInfo>(
+            r#"
+            SELECT inclusion_mode, start_date, end_date, rules
+            FROM overlays
+            WHERE id = $1 AND user_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(overlay_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let manual_map: HashMap<Uuid, bool> =
+            sqlx::query_as::<_, (Uuid, bool)>("SELECT transaction_id, is_included FROM overlay_transaction_inclusions WHERE overlay_id = $1")
+                .bind(overlay_id)
+                .fetch_all(&mut *tx)
+                .await?
+                .into_iter()
+                .collect();
+
+        let transactions = sqlx::query_as::<_, MembershipSubjectRow>(&format!(
+            "{} WHERE t.user_id = $1 AND t.occurred_at >= $2 AND t.occurred_at <= $3",
+            MEMBERSHIP_SUBJECT_SELECT
+        ))
+        .bind(user_id)
+        .bind(overlay_info.start_date)
+        .bind(overlay_info.end_date)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let mut transaction_ids = Vec::new();
+        let mut sources = Vec::new();
+        for transaction in &transactions {
+            let (_, inclusion_source) = self.determine_transaction_membership(
+                &transaction.id,
+                &overlay_info.inclusion_mode,
+                overlay_info.rules.as_deref(),
+                &transaction.subject(),
+                &manual_map,
+            );
+            if let Some(inclusion_source) = inclusion_source {
+                transaction_ids.push(transaction.id);
+                sources.push(inclusion_source.to_string());
             }
         }
+
+        sqlx::query("DELETE FROM overlay_transaction_membership WHERE overlay_id = $1")
+            .bind(overlay_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO overlay_transaction_membership (overlay_id, transaction_id, inclusion_source)
+            SELECT $1, m.transaction_id, m.inclusion_source
+            FROM UNNEST($2::uuid[], $3::text[]) AS m(transaction_id, inclusion_source)
+            "#,
+        )
+        .bind(overlay_id)
+        .bind(&transaction_ids)
+        .bind(&sources)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE overlays SET membership_refreshed_at = now() WHERE id = $1")
+            .bind(overlay_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(())
     }
 
-    fn transaction_matches_rules_response(&self, tx: &TransactionResponse, rules: &OverlayRules) -> bool {
-        let mut matches = false;
+    /// Recompute which overlays a transaction belongs to, after the transaction or its manual inclusions changed.
+    /// The transaction and the user's overlays stay locked until the new membership is stored, so concurrent
+    /// refreshes cannot overwrite it with membership computed from older reads.
+    pub async fn refresh_transaction_membership(&self, transaction_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
 
-        // Check category
-        if !rules.category_ids.is_empty() && rules.category_ids.contains(&tx.category.id) {
-            matches = true;
-        }
+        sqlx::query("SELECT 1 FROM transaction WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(transaction_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("SELECT 1 FROM overlays WHERE user_id = $1 FOR SHARE")
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?;
 
-        // Check vendor
-        if !rules.vendor_ids.is_empty()
-            && let Some(ref vendor) = tx.vendor
-            && rules.vendor_ids.contains(&vendor.id)
-        {
-            matches = true;
+        let transaction = sqlx::query_as::<_, MembershipSubjectRow>(&format!("{} WHERE t.id = $1 AND t.user_id = $2", MEMBERSHIP_SUBJECT_SELECT))
+            .bind(transaction_id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        // Deleted transactions lose their membership through the foreign key
+        let Some(transaction) = transaction else {
+            return Ok(());
+        };
+
+        #[derive(sqlx::FromRow)]
+        struct OverlayInfo {
+            id: Uuid,
+            inclusion_mode: InclusionMode,
+            rules: Option<sqlx::types::Json<OverlayRule>>,
+            is_included: Option<bool>,
         }
 
-        // Check account
-        if !rules.account_ids.is_empty() && rules.account_ids.contains(&tx.from_account.id) {
-            matches = true;
+        let overlays = sqlx::query_as::<_, OverlayInfo>(
+            r#"
+            SELECT o.id, o.inclusion_mode, o.rules, oti.is_included
+            FROM overlays o
+            LEFT JOIN overlay_transaction_inclusions oti ON oti.overlay_id = o.id AND oti.transaction_id = $3
+            WHERE o.user_id = $1 AND o.start_date <= $2 AND o.end_date >= $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(transaction.occurred_at)
+        .bind(transaction_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let subject = transaction.subject();
+        let mut overlay_ids = Vec::new();
+        let mut sources = Vec::new();
+        for overlay in &overlays {
+            let manual_map: HashMap<Uuid, bool> = overlay.is_included.map(|is_included| (transaction.id, is_included)).into_iter().collect();
+            let (_, inclusion_source) =
+                self.determine_transaction_membership(&transaction.id, &overlay.inclusion_mode, overlay.rules.as_deref(), &subject, &manual_map);
+            if let Some(inclusion_source) = inclusion_source {
+                overlay_ids.push(overlay.id);
+                sources.push(inclusion_source.to_string());
+            }
         }
 
-        matches
+        sqlx::query("DELETE FROM overlay_transaction_membership WHERE transaction_id = $1")
+            .bind(transaction_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO overlay_transaction_membership (overlay_id, transaction_id, inclusion_source)
+            SELECT m.overlay_id, $1, m.inclusion_source
+            FROM UNNEST($2::uuid[], $3::text[]) AS m(overlay_id, inclusion_source)
+            "#,
+        )
+        .bind(transaction_id)
+        .bind(&overlay_ids)
+        .bind(&sources)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(())
     }
 
-    fn transaction_matches_rules_simple(&self, category_id: &Uuid, from_account_id: &Uuid, vendor_id: &Option<Uuid>, rules: &OverlayRules) -> bool {
-        let mut matches = false;
+    /// Recompute the membership of every rule-based overlay of a user, after something their rules look at changed.
+    pub async fn refresh_rule_overlay_membership(&self, user_id: &Uuid) -> Result<(), AppError> {
+        let overlay_ids: Vec<Uuid> = sqlx::query_scalar("SELECT id FROM overlays WHERE user_id = $1 AND inclusion_mode = 'rules'")
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
 
-        // Check category
-        if !rules.category_ids.is_empty() && rules.category_ids.contains(category_id) {
-            matches = true;
+        for overlay_id in &overlay_ids {
+            self.refresh_overlay_membership(overlay_id, user_id).await?;
         }
 
-        // Check vendor
-        if !rules.vendor_ids.is_empty()
-            && let Some(v_id) = vendor_id
-            && rules.vendor_ids.contains(v_id)
-        {
-            matches = true;
-        }
+        Ok(())
+    }
 
-        // Check account
-        if !rules.account_ids.is_empty() && rules.account_ids.contains(from_account_id) {
-            matches = true;
+    /// Refresh an overlay after a write that has already committed. A failure must not fail that write, so the
+    /// overlay is left for `with_metrics` to recompute on its next read instead.
+    pub(crate) async fn refresh_overlay_membership_after_commit(&self, overlay_id: &Uuid, user_id: &Uuid) {
+        if let Err(err) = self.refresh_overlay_membership(overlay_id, user_id).await {
+            tracing::warn!("Failed to refresh membership of overlay {}: {:?}", overlay_id, err);
+            self.mark_membership_stale(user_id, Some(overlay_id), false).await;
         }
+    }
 
-        matches
+    /// Like `refresh_overlay_membership_after_commit`, for a transaction; any overlay may be affected by it.
+    pub(crate) async fn refresh_transaction_membership_after_commit(&self, transaction_id: &Uuid, user_id: &Uuid) {
+        if let Err(err) = self.refresh_transaction_membership(transaction_id, user_id).await {
+            tracing::warn!("Failed to refresh overlay membership of transaction {}: {:?}", transaction_id, err);
+            self.mark_membership_stale(user_id, None, false).await;
+        }
     }
 
-    // Helper to get transactions in date range with full details
-    async fn list_transactions_in_date_range(
-        &self,
-        user_id: &Uuid,
-        start_date: &NaiveDate,
-        end_date: &NaiveDate,
-    ) -> Result<Vec<TransactionResponse>, AppError> {
-        #[derive(sqlx::FromRow)]
-        struct TransactionRow {
-            id: Uuid,
-            amount: i64,
-            description: String,
-            occurred_at: NaiveDate,
-            category_id: Uuid,
-            from_account_id: Uuid,
-            to_account_id: Option<Uuid>,
-            vendor_id: Option<Uuid>,
+    /// Like `refresh_overlay_membership_after_commit`, for every rule-based overlay of a user.
+    pub(crate) async fn refresh_rule_overlay_membership_after_commit(&self, user_id: &Uuid) {
+        if let Err(err) = self.refresh_rule_overlay_membership(user_id).await {
+            tracing::warn!("Failed to refresh membership of rule-based overlays of user {}: {:?}", user_id, err);
+            self.mark_membership_stale(user_id, None, true).await;
         }
+    }
 
-        let transaction_rows = sqlx::query_as::<_, TransactionRow>(
+    async fn mark_membership_stale(&self, user_id: &Uuid, overlay_id: Option<&Uuid>, rules_only: bool) {
+        let result = sqlx::query(
             r#"
-            SELECT id, amount, description, occurred_at, category_id, from_account_id, to_account_id, vendor_id
-            FROM transaction
+            UPDATE overlays
+            SET membership_refreshed_at = NULL
             WHERE user_id = $1
-                AND occurred_at >= $2
-                AND occurred_at <= $3
-            ORDER BY occurred_at DESC, id DESC
+              AND ($2::uuid IS NULL OR id = $2)
+              AND (NOT $3 OR inclusion_mode = 'rules')
             "#,
         )
         .bind(user_id)
-        .bind(start_date)
-        .bind(end_date)
-        .fetch_all(&self.pool)
-        .await?;
+        .bind(overlay_id)
+        .bind(rules_only)
+        .execute(&self.pool)
+        .await;
 
-        let mut result = Vec::new();
+        if let Err(err) = result {
+            tracing::warn!("Failed to mark overlay membership of user {} for refresh: {:?}", user_id, err);
+        }
+    }
 
-        for tx_row in transaction_rows {
-            let category_option = self.get_category_by_id(&tx_row.category_id, user_id).await?;
-            let category = category_option.ok_or_else(|| AppError::NotFound(format!("Category {} not found", tx_row.category_id)))?;
-            let from_account = self.get_account_by_id_simple(&tx_row.from_account_id, user_id).await?;
-            let to_account = if let Some(to_id) = tx_row.to_account_id {
-                Some(self.get_account_by_id_simple(&to_id, user_id).await?)
-            } else {
-                None
-            };
-            let vendor = if let Some(vendor_id) = tx_row.vendor_id {
-                Some(self.get_vendor_by_id_simple(&vendor_id, user_id).await?)
-            } else {
-                None
-            };
+    // ===== Helper Methods =====
 
-            result.push(TransactionResponse {
-                id: tx_row.id,
-                amount: tx_row.amount,
-                description: tx_row.description,
-                occurred_at: tx_row.occurred_at,
-                category: CategoryResponse::from(&category),
-                from_account: AccountResponse::from(&from_account),
-                to_account: to_account.as_ref().map(AccountResponse::from),
-                vendor: vendor.as_ref().map(VendorResponse::from),
-            });
+    /// Attach caps and spend to overlays, computing membership first for overlays that have never had it stored.
+    async fn with_metrics(&self, overlay_rows: Vec<OverlayRow>, user_id: &Uuid) -> Result<Vec<OverlayWithMetrics>, AppError> {
+        for overlay_row in &overlay_rows {
+            if overlay_row.membership_refreshed_at.is_none() {
+                self.refresh_overlay_membership(&overlay_row.id, user_id).await?;
+            }
         }
 
-        Ok(result)
-    }
-
-    // Simple account fetcher (without full metrics)
-    async fn get_account_by_id_simple(&self, account_id: &Uuid, user_id: &Uuid) -> Result<crate::models::account::Account, AppError> {
-        use crate::database::account::account_type_from_db;
+        let overlay_ids: Vec<Uuid> = overlay_rows.iter().map(|row| row.id).collect();
 
         #[derive(sqlx::FromRow)]
-        struct AccountRow {
-            id: Uuid,
-            name: String,
-            color: String,
-            icon: String,
-            account_type: String,
-            currency_id: Uuid,
-            spend_limit: Option<i32>,
+        struct CapRow {
+            overlay_id: Uuid,
+            category_id: Uuid,
+            cap_amount: i64,
         }
 
-        let account_row = sqlx::query_as::<_, AccountRow>(
+        let cap_rows = sqlx::query_as::<_, CapRow>(
             r#"
-            SELECT id, name, color, icon, account_type, currency_id, spend_limit
-            FROM account
-            WHERE id = $1 AND user_id = $2
+            SELECT overlay_id, category_id, cap_amount
+            FROM overlay_category_caps
+            WHERE overlay_id = ANY($1)
             "#,
         )
-        .bind(account_id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
+        .bind(&overlay_ids)
+        .fetch_all(&self.pool)
         .await?;
 
-        // Fetch currency
-        let currency = sqlx::query_as::<_, crate::models::currency::Currency>(
+        let mut caps_per_overlay: HashMap<Uuid, Vec<OverlayCategoryCap>> = HashMap::new();
+        for row in cap_rows {
+            caps_per_overlay.entry(row.overlay_id).or_default().push(OverlayCategoryCap {
+                category_id: row.category_id,
+                cap_amount: row.cap_amount,
+            });
+        }
+
+        #[derive(sqlx::FromRow)]
+        struct SpendRow {
+            overlay_id: Uuid,
+            category_id: Uuid,
+            spent_amount: i64,
+            transaction_count: i64,
+        }
+
+        let spend_rows = sqlx::query_as::<_, SpendRow>(
             r#"
-            SELECT id, name, symbol, currency, decimal_places
-            FROM currency
-            WHERE id = $1
+            SELECT m.overlay_id, t.category_id, SUM(t.amount)::BIGINT AS spent_amount, COUNT(*) AS transaction_count
+            FROM overlay_transaction_membership m
+            JOIN transaction t ON t.id = m.transaction_id
+            WHERE m.overlay_id = ANY($1)
+            GROUP BY m.overlay_id, t.category_id
             "#,
         )
-        .bind(account_row.currency_id)
-        .fetch_one(&self.pool)
+        .bind(&overlay_ids)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(crate::models::account::Account {
-            id: account_row.id,
-            name: account_row.name,
-            color: account_row.color,
-            icon: account_row.icon,
-            account_type: account_type_from_db(&account_row.account_type),
-            currency,
-            balance: 0, // Not needed for overlay context
-            spend_limit: account_row.spend_limit,
-            is_archived: false,
-            next_transfer_amount: None,
-        })
+        let mut spend_per_overlay: HashMap<Uuid, Vec<SpendRow>> = HashMap::new();
+        for row in spend_rows {
+            spend_per_overlay.entry(row.overlay_id).or_default().push(row);
+        }
+
+        Ok(overlay_rows
+            .into_iter()
+            .map(|overlay_row| {
+                let spend = spend_per_overlay.remove(&overlay_row.id).unwrap_or_default();
+                let category_caps = caps_per_overlay.remove(&overlay_row.id).unwrap_or_default();
+                OverlayWithMetrics {
+                    overlay: overlay_row.into_overlay(),
+                    spent_amount: spend.iter().map(|row| row.spent_amount).sum(),
+                    transaction_count: spend.iter().map(|row| row.transaction_count).sum(),
+                    category_caps,
+                    spent_per_category: spend.iter().map(|row| (row.category_id, row.spent_amount)).collect(),
+                }
+            })
+            .collect())
     }
 
-    // Simple vendor fetcher
-    async fn get_vendor_by_id_simple(&self, vendor_id: &Uuid, user_id: &Uuid) -> Result<crate::models::vendor::Vendor, AppError> {
-        let vendor = sqlx::query_as::<_, crate::models::vendor::Vendor>(
-            r#"
-            SELECT id, user_id, name, created_at
-            FROM vendor
-            WHERE id = $1 AND user_id = $2
-            "#,
-        )
-        .bind(vendor_id)
-        .bind(user_id)
-        .fetch_one(&self.pool)
-        .await?;
+    fn determine_transaction_membership(
+        &self,
+        transaction_id: &Uuid,
+        inclusion_mode: &InclusionMode,
+        rules: Option<&OverlayRule>,
+        subject: &OverlayRuleSubject,
+        manual_map: &std::collections::HashMap<Uuid, bool>,
+    ) -> (bool, Option<InclusionSource>) {
+        // Check manual override first
+        if let Some(&is_manually_included) = manual_map.get(transaction_id) {
+            if is_manually_included {
+                return (true, Some(InclusionSource::Manual));
+            } else {
+                return (false, None);
+            }
+        }
 
-        Ok(vendor)
+        // Apply inclusion mode logic
+        match inclusion_mode {
+            InclusionMode::Manual => (false, None),
+            InclusionMode::All => (true, Some(InclusionSource::All)),
+            InclusionMode::Rules => {
+                let matches_rules = rules.is_some_and(|rules| rules.matches(subject));
+                if matches_rules { (true, Some(InclusionSource::Rules)) } else { (false, None) }
+            }
+        }
     }
 }
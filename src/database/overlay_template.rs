@@ -0,0 +1,322 @@
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
+use crate::error::app_error::AppError;
+use crate::models::overlay::{InclusionMode, OverlayCategoryCap, OverlayRule, OverlayWithMetrics};
+use crate::models::overlay_template::{
+    OVERLAY_TEMPLATE_REPORT_INSTANCES, OverlayDateRule, OverlayInstanceCategoryDelta, OverlayInstanceSummary, OverlayTemplate,
+    OverlayTemplateFromOverlayRequest, OverlayTemplateReportResponse, OverlayTemplateRequest, OverlayTemplateRunResult, due_occurrence,
+};
+use crate::models::period_comparison::AmountDeltaResponse;
+use chrono::NaiveDate;
+use std::collections::BTreeSet;
+use uuid::Uuid;
+
+const OVERLAY_TEMPLATE_SELECT: &str = r#"
+    SELECT id, user_id, name, icon, inclusion_mode, total_cap_amount, category_caps, alert_thresholds, rules, date_rule,
+           auto_create, lead_days, created_at
+    FROM overlay_template
+"#;
+
+/// The fields of a template, whether given directly or taken from an overlay.
+struct OverlayTemplateFields<'a> {
+    name: &'a str,
+    icon: Option<&'a str>,
+    inclusion_mode: &'a InclusionMode,
+    total_cap_amount: Option<i64>,
+    category_caps: &'a [OverlayCategoryCap],
+    alert_thresholds: &'a [i32],
+    rules: Option<&'a OverlayRule>,
+    date_rule: &'a OverlayDateRule,
+    auto_create: bool,
+    lead_days: i32,
+}
+
+impl<'a> From<&'a OverlayTemplateRequest> for OverlayTemplateFields<'a> {
+    fn from(request: &'a OverlayTemplateRequest) -> Self {
+        Self {
+            name: &request.name,
+            icon: request.icon.as_deref(),
+            inclusion_mode: &request.inclusion_mode,
+            total_cap_amount: request.total_cap_amount,
+            category_caps: &request.category_caps,
+            alert_thresholds: &request.alert_thresholds,
+            rules: request.rules.as_ref(),
+            date_rule: &request.date_rule,
+            auto_create: request.auto_create,
+            lead_days: request.lead_days,
+        }
+    }
+}
+
+impl PostgresRepository {
+    pub async fn list_overlay_templates(&self, user_id: &Uuid) -> Result<Vec<OverlayTemplate>, AppError> {
+        let query = format!("{} WHERE user_id = $1 ORDER BY name ASC", OVERLAY_TEMPLATE_SELECT);
+        let templates = sqlx::query_as::<_, OverlayTemplate>(&query).bind(user_id).fetch_all(&self.pool).await?;
+
+        Ok(templates)
+    }
+
+    pub async fn get_overlay_template(&self, id: &Uuid, user_id: &Uuid) -> Result<OverlayTemplate, AppError> {
+        let query = format!("{} WHERE id = $1 AND user_id = $2", OVERLAY_TEMPLATE_SELECT);
+        sqlx::query_as::<_, OverlayTemplate>(&query)
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Overlay template not found".to_string()))
+    }
+
+    pub async fn create_overlay_template(&self, request: &OverlayTemplateRequest, user_id: &Uuid) -> Result<OverlayTemplate, AppError> {
+        self.insert_overlay_template(&OverlayTemplateFields::from(request), user_id).await
+    }
+
+    /// Save the rules, caps and alert thresholds of an existing overlay as a template.
+    pub async fn create_overlay_template_from_overlay(&self, request: &OverlayTemplateFromOverlayRequest, user_id: &Uuid) -> Result<OverlayTemplate, AppError> {
+        let overlay = self.get_overlay(&request.overlay_id, user_id).await?;
+        let fields = OverlayTemplateFields {
+            name: &request.name,
+            icon: overlay.overlay.icon.as_deref(),
+            inclusion_mode: &overlay.overlay.inclusion_mode,
+            total_cap_amount: overlay.overlay.total_cap_amount,
+            category_caps: &overlay.category_caps,
+            alert_thresholds: &overlay.overlay.alert_thresholds,
+            rules: overlay.overlay.rules.as_ref(),
+            date_rule: &request.date_rule,
+            auto_create: request.auto_create,
+            lead_days: request.lead_days,
+        };
+        let template = self.insert_overlay_template(&fields, user_id).await?;
+
+        // The overlay becomes the template's first instance unless it is already an instance of another one
+        sqlx::query("UPDATE overlays SET template_id = $1, updated_at = now() WHERE id = $2 AND user_id = $3 AND template_id IS NULL")
+            .bind(template.id)
+            .bind(overlay.overlay.id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(template)
+    }
+
+    async fn insert_overlay_template(&self, fields: &OverlayTemplateFields<'_>, user_id: &Uuid) -> Result<OverlayTemplate, AppError> {
+        let query = r#"
+            INSERT INTO overlay_template (user_id, name, icon, inclusion_mode, total_cap_amount, category_caps, alert_thresholds, rules,
+                                          date_rule, auto_create, lead_days)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id
+        "#;
+        let result = sqlx::query_scalar::<_, Uuid>(query)
+            .bind(user_id)
+            .bind(fields.name)
+            .bind(fields.icon)
+            .bind(fields.inclusion_mode)
+            .bind(fields.total_cap_amount)
+            .bind(sqlx::types::Json(fields.category_caps))
+            .bind(fields.alert_thresholds)
+            .bind(fields.rules.map(sqlx::types::Json))
+            .bind(sqlx::types::Json(fields.date_rule))
+            .bind(fields.auto_create)
+            .bind(fields.lead_days)
+            .fetch_one(&self.pool)
+            .await;
+
+        match result {
+            Ok(id) => self.get_overlay_template(&id, user_id).await,
+            Err(err) if is_unique_violation(&err) => Err(AppError::BadRequest("Overlay template name already exists".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn update_overlay_template(&self, id: &Uuid, request: &OverlayTemplateRequest, user_id: &Uuid) -> Result<OverlayTemplate, AppError> {
+        let result = sqlx::query(
+            r#"
+            UPDATE overlay_template
+            SET name = $1, icon = $2, inclusion_mode = $3, total_cap_amount = $4, category_caps = $5, alert_thresholds = $6,
+                rules = $7, date_rule = $8, auto_create = $9, lead_days = $10, updated_at = now()
+            WHERE id = $11 AND user_id = $12
+            "#,
+        )
+        .bind(&request.name)
+        .bind(&request.icon)
+        .bind(request.inclusion_mode)
+        .bind(request.total_cap_amount)
+        .bind(sqlx::types::Json(&request.category_caps))
+        .bind(&request.alert_thresholds)
+        .bind(request.rules.as_ref().map(sqlx::types::Json))
+        .bind(sqlx::types::Json(&request.date_rule))
+        .bind(request.auto_create)
+        .bind(request.lead_days)
+        .bind(id)
+        .bind(user_id)
+        .execute(&self.pool)
+        .await;
+
+        match result {
+            Ok(done) if done.rows_affected() == 0 => Err(AppError::NotFound("Overlay template not found".to_string())),
+            Ok(_) => self.get_overlay_template(id, user_id).await,
+            Err(err) if is_unique_violation(&err) => Err(AppError::BadRequest("Overlay template name already exists".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Deleting a template keeps its instances as plain overlays.
+    pub async fn delete_overlay_template(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM overlay_template WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Overlay template not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Create the template's overlay for the occurrence that has not ended by `date`.
+    pub async fn instantiate_overlay_template(&self, id: &Uuid, date: NaiveDate, user_id: &Uuid) -> Result<OverlayWithMetrics, AppError> {
+        let template = self.get_overlay_template(id, user_id).await?;
+        let (start_date, end_date) = template.date_rule.0.occurrence_for(date);
+        self.create_overlay_instance(&template.overlay_request(start_date, end_date), Some(&template.id), user_id)
+            .await
+    }
+
+    /// Create the due instance of every template with `auto_create`, for all users.
+    /// A template that fails is logged and skipped so the others still run.
+    pub async fn run_overlay_template_instantiation(&self, today: NaiveDate) -> Result<OverlayTemplateRunResult, AppError> {
+        let query = format!("{} WHERE auto_create = TRUE ORDER BY user_id, name", OVERLAY_TEMPLATE_SELECT);
+        let templates = sqlx::query_as::<_, OverlayTemplate>(&query).fetch_all(&self.pool).await?;
+        let mut result = OverlayTemplateRunResult::default();
+
+        for template in &templates {
+            result.templates_processed += 1;
+
+            let latest_start: Option<NaiveDate> = sqlx::query_scalar("SELECT MAX(start_date) FROM overlays WHERE template_id = $1")
+                .bind(template.id)
+                .fetch_one(&self.pool)
+                .await?;
+            let Some((start_date, end_date)) = due_occurrence(&template.date_rule.0, latest_start, template.lead_days, today) else {
+                continue;
+            };
+
+            match self
+                .create_overlay_instance(&template.overlay_request(start_date, end_date), Some(&template.id), &template.user_id)
+                .await
+            {
+                Ok(_) => result.overlays_created += 1,
+                Err(err) => tracing::warn!("Failed to instantiate overlay template {}: {:?}", template.id, err),
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Compare an instance of a template (the latest one started by `today` unless given) with the instances before it.
+    pub async fn overlay_template_report(
+        &self,
+        id: &Uuid,
+        overlay_id: Option<&Uuid>,
+        today: NaiveDate,
+        user_id: &Uuid,
+    ) -> Result<OverlayTemplateReportResponse, AppError> {
+        let template = self.get_overlay_template(id, user_id).await?;
+
+        let instance_id: Option<Uuid> = match overlay_id {
+            Some(overlay_id) => {
+                sqlx::query_scalar("SELECT id FROM overlays WHERE id = $1 AND template_id = $2 AND user_id = $3")
+                    .bind(overlay_id)
+                    .bind(template.id)
+                    .bind(user_id)
+                    .fetch_optional(&self.pool)
+                    .await?
+            }
+            None => {
+                sqlx::query_scalar(
+                    r#"
+                SELECT id
+                FROM overlays
+                WHERE template_id = $1 AND user_id = $2
+                ORDER BY (start_date <= $3) DESC, start_date DESC
+                LIMIT 1
+                "#,
+                )
+                .bind(template.id)
+                .bind(user_id)
+                .bind(today)
+                .fetch_optional(&self.pool)
+                .await?
+            }
+        };
+        let instance_id = instance_id.ok_or_else(|| AppError::NotFound("Overlay template instance not found".to_string()))?;
+        let instance = self.get_overlay(&instance_id, user_id).await?;
+
+        let previous_ids: Vec<Uuid> = sqlx::query_scalar(
+            r#"
+            SELECT id
+            FROM overlays
+            WHERE template_id = $1 AND user_id = $2 AND start_date < $3
+            ORDER BY start_date DESC
+            LIMIT $4
+            "#,
+        )
+        .bind(template.id)
+        .bind(user_id)
+        .bind(instance.overlay.start_date)
+        .bind(OVERLAY_TEMPLATE_REPORT_INSTANCES)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut previous = Vec::with_capacity(previous_ids.len());
+        for previous_id in &previous_ids {
+            previous.push(self.get_overlay(previous_id, user_id).await?);
+        }
+
+        let category_ids: BTreeSet<Uuid> = std::iter::once(&instance)
+            .chain(previous.iter())
+            .flat_map(|overlay| overlay.spent_per_category.keys().copied())
+            .collect();
+        let mut categories = Vec::with_capacity(category_ids.len());
+        for category_id in category_ids {
+            let category_name = self
+                .get_category_by_id(&category_id, user_id)
+                .await?
+                .map(|category| category.name)
+                .unwrap_or_default();
+            let current = instance.spent_per_category.get(&category_id).copied().unwrap_or(0);
+            let previous_spent: Vec<i64> = previous
+                .iter()
+                .map(|overlay| overlay.spent_per_category.get(&category_id).copied().unwrap_or(0))
+                .collect();
+            categories.push(OverlayInstanceCategoryDelta {
+                category_id,
+                category_name,
+                amounts: AmountDeltaResponse::new(current, mean(&previous_spent).unwrap_or(0)),
+            });
+        }
+        categories.sort_by_key(|category| std::cmp::Reverse(category.amounts.delta.abs()));
+
+        let previous_totals: Vec<i64> = previous.iter().map(|overlay| overlay.spent_amount).collect();
+        Ok(OverlayTemplateReportResponse {
+            template_id: template.id,
+            instance: instance_summary(&instance),
+            spent_vs_previous: previous_totals.first().map(|spent| AmountDeltaResponse::new(instance.spent_amount, *spent)),
+            spent_vs_average: mean(&previous_totals).map(|average| AmountDeltaResponse::new(instance.spent_amount, average)),
+            previous_instances: previous.iter().map(instance_summary).collect(),
+            categories,
+        })
+    }
+}
+
+fn instance_summary(overlay: &OverlayWithMetrics) -> OverlayInstanceSummary {
+    OverlayInstanceSummary {
+        overlay_id: overlay.overlay.id,
+        name: overlay.overlay.name.clone(),
+        start_date: overlay.overlay.start_date,
+        end_date: overlay.overlay.end_date,
+        spent_amount: overlay.spent_amount,
+        transaction_count: overlay.transaction_count,
+    }
+}
+
+fn mean(values: &[i64]) -> Option<i64> {
+    (!values.is_empty()).then(|| (values.iter().sum::<i64>() as f64 / values.len() as f64).round() as i64)
+}
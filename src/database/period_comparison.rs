@@ -0,0 +1,96 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::period_comparison::{ComparedPeriod, ComparisonTransaction, PeriodComparisonResponse, compare_periods};
+use uuid::Uuid;
+
+impl PostgresRepository {
+    /// Compare a budget period with another one.
+    pub async fn compare_budget_periods(&self, period_id: &Uuid, other_period_id: &Uuid, user_id: &Uuid) -> Result<PeriodComparisonResponse, AppError> {
+        if period_id == other_period_id {
+            return Err(AppError::BadRequest("Cannot compare a budget period with itself".to_string()));
+        }
+
+        let period = self.get_compared_period(period_id, user_id).await?;
+        let other = self.get_compared_period(other_period_id, user_id).await?;
+        let current = self.list_comparison_transactions(&[period.id], user_id).await?;
+        let baseline = self.list_comparison_transactions(&[other.id], user_id).await?;
+
+        Ok(compare_periods(&period, &[other], &current, &baseline))
+    }
+
+    /// Compare a budget period with the average of the last `count` closed periods that ended before it.
+    pub async fn compare_budget_period_with_average(&self, period_id: &Uuid, count: i64, user_id: &Uuid) -> Result<PeriodComparisonResponse, AppError> {
+        let period = self.get_compared_period(period_id, user_id).await?;
+
+        let mut baseline_periods = sqlx::query_as::<_, ComparedPeriod>(
+            r#"
+            SELECT id, name, start_date, end_date
+            FROM budget_period
+            WHERE user_id = $1
+              AND closed_at IS NOT NULL
+              AND end_date < $2
+            ORDER BY start_date DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(user_id)
+        .bind(period.start_date)
+        .bind(count)
+        .fetch_all(&self.pool)
+        .await?;
+
+        if baseline_periods.is_empty() {
+            return Err(AppError::BadRequest("No closed budget periods before this one to compare with".to_string()));
+        }
+        baseline_periods.reverse();
+
+        let baseline_ids: Vec<Uuid> = baseline_periods.iter().map(|baseline_period| baseline_period.id).collect();
+        let current = self.list_comparison_transactions(&[period.id], user_id).await?;
+        let baseline = self.list_comparison_transactions(&baseline_ids, user_id).await?;
+
+        Ok(compare_periods(&period, &baseline_periods, &current, &baseline))
+    }
+
+    async fn get_compared_period(&self, id: &Uuid, user_id: &Uuid) -> Result<ComparedPeriod, AppError> {
+        sqlx::query_as::<_, ComparedPeriod>("SELECT id, name, start_date, end_date FROM budget_period WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?
+            .ok_or_else(|| AppError::NotFound("Budget period not found".to_string()))
+    }
+
+    /// Incoming and Outgoing transactions dated inside any of the given periods.
+    async fn list_comparison_transactions(&self, period_ids: &[Uuid], user_id: &Uuid) -> Result<Vec<ComparisonTransaction>, AppError> {
+        let transactions = sqlx::query_as::<_, ComparisonTransaction>(
+            r#"
+            SELECT DISTINCT ON (t.id)
+                   t.id,
+                   t.occurred_at,
+                   t.description,
+                   t.amount::bigint          AS amount,
+                   c.id                      AS category_id,
+                   c.name                    AS category_name,
+                   c.category_type::text     AS category_type,
+                   v.id                      AS vendor_id,
+                   v.name                    AS vendor_name
+            FROM transaction t
+            JOIN budget_period bp ON bp.user_id = t.user_id
+                                 AND t.occurred_at >= bp.start_date
+                                 AND t.occurred_at <= bp.end_date
+            JOIN category c       ON c.id = t.category_id
+            LEFT JOIN vendor v    ON v.id = t.vendor_id
+            WHERE t.user_id = $1
+              AND bp.id = ANY($2)
+              AND c.category_type::text IN ('Incoming', 'Outgoing')
+            ORDER BY t.id
+            "#,
+        )
+        .bind(user_id)
+        .bind(period_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(transactions)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::savings_goal::{SavingsGoal, SavingsGoalRequest, contribution_window_start};
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+// $1 is "today" and $2 the first day of the recent contribution window.
+// Contributions are transfers that cross the boundary of the goal's accounts; moving money
+// between two linked accounts does not count.
+const SAVINGS_GOAL_SELECT: &str = r#"
+    SELECT
+        g.id,
+        g.name,
+        g.description,
+        g.target_amount,
+        g.target_date,
+        g.tracking_mode,
+        g.starts_on,
+        ga.account_ids,
+        CASE
+            WHEN g.tracking_mode = 'balance' THEN (
+                SELECT COALESCE(SUM(a.balance + COALESCE((
+                    SELECT SUM(
+                        CASE
+                            WHEN cat.category_type = 'Incoming'                              THEN  t.amount::bigint
+                            WHEN cat.category_type = 'Outgoing'                              THEN -t.amount::bigint
+                            WHEN cat.category_type = 'Transfer' AND t.from_account_id = a.id THEN -t.amount::bigint
+                            WHEN cat.category_type = 'Transfer' AND t.to_account_id   = a.id THEN  t.amount::bigint
+                            ELSE 0
+                        END
+                    )
+                    FROM transaction t
+                    JOIN category cat ON cat.id = t.category_id
+                    WHERE t.from_account_id = a.id OR t.to_account_id = a.id
+                ), 0)), 0)::bigint
+                FROM account a
+                WHERE a.id = ANY(ga.account_ids)
+            )
+            ELSE (
+                SELECT COALESCE(SUM(CASE WHEN t.to_account_id = ANY(ga.account_ids) THEN t.amount::bigint ELSE -t.amount::bigint END), 0)::bigint
+                FROM transaction t
+                JOIN category cat ON cat.id = t.category_id
+                WHERE t.user_id = g.user_id
+                  AND cat.category_type = 'Transfer'
+                  AND t.occurred_at >= g.starts_on
+                  AND t.occurred_at <= $1
+                  AND (t.to_account_id = ANY(ga.account_ids)) <> (t.from_account_id = ANY(ga.account_ids))
+            )
+        END AS current_amount,
+        (
+            SELECT COALESCE(SUM(CASE WHEN t.to_account_id = ANY(ga.account_ids) THEN t.amount::bigint ELSE -t.amount::bigint END), 0)::bigint
+            FROM transaction t
+            JOIN category cat ON cat.id = t.category_id
+            WHERE t.user_id = g.user_id
+              AND cat.category_type = 'Transfer'
+              AND t.occurred_at >= GREATEST(g.starts_on, $2)
+              AND t.occurred_at <= $1
+              AND (t.to_account_id = ANY(ga.account_ids)) <> (t.from_account_id = ANY(ga.account_ids))
+        ) AS recent_contributions,
+        g.created_at
+    FROM savings_goal g
+    CROSS JOIN LATERAL (
+        SELECT COALESCE(array_agg(sga.account_id ORDER BY sga.account_id), '{}'::uuid[]) AS account_ids
+        FROM savings_goal_account sga
+        WHERE sga.goal_id = g.id
+    ) ga
+"#;
+
+impl PostgresRepository {
+    async fn validate_savings_goal_accounts(&self, account_ids: &[Uuid], user_id: &Uuid) -> Result<(), AppError> {
+        let owned: i64 = sqlx::query_scalar("SELECT COUNT(DISTINCT id) FROM account WHERE id = ANY($1) AND user_id = $2")
+            .bind(account_ids)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let mut requested = account_ids.to_vec();
+        requested.sort_unstable();
+        requested.dedup();
+        if owned != requested.len() as i64 {
+            return Err(AppError::BadRequest("Invalid account_ids for current user".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn create_savings_goal(&self, request: &SavingsGoalRequest, today: NaiveDate, user_id: &Uuid) -> Result<SavingsGoal, AppError> {
+        self.validate_savings_goal_accounts(&request.account_ids, user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO savings_goal (user_id, name, description, target_amount, target_date, tracking_mode, starts_on)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id)
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(request.target_amount)
+        .bind(request.target_date)
+        .bind(request.tracking_mode)
+        .bind(request.starts_on.unwrap_or(today))
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO savings_goal_account (goal_id, account_id) SELECT $1, UNNEST($2::uuid[]) ON CONFLICT DO NOTHING")
+            .bind(id)
+            .bind(&request.account_ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.get_savings_goal(&id, today, user_id).await
+    }
+
+    pub async fn get_savings_goal(&self, id: &Uuid, today: NaiveDate, user_id: &Uuid) -> Result<SavingsGoal, AppError> {
+        let query = format!("{} WHERE g.id = $3 AND g.user_id = $4", SAVINGS_GOAL_SELECT);
+        let goal = sqlx::query_as::<_, SavingsGoal>(&query)
+            .bind(today)
+            .bind(contribution_window_start(today))
+            .bind(id)
+            .bind(user_id)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(goal)
+    }
+
+    pub async fn list_savings_goals(&self, today: NaiveDate, user_id: &Uuid) -> Result<Vec<SavingsGoal>, AppError> {
+        let query = format!("{} WHERE g.user_id = $3 ORDER BY g.created_at DESC, g.id DESC", SAVINGS_GOAL_SELECT);
+        let goals = sqlx::query_as::<_, SavingsGoal>(&query)
+            .bind(today)
+            .bind(contribution_window_start(today))
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(goals)
+    }
+
+    pub async fn update_savings_goal(&self, id: &Uuid, request: &SavingsGoalRequest, today: NaiveDate, user_id: &Uuid) -> Result<SavingsGoal, AppError> {
+        self.validate_savings_goal_accounts(&request.account_ids, user_id).await?;
+
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE savings_goal
+            SET name = $1,
+                description = $2,
+                target_amount = $3,
+                target_date = $4,
+                tracking_mode = $5,
+                starts_on = COALESCE($6, starts_on),
+                updated_at = now()
+            WHERE id = $7 AND user_id = $8
+            "#,
+        )
+        .bind(&request.name)
+        .bind(&request.description)
+        .bind(request.target_amount)
+        .bind(request.target_date)
+        .bind(request.tracking_mode)
+        .bind(request.starts_on)
+        .bind(id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Savings goal not found".to_string()));
+        }
+
+        sqlx::query("DELETE FROM savings_goal_account WHERE goal_id = $1 AND account_id <> ALL($2)")
+            .bind(id)
+            .bind(&request.account_ids)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("INSERT INTO savings_goal_account (goal_id, account_id) SELECT $1, UNNEST($2::uuid[]) ON CONFLICT DO NOTHING")
+            .bind(id)
+            .bind(&request.account_ids)
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        self.get_savings_goal(id, today, user_id).await
+    }
+
+    pub async fn delete_savings_goal(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        sqlx::query("DELETE FROM savings_goal WHERE id = $1 AND user_id = $2")
+            .bind(id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
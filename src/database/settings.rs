@@ -11,6 +11,8 @@ impl PostgresRepository {
             r#"
             SELECT id, user_id, theme, language, default_currency_id,
                    budget_stability_tolerance_basis_points,
+                   spend_limit_alert_thresholds, spend_limit_email_alerts,
+                   category_alert_thresholds, category_email_alerts, budgeting_mode,
                    created_at, updated_at
             FROM settings
             WHERE user_id = $1
@@ -33,9 +35,17 @@ impl PostgresRepository {
                 theme,
                 language,
                 default_currency_id,
-                budget_stability_tolerance_basis_points
+                budget_stability_tolerance_basis_points,
+                spend_limit_alert_thresholds,
+                spend_limit_email_alerts,
+                category_alert_thresholds,
+                category_email_alerts,
+                budgeting_mode
+            )
+            VALUES (
+                $1, $2, $3, $4, COALESCE($5, 1000), COALESCE($6, '{8000,10000}'), COALESCE($7, TRUE),
+                COALESCE($9, '{8000,10000}'), COALESCE($10, FALSE), COALESCE($8, 'standard')
             )
-            VALUES ($1, $2, $3, $4, COALESCE($5, 1000))
             ON CONFLICT (user_id)
             DO UPDATE SET
                 theme = EXCLUDED.theme,
@@ -45,9 +55,16 @@ impl PostgresRepository {
                     $5,
                     settings.budget_stability_tolerance_basis_points
                 ),
+                spend_limit_alert_thresholds = COALESCE($6, settings.spend_limit_alert_thresholds),
+                spend_limit_email_alerts = COALESCE($7, settings.spend_limit_email_alerts),
+                category_alert_thresholds = COALESCE($9, settings.category_alert_thresholds),
+                category_email_alerts = COALESCE($10, settings.category_email_alerts),
+                budgeting_mode = COALESCE($8, settings.budgeting_mode),
                 updated_at = now()
             RETURNING id, user_id, theme, language, default_currency_id,
                       budget_stability_tolerance_basis_points,
+                      spend_limit_alert_thresholds, spend_limit_email_alerts,
+                      category_alert_thresholds, category_email_alerts, budgeting_mode,
                       created_at, updated_at
             "#,
         )
@@ -56,6 +73,11 @@ impl PostgresRepository {
         .bind(&request.language)
         .bind(request.default_currency_id)
         .bind(request.budget_stability_tolerance_basis_points)
+        .bind(request.spend_limit_alert_thresholds.as_deref())
+        .bind(request.spend_limit_email_alerts)
+        .bind(request.budgeting_mode)
+        .bind(request.category_alert_thresholds.as_deref())
+        .bind(request.category_email_alerts)
         .fetch_one(&mut *transaction)
         .await?;
 
@@ -91,6 +113,8 @@ impl PostgresRepository {
             VALUES ($1, 'light', 'en', NULL, 1000)
             RETURNING id, user_id, theme, language, default_currency_id,
                       budget_stability_tolerance_basis_points,
+                      spend_limit_alert_thresholds, spend_limit_email_alerts,
+                      category_alert_thresholds, category_email_alerts, budgeting_mode,
                       created_at, updated_at
             "#,
         )
@@ -244,7 +268,10 @@ impl PostgresRepository {
                         )
                         VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
                         ON CONFLICT (user_id) DO UPDATE SET
+                            schedule_kind = 'interval',
                             start_day = EXCLUDED.start_day,
+                            second_day = NULL,
+                            anchor_date = NULL,
                             duration_value = EXCLUDED.duration_value,
                             duration_unit = EXCLUDED.duration_unit,
                             saturday_adjustment = EXCLUDED.saturday_adjustment,
@@ -1,3 +1,4 @@
+use crate::database::budget_period_snapshot::ensure_dates_in_open_periods;
 use crate::database::category::category_type_from_db;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
@@ -328,7 +329,11 @@ impl PostgresRepository {
         self.validate_transaction_ownership(transaction, user_id).await?;
 
         let to_account_id = if let Some(acc_id) = &transaction.to_account_id { Some(acc_id) } else { None };
-        let vendor_id = if let Some(v_id) = &transaction.vendor_id { Some(v_id) } else { None };
+        // Without an explicit vendor, the description may name one through an alias
+        let vendor_id = match transaction.vendor_id {
+            Some(vendor_id) => Some(vendor_id),
+            None => self.resolve_vendor_id(&transaction.description, user_id).await?,
+        };
 
         let select_query = build_transaction_query("inserted t", &[], "");
         let query = format!(
@@ -352,6 +357,9 @@ impl PostgresRepository {
             select_query
         );
 
+        let mut tx = self.pool.begin().await?;
+        ensure_dates_in_open_periods(&mut tx, &[transaction.occurred_at], user_id).await?;
+
         let row = sqlx::query_as::<_, TransactionRow>(&query)
             .bind(user_id)
             .bind(transaction.amount)
@@ -361,8 +369,10 @@ impl PostgresRepository {
             .bind(transaction.from_account_id)
             .bind(to_account_id)
             .bind(vendor_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *tx)
             .await?;
+        tx.commit().await?;
+        self.refresh_transaction_membership_after_commit(&row.id, user_id).await;
 
         Ok(Transaction::from(row))
     }
@@ -482,12 +492,81 @@ impl PostgresRepository {
         Ok(rows.into_iter().map(Transaction::from).collect())
     }
 
+    /// Transactions between two dates (inclusive), newest first, one page at a time.
+    pub async fn list_transactions_in_date_range(
+        &self,
+        user_id: &Uuid,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+        params: &CursorParams,
+    ) -> Result<Vec<Transaction>, AppError> {
+        let rows = if let Some(cursor) = params.cursor {
+            let base = build_transaction_query(
+                "transaction t",
+                &[
+                    "t.user_id = $1 AND t.occurred_at >= $2 AND t.occurred_at <= $3",
+                    "(t.occurred_at, t.created_at, t.id) < (SELECT occurred_at, created_at, id FROM transaction WHERE id = $4)",
+                ],
+                "t.occurred_at DESC, t.created_at DESC, t.id DESC",
+            );
+            sqlx::query_as::<_, TransactionRow>(&format!("{} LIMIT $5", base))
+                .bind(user_id)
+                .bind(start_date)
+                .bind(end_date)
+                .bind(cursor)
+                .bind(params.fetch_limit())
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            let base = build_transaction_query(
+                "transaction t",
+                &["t.user_id = $1 AND t.occurred_at >= $2 AND t.occurred_at <= $3"],
+                "t.occurred_at DESC, t.created_at DESC, t.id DESC",
+            );
+            sqlx::query_as::<_, TransactionRow>(&format!("{} LIMIT $4", base))
+                .bind(user_id)
+                .bind(start_date)
+                .bind(end_date)
+                .bind(params.fetch_limit())
+                .fetch_all(&self.pool)
+                .await?
+        };
+
+        Ok(rows.into_iter().map(Transaction::from).collect())
+    }
+
+    pub async fn list_transactions_by_ids(&self, ids: &[Uuid], user_id: &Uuid) -> Result<Vec<Transaction>, AppError> {
+        let query = build_transaction_query(
+            "transaction t",
+            &["t.id = ANY($1) AND t.user_id = $2"],
+            "t.occurred_at DESC, t.created_at DESC, t.id DESC",
+        );
+        let rows = sqlx::query_as::<_, TransactionRow>(&query)
+            .bind(ids)
+            .bind(user_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(Transaction::from).collect())
+    }
+
     pub async fn delete_transaction(&self, id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+        let existing_date: Option<NaiveDate> = sqlx::query_scalar("SELECT occurred_at FROM transaction WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        if let Some(existing_date) = existing_date {
+            ensure_dates_in_open_periods(&mut tx, &[existing_date], user_id).await?;
+        }
+
         sqlx::query("DELETE FROM transaction WHERE id = $1 AND user_id = $2")
             .bind(id)
             .bind(user_id)
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+        tx.commit().await?;
 
         Ok(())
     }
@@ -495,6 +574,18 @@ impl PostgresRepository {
     pub async fn update_transaction(&self, id: &Uuid, transaction: &TransactionRequest, user_id: &Uuid) -> Result<Transaction, AppError> {
         self.validate_transaction_ownership(transaction, user_id).await?;
 
+        let mut tx = self.pool.begin().await?;
+
+        // Moving a transaction out of a closed period changes it as much as moving one in
+        let mut dates = vec![transaction.occurred_at];
+        let existing_date: Option<NaiveDate> = sqlx::query_scalar("SELECT occurred_at FROM transaction WHERE id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(id)
+            .bind(user_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        dates.extend(existing_date);
+        ensure_dates_in_open_periods(&mut tx, &dates, user_id).await?;
+
         let select_query = build_transaction_query("updated t", &[], "");
         let query = format!(
             r#"
@@ -526,8 +617,10 @@ impl PostgresRepository {
             .bind(transaction.vendor_id)
             .bind(id)
             .bind(user_id)
-            .fetch_one(&self.pool)
+            .fetch_one(&mut *tx)
             .await?;
+        tx.commit().await?;
+        self.refresh_transaction_membership_after_commit(&row.id, user_id).await;
 
         Ok(Transaction::from(row))
     }
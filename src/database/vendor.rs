@@ -1,8 +1,12 @@
+use crate::database::budget_period_snapshot::ensure_merge_leaves_closed_periods;
 use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
 use crate::error::app_error::AppError;
 use crate::models::budget_period::BudgetPeriod;
+use crate::models::overlay::OverlayRule;
 use crate::models::pagination::CursorParams;
-use crate::models::vendor::{Vendor, VendorPeriodStats, VendorRequest, VendorStats, VendorWithPeriodStats, VendorWithStats};
+use crate::models::vendor::{
+    Vendor, VendorMergeResponse, VendorPeriodStats, VendorRequest, VendorResponse, VendorStats, VendorWithPeriodStats, VendorWithStats,
+};
 use chrono::NaiveDate;
 use rocket::FromFormField;
 use schemars::JsonSchema;
@@ -349,4 +353,110 @@ LIMIT $5
 
         vendor.ok_or_else(|| AppError::NotFound("Vendor not found".to_string()))
     }
+
+    /// Move the transactions, overlay rules and aliases of `source_id` to `destination_id`, then delete the
+    /// source vendor or, with `archive_source`, keep it archived.
+    pub async fn merge_vendors(&self, source_id: &Uuid, destination_id: &Uuid, archive_source: bool, user_id: &Uuid) -> Result<VendorMergeResponse, AppError> {
+        if source_id == destination_id {
+            return Err(AppError::BadRequest("A vendor cannot be merged into itself.".to_string()));
+        }
+
+        let mut tx = self.pool.begin().await?;
+
+        let vendors = sqlx::query_as::<_, Vendor>(
+            r#"
+            SELECT id, name, description, archived
+            FROM vendor
+            WHERE id = ANY($1) AND user_id = $2
+            FOR UPDATE
+            "#,
+        )
+        .bind(vec![*source_id, *destination_id])
+        .bind(user_id)
+        .fetch_all(&mut *tx)
+        .await?;
+        if !vendors.iter().any(|vendor| vendor.id == *source_id) {
+            return Err(AppError::NotFound("Vendor not found".to_string()));
+        }
+        let destination = vendors
+            .into_iter()
+            .find(|vendor| vendor.id == *destination_id)
+            .ok_or_else(|| AppError::NotFound("Destination vendor not found".to_string()))?;
+        if destination.archived {
+            return Err(AppError::BadRequest("Cannot merge into an archived vendor.".to_string()));
+        }
+        // Closed periods keep their snapshots, so their transactions cannot change vendor
+        ensure_merge_leaves_closed_periods(&mut tx, None, Some(source_id), user_id).await?;
+
+        let transactions = sqlx::query("UPDATE transaction SET vendor_id = $1 WHERE vendor_id = $2 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let overlays = sqlx::query_as::<_, (Uuid, sqlx::types::Json<OverlayRule>)>("SELECT id, rules FROM overlays WHERE user_id = $1 AND rules IS NOT NULL")
+            .bind(user_id)
+            .fetch_all(&mut *tx)
+            .await?;
+        let mut overlay_rules = 0;
+        for (overlay_id, mut rules) in overlays {
+            if rules.0.replace_vendor_id(source_id, destination_id) {
+                sqlx::query("UPDATE overlays SET rules = $1, updated_at = now() WHERE id = $2")
+                    .bind(rules)
+                    .bind(overlay_id)
+                    .execute(&mut *tx)
+                    .await?;
+                overlay_rules += 1;
+            }
+        }
+
+        let templates =
+            sqlx::query_as::<_, (Uuid, sqlx::types::Json<OverlayRule>)>("SELECT id, rules FROM overlay_template WHERE user_id = $1 AND rules IS NOT NULL")
+                .bind(user_id)
+                .fetch_all(&mut *tx)
+                .await?;
+        let mut overlay_templates = 0;
+        for (template_id, mut rules) in templates {
+            if rules.0.replace_vendor_id(source_id, destination_id) {
+                sqlx::query("UPDATE overlay_template SET rules = $1, updated_at = now() WHERE id = $2")
+                    .bind(rules)
+                    .bind(template_id)
+                    .execute(&mut *tx)
+                    .await?;
+                overlay_templates += 1;
+            }
+        }
+
+        let aliases = sqlx::query("UPDATE vendor_alias SET vendor_id = $1 WHERE vendor_id = $2 AND user_id = $3")
+            .bind(destination_id)
+            .bind(source_id)
+            .bind(user_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+
+        let source_query = if archive_source {
+            "UPDATE vendor SET archived = TRUE WHERE id = $1 AND user_id = $2"
+        } else {
+            // Nothing refers to the vendor anymore, so the cascade to its transactions deletes nothing
+            "DELETE FROM vendor WHERE id = $1 AND user_id = $2"
+        };
+        sqlx::query(source_query).bind(source_id).bind(user_id).execute(&mut *tx).await?;
+
+        tx.commit().await?;
+
+        // Rules may look at the vendor of the moved transactions
+        self.refresh_rule_overlay_membership_after_commit(user_id).await;
+
+        Ok(VendorMergeResponse {
+            destination: VendorResponse::from(&destination),
+            transactions: transactions as i64,
+            overlay_rules,
+            overlay_templates,
+            aliases: aliases as i64,
+            source_archived: archive_source,
+        })
+    }
 }
@@ -0,0 +1,88 @@
+use crate::database::postgres_repository::{PostgresRepository, is_unique_violation};
+use crate::error::app_error::AppError;
+use crate::models::vendor_alias::{VendorAlias, VendorAliasRequest, resolve_vendor_alias};
+use uuid::Uuid;
+
+impl PostgresRepository {
+    async fn ensure_vendor_exists(&self, vendor_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        if self.get_vendor_by_id(vendor_id, user_id).await?.is_none() {
+            return Err(AppError::NotFound("Vendor not found".to_string()));
+        }
+        Ok(())
+    }
+
+    pub async fn list_vendor_aliases(&self, vendor_id: &Uuid, user_id: &Uuid) -> Result<Vec<VendorAlias>, AppError> {
+        self.ensure_vendor_exists(vendor_id, user_id).await?;
+
+        let aliases = sqlx::query_as::<_, VendorAlias>(
+            r#"
+            SELECT id, vendor_id, pattern, match_type, created_at
+            FROM vendor_alias
+            WHERE vendor_id = $1 AND user_id = $2
+            ORDER BY created_at, id
+            "#,
+        )
+        .bind(vendor_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(aliases)
+    }
+
+    pub async fn create_vendor_alias(&self, vendor_id: &Uuid, request: &VendorAliasRequest, user_id: &Uuid) -> Result<VendorAlias, AppError> {
+        self.ensure_vendor_exists(vendor_id, user_id).await?;
+
+        let alias = sqlx::query_as::<_, VendorAlias>(
+            r#"
+            INSERT INTO vendor_alias (user_id, vendor_id, pattern, match_type)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, vendor_id, pattern, match_type, created_at
+            "#,
+        )
+        .bind(user_id)
+        .bind(vendor_id)
+        .bind(request.pattern.trim())
+        .bind(request.match_type)
+        .fetch_one(&self.pool)
+        .await;
+
+        match alias {
+            Ok(alias) => Ok(alias),
+            Err(err) if is_unique_violation(&err) => Err(AppError::BadRequest("Vendor alias already exists".to_string())),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub async fn delete_vendor_alias(&self, vendor_id: &Uuid, alias_id: &Uuid, user_id: &Uuid) -> Result<(), AppError> {
+        let result = sqlx::query("DELETE FROM vendor_alias WHERE id = $1 AND vendor_id = $2 AND user_id = $3")
+            .bind(alias_id)
+            .bind(vendor_id)
+            .bind(user_id)
+            .execute(&self.pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("Vendor alias not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// The vendor whose aliases match a raw transaction description; archived vendors are never picked.
+    pub async fn resolve_vendor_id(&self, description: &str, user_id: &Uuid) -> Result<Option<Uuid>, AppError> {
+        let aliases = sqlx::query_as::<_, VendorAlias>(
+            r#"
+            SELECT va.id, va.vendor_id, va.pattern, va.match_type, va.created_at
+            FROM vendor_alias va
+            JOIN vendor v ON v.id = va.vendor_id
+            WHERE va.user_id = $1 AND v.archived = FALSE
+            ORDER BY va.created_at, va.id
+            "#,
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(resolve_vendor_alias(&aliases, description).map(|alias| alias.vendor_id))
+    }
+}
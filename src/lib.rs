@@ -14,7 +14,10 @@ mod service;
 pub mod test_utils;
 
 pub use config::Config;
-pub use cron_tasks::{GeneratePeriodsResult, generate_periods};
+pub use cron_tasks::{
+    EvaluateAlertsResult, FundAllowancesResult, GeneratePeriodsResult, InstantiateOverlaysResult, evaluate_alerts, fund_allowances, generate_periods,
+    instantiate_overlays,
+};
 
 use crate::db::stage_db;
 use crate::middleware::RequestLogger;
@@ -239,14 +242,21 @@ fn mount_api_routes(mut rocket: Rocket<Build>, base_path: &str) -> Rocket<Build>
     rocket = rocket.mount(join_base_path(base_path, "currency"), app_routes::currency::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "categories"), app_routes::category::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "budget-categories"), app_routes::budget_category::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "category-alerts"), app_routes::category_alert::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "category-targets"), app_routes::category_target::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "transactions"), app_routes::transaction::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "vendors"), app_routes::vendor::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "health"), app_routes::health::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "holiday-calendars"), app_routes::holiday_calendar::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "dashboard"), app_routes::dashboard::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "budget_period"), app_routes::budget_period::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "overlays"), app_routes::overlay::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "overlay-templates"), app_routes::overlay_template::routes().0);
     rocket = rocket.mount(join_base_path(base_path, "two-factor"), app_routes::two_factor::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "allowance-funding"), app_routes::allowance_funding::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "notifications"), app_routes::notification::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "budget-templates"), app_routes::budget_template::routes().0);
+    rocket = rocket.mount(join_base_path(base_path, "savings-goals"), app_routes::savings_goal::routes().0);
     rocket = rocket.mount(join_base_path(base_path, ""), app_routes::unlock::routes().0);
     rocket
 }
@@ -282,15 +292,22 @@ pub fn build_rocket(config: Config) -> Rocket<Build> {
             "/currency" => app_routes::currency::routes(),
             "/categories" => app_routes::category::routes(),
             "/budget-categories" => app_routes::budget_category::routes(),
+            "/category-alerts" => app_routes::category_alert::routes(),
             "/category-targets" => app_routes::category_target::routes(),
             "/transactions" => app_routes::transaction::routes(),
             "/vendors" => app_routes::vendor::routes(),
             "/health" => app_routes::health::routes(),
+            "/holiday-calendars" => app_routes::holiday_calendar::routes(),
             "/dashboard" => app_routes::dashboard::routes(),
             "/budget_period" => app_routes::budget_period::routes(),
             "/overlays" => app_routes::overlay::routes(),
+            "/overlay-templates" => app_routes::overlay_template::routes(),
             "/two-factor" => app_routes::two_factor::routes(),
             "" => app_routes::unlock::routes(),
+            "/allowance-funding" => app_routes::allowance_funding::routes(),
+            "/notifications" => app_routes::notification::routes(),
+            "/budget-templates" => app_routes::budget_template::routes(),
+            "/savings-goals" => app_routes::savings_goal::routes(),
         }
         if config.api.expose_swagger_ui {
             let docs_path = join_base_path(primary_base_path, "docs");
@@ -318,14 +335,21 @@ pub fn build_rocket(config: Config) -> Rocket<Build> {
                     "/currency" => app_routes::currency::routes(),
                     "/categories" => app_routes::category::routes(),
                 "/budget-categories" => app_routes::budget_category::routes(),
+                "/category-alerts" => app_routes::category_alert::routes(),
                 "/category-targets" => app_routes::category_target::routes(),
                 "/transactions" => app_routes::transaction::routes(),
                 "/vendors" => app_routes::vendor::routes(),
                 "/health" => app_routes::health::routes(),
+                "/holiday-calendars" => app_routes::holiday_calendar::routes(),
                 "/dashboard" => app_routes::dashboard::routes(),
                 "/budget_period" => app_routes::budget_period::routes(),
                 "/overlays" => app_routes::overlay::routes(),
+                "/overlay-templates" => app_routes::overlay_template::routes(),
                 "/two-factor" => app_routes::two_factor::routes(),
+                "/allowance-funding" => app_routes::allowance_funding::routes(),
+                "/notifications" => app_routes::notification::routes(),
+                "/budget-templates" => app_routes::budget_template::routes(),
+                "/savings-goals" => app_routes::savings_goal::routes(),
             }
             if config.api.expose_swagger_ui {
                 let docs_path = join_base_path(base_path, "docs");
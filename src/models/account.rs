@@ -115,6 +115,10 @@ pub struct AccountListResponse {
     pub balance_per_day: Vec<BudgetPerDayResponse>,
     pub balance_change_this_period: i64,
     pub transaction_count: i64,
+    /// Outgoing spend from this account within the requested period
+    pub spent_this_period: i64,
+    /// Headroom left under `spend_limit`; negative once the limit is exceeded
+    pub spend_limit_remaining: Option<i64>,
 }
 
 #[derive(Debug, Clone)]
@@ -122,9 +126,36 @@ pub struct AccountWithMetrics {
     pub account: Account,
     pub current_balance: i64,
     pub balance_change_this_period: i64,
+    pub spent_this_period: i64,
     pub transaction_count: i64,
 }
 
+/// Outgoing spend of a limited account within the budget period covering a given date.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AccountSpendInPeriod {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub spend_limit: i32,
+    pub spent: i64,
+    pub currency_symbol: String,
+    pub currency_decimal_places: i32,
+    pub period_id: Uuid,
+    pub period_name: String,
+}
+
+/// Remaining spend before `spend_limit` is reached, or `None` when the account has no limit.
+pub fn spend_limit_remaining(spend_limit: Option<i32>, spent: i64) -> Option<i64> {
+    spend_limit.map(|limit| i64::from(limit) - spent)
+}
+
+/// Spend as a share of `spend_limit` in basis points. A zero limit counts as fully used.
+pub fn spend_limit_usage_basis_points(spend_limit: i32, spent: i64) -> i64 {
+    if spend_limit <= 0 {
+        return if spent > 0 { i64::MAX } else { 0 };
+    }
+    spent.max(0).saturating_mul(10_000) / i64::from(spend_limit)
+}
+
 #[derive(Debug, Clone)]
 pub struct AccountBalancePerDay {
     pub account_id: Uuid,
@@ -175,6 +206,9 @@ pub struct AccountDetailResponse {
     pub net: i64,
     pub period_start: NaiveDate,
     pub period_end: NaiveDate,
+    pub spend_limit: Option<i32>,
+    pub spent_this_period: i64,
+    pub spend_limit_remaining: Option<i64>,
 }
 
 #[derive(Serialize, Debug, JsonSchema)]
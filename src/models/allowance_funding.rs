@@ -0,0 +1,195 @@
+use crate::models::budget_period::DurationUnit;
+use chrono::{DateTime, NaiveDate, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+use validator::{Validate, ValidationError};
+
+// ===== Enums =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FundingCadence {
+    /// Fund at the start of every budget period generated by the user's schedule
+    Period,
+    /// Fund on a custom interval starting from `starts_on`
+    Interval,
+}
+
+impl std::fmt::Display for FundingCadence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FundingCadence::Period => write!(f, "period"),
+            FundingCadence::Interval => write!(f, "interval"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FundingRunStatus {
+    Funded,
+    Skipped,
+}
+
+impl std::fmt::Display for FundingRunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FundingRunStatus::Funded => write!(f, "funded"),
+            FundingRunStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+// ===== Funding Rule Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AllowanceFundingRule {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub allowance_account_id: Uuid,
+    pub source_account_id: Uuid,
+    pub category_id: Uuid,
+    pub cadence: FundingCadence,
+    pub interval_value: Option<i32>,
+    pub interval_unit: Option<DurationUnit>,
+    pub starts_on: NaiveDate,
+    pub next_run_date: Option<NaiveDate>,
+    pub skip_next_run: bool,
+    pub next_run_amount_override: Option<i64>,
+    pub is_active: bool,
+    /// `next_transfer_amount` of the allowance account at the time the rule was read
+    pub next_transfer_amount: Option<i64>,
+}
+
+impl AllowanceFundingRule {
+    /// Amount the next run will transfer: the one-off override when set, otherwise the account's `next_transfer_amount`.
+    pub fn next_run_amount(&self) -> Option<i64> {
+        self.next_run_amount_override.or(self.next_transfer_amount)
+    }
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+#[validate(schema(function = "validate_funding_cadence"))]
+pub struct AllowanceFundingRuleRequest {
+    pub allowance_account_id: Uuid,
+    pub source_account_id: Uuid,
+    /// Transfer category used for the generated transactions
+    pub category_id: Uuid,
+    pub cadence: FundingCadence,
+    #[validate(range(min = 1))]
+    pub interval_value: Option<i32>,
+    pub interval_unit: Option<DurationUnit>,
+    /// First date a top-up may be created for
+    pub starts_on: NaiveDate,
+    #[serde(default = "default_is_active")]
+    pub is_active: bool,
+}
+
+fn default_is_active() -> bool {
+    true
+}
+
+fn validate_funding_cadence(request: &AllowanceFundingRuleRequest) -> Result<(), ValidationError> {
+    if request.allowance_account_id == request.source_account_id {
+        return Err(ValidationError::new("source_account_must_differ_from_allowance_account"));
+    }
+    if request.cadence == FundingCadence::Interval && (request.interval_value.is_none() || request.interval_unit.is_none()) {
+        return Err(ValidationError::new("interval_cadence_requires_interval_value_and_unit"));
+    }
+    Ok(())
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct NextRunAdjustmentRequest {
+    /// Skip the next scheduled run without transferring anything
+    #[serde(default)]
+    pub skip: bool,
+    /// Transfer this amount on the next run instead of `next_transfer_amount`
+    #[validate(range(min = 0))]
+    pub amount_override: Option<i64>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct AllowanceFundingRuleResponse {
+    pub id: Uuid,
+    pub allowance_account_id: Uuid,
+    pub source_account_id: Uuid,
+    pub category_id: Uuid,
+    pub cadence: FundingCadence,
+    pub interval_value: Option<i32>,
+    pub interval_unit: Option<DurationUnit>,
+    pub starts_on: NaiveDate,
+    pub next_run_date: Option<NaiveDate>,
+    pub next_run_amount: Option<i64>,
+    pub skip_next_run: bool,
+    pub next_run_amount_override: Option<i64>,
+    pub is_active: bool,
+}
+
+impl From<&AllowanceFundingRule> for AllowanceFundingRuleResponse {
+    fn from(rule: &AllowanceFundingRule) -> Self {
+        Self {
+            id: rule.id,
+            allowance_account_id: rule.allowance_account_id,
+            source_account_id: rule.source_account_id,
+            category_id: rule.category_id,
+            cadence: rule.cadence,
+            interval_value: rule.interval_value,
+            interval_unit: rule.interval_unit,
+            starts_on: rule.starts_on,
+            next_run_date: rule.next_run_date,
+            next_run_amount: rule.next_run_amount(),
+            skip_next_run: rule.skip_next_run,
+            next_run_amount_override: rule.next_run_amount_override,
+            is_active: rule.is_active,
+        }
+    }
+}
+
+// ===== Funding Run Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AllowanceFundingRun {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub scheduled_for: NaiveDate,
+    pub status: FundingRunStatus,
+    pub amount: i64,
+    pub transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct AllowanceFundingRunResponse {
+    pub id: Uuid,
+    pub rule_id: Uuid,
+    pub scheduled_for: NaiveDate,
+    pub status: FundingRunStatus,
+    pub amount: i64,
+    pub transaction_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&AllowanceFundingRun> for AllowanceFundingRunResponse {
+    fn from(run: &AllowanceFundingRun) -> Self {
+        Self {
+            id: run.id,
+            rule_id: run.rule_id,
+            scheduled_for: run.scheduled_for,
+            status: run.status,
+            amount: run.amount,
+            transaction_id: run.transaction_id,
+            created_at: run.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct AllowanceFundingResult {
+    pub rules_processed: i64,
+    pub transfers_created: i64,
+    pub runs_skipped: i64,
+}
@@ -44,6 +44,56 @@ impl std::fmt::Display for WeekendAdjustment {
     }
 }
 
+/// Where a period boundary moves when it falls on a weekday holiday
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum HolidayAdjustment {
+    #[default]
+    Keep,
+    /// The closest business day before the holiday
+    Previous,
+    /// The closest business day after the holiday
+    Next,
+}
+
+impl std::fmt::Display for HolidayAdjustment {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HolidayAdjustment::Keep => write!(f, "keep"),
+            HolidayAdjustment::Previous => write!(f, "previous"),
+            HolidayAdjustment::Next => write!(f, "next"),
+        }
+    }
+}
+
+/// How a schedule places period boundaries
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleKind {
+    /// Periods of `duration_value`/`duration_unit` starting on `start_day`
+    #[default]
+    Interval,
+    /// Two periods a month, starting on `start_day` and `second_day`
+    SemiMonthly,
+    /// Periods of `duration_value`/`duration_unit` counted from `anchor_date`, e.g. every other Friday
+    Anchored,
+    /// Periods starting on the last weekday of every month
+    LastBusinessDay,
+}
+
+impl std::fmt::Display for ScheduleKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScheduleKind::Interval => write!(f, "interval"),
+            ScheduleKind::SemiMonthly => write!(f, "semi_monthly"),
+            ScheduleKind::Anchored => write!(f, "anchored"),
+            ScheduleKind::LastBusinessDay => write!(f, "last_business_day"),
+        }
+    }
+}
+
 // ===== Budget Period Models =====
 
 #[derive(Serialize, Debug, Clone, Default, sqlx::FromRow)]
@@ -55,6 +105,7 @@ pub struct BudgetPeriod {
     pub end_date: NaiveDate,
     pub is_auto_generated: bool,
     pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Clone)]
@@ -89,6 +140,8 @@ pub struct BudgetPeriodResponse {
     pub is_auto_generated: bool,
     pub transaction_count: i64,
     pub budget_used_percentage: f64,
+    /// Set once the period is closed; its transactions are locked until it is reopened
+    pub closed_at: Option<DateTime<Utc>>,
 }
 
 impl From<&BudgetPeriod> for BudgetPeriodResponse {
@@ -101,6 +154,7 @@ impl From<&BudgetPeriod> for BudgetPeriodResponse {
             is_auto_generated: budget_period.is_auto_generated,
             transaction_count: 0,
             budget_used_percentage: 0.0,
+            closed_at: budget_period.closed_at,
         }
     }
 }
@@ -115,6 +169,7 @@ impl From<&BudgetPeriodWithMetrics> for BudgetPeriodResponse {
             is_auto_generated: period_with_metrics.period.is_auto_generated,
             transaction_count: period_with_metrics.transaction_count,
             budget_used_percentage: period_with_metrics.budget_used_percentage,
+            closed_at: period_with_metrics.period.closed_at,
         }
     }
 }
@@ -124,11 +179,15 @@ impl From<&BudgetPeriodWithMetrics> for BudgetPeriodResponse {
 pub struct PeriodSchedule {
     pub id: Uuid,
     pub user_id: Uuid,
+    pub schedule_kind: ScheduleKind,
     pub start_day: i32,
+    pub second_day: Option<i32>,
+    pub anchor_date: Option<NaiveDate>,
     pub duration_value: i32,
     pub duration_unit: DurationUnit,
     pub saturday_adjustment: WeekendAdjustment,
     pub sunday_adjustment: WeekendAdjustment,
+    pub holiday_adjustment: HolidayAdjustment,
     pub name_pattern: String,
     pub generate_ahead: i32,
     pub created_at: DateTime<Utc>,
@@ -136,28 +195,53 @@ pub struct PeriodSchedule {
 }
 
 #[derive(Deserialize, Debug, Validate, JsonSchema)]
+#[validate(schema(function = "validate_schedule_kind"))]
 pub struct PeriodScheduleRequest {
+    #[serde(default)]
+    pub schedule_kind: ScheduleKind,
     #[validate(range(min = 1, max = 31))]
     pub start_day: i32,
+    /// Second start day of the month for semi-monthly schedules
+    #[validate(range(min = 1, max = 31))]
+    pub second_day: Option<i32>,
+    /// First period start of anchored schedules
+    pub anchor_date: Option<NaiveDate>,
     #[validate(range(min = 1))]
     pub duration_value: i32,
     pub duration_unit: DurationUnit,
     pub saturday_adjustment: WeekendAdjustment,
     pub sunday_adjustment: WeekendAdjustment,
+    #[serde(default)]
+    pub holiday_adjustment: HolidayAdjustment,
     #[validate(length(min = 1))]
     pub name_pattern: String,
     #[validate(range(min = 0))]
     pub generate_ahead: i32,
 }
 
+fn validate_schedule_kind(request: &PeriodScheduleRequest) -> Result<(), ValidationError> {
+    match request.schedule_kind {
+        ScheduleKind::SemiMonthly => match request.second_day {
+            Some(second_day) if second_day > request.start_day => Ok(()),
+            _ => Err(ValidationError::new("second_day_must_be_after_start_day")),
+        },
+        ScheduleKind::Anchored if request.anchor_date.is_none() => Err(ValidationError::new("anchor_date_required")),
+        _ => Ok(()),
+    }
+}
+
 #[derive(Serialize, Debug, JsonSchema)]
 pub struct PeriodScheduleResponse {
     pub id: Uuid,
+    pub schedule_kind: ScheduleKind,
     pub start_day: i32,
+    pub second_day: Option<i32>,
+    pub anchor_date: Option<NaiveDate>,
     pub duration_value: i32,
     pub duration_unit: DurationUnit,
     pub saturday_adjustment: WeekendAdjustment,
     pub sunday_adjustment: WeekendAdjustment,
+    pub holiday_adjustment: HolidayAdjustment,
     pub name_pattern: String,
     pub generate_ahead: i32,
 }
@@ -166,17 +250,29 @@ impl From<&PeriodSchedule> for PeriodScheduleResponse {
     fn from(schedule: &PeriodSchedule) -> Self {
         Self {
             id: schedule.id,
+            schedule_kind: schedule.schedule_kind,
             start_day: schedule.start_day,
+            second_day: schedule.second_day,
+            anchor_date: schedule.anchor_date,
             duration_value: schedule.duration_value,
             duration_unit: schedule.duration_unit,
             saturday_adjustment: schedule.saturday_adjustment,
             sunday_adjustment: schedule.sunday_adjustment,
+            holiday_adjustment: schedule.holiday_adjustment,
             name_pattern: schedule.name_pattern.clone(),
             generate_ahead: schedule.generate_ahead,
         }
     }
 }
 
+/// A period a schedule would generate
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct PeriodPreview {
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
 // ===== Gap Detection Models =====
 
 #[derive(Serialize, Debug, Clone, JsonSchema)]
@@ -187,12 +283,50 @@ pub struct UnassignedTransaction {
     pub amount: i64,
 }
 
+/// A run of days outside every period that holds unassigned transactions. Bounded by the neighbouring periods;
+/// an open side ends at the outermost unassigned transaction.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct PeriodGap {
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub transaction_count: i64,
+}
+
 #[derive(Serialize, Debug, JsonSchema)]
 pub struct GapsResponse {
+    /// Exact number of transactions outside every period
     pub unassigned_count: i64,
+    pub gaps: Vec<PeriodGap>,
+    /// The 100 most recent unassigned transactions
     pub transactions: Vec<UnassignedTransaction>,
 }
 
+/// Fill gaps with periods of the given duration instead of the user's period schedule
+#[derive(Deserialize, Debug, Default, Validate, JsonSchema)]
+#[validate(schema(function = "validate_fill_gaps_duration"))]
+pub struct FillGapsRequest {
+    #[validate(range(min = 1))]
+    pub duration_value: Option<i32>,
+    pub duration_unit: Option<DurationUnit>,
+    /// Defaults to the schedule's name pattern, or `{start_date} - {end_date}` without a schedule
+    #[validate(length(min = 1))]
+    pub name_pattern: Option<String>,
+}
+
+fn validate_fill_gaps_duration(request: &FillGapsRequest) -> Result<(), ValidationError> {
+    if request.duration_value.is_some() != request.duration_unit.is_some() {
+        return Err(ValidationError::new("duration_value_and_duration_unit_go_together"));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct FillGapsResponse {
+    /// Periods that cover every unassigned transaction
+    pub periods: Vec<PeriodPreview>,
+    pub covered_transaction_count: i64,
+}
+
 #[derive(Serialize, Debug)]
 pub struct AutoPeriodGenerationResponse {
     pub users_processed: i64,
@@ -0,0 +1,113 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::Serialize;
+use schemars::JsonSchema;
+use uuid::Uuid;
+
+// ===== Snapshot Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct BudgetPeriodSnapshot {
+    pub period_id: Uuid,
+    pub total_budgeted: i64,
+    pub total_spent: i64,
+    pub total_income: i64,
+    pub transaction_count: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategorySnapshot {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub category_type: String,
+    pub target: Option<i32>,
+    pub actual: i64,
+    pub is_excluded: bool,
+    pub within_horizon: bool,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct AccountSnapshot {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub account_type: String,
+    pub closing_balance: i64,
+}
+
+// ===== Responses =====
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategorySnapshotResponse {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub category_type: String,
+    pub target: Option<i32>,
+    pub actual: i64,
+    /// `actual - target`; positive means the target was exceeded
+    pub variance: Option<i64>,
+    pub is_excluded: bool,
+    /// Long-horizon target paid within its horizon amount, so the payment was planned
+    pub within_horizon: bool,
+}
+
+impl From<&CategorySnapshot> for CategorySnapshotResponse {
+    fn from(snapshot: &CategorySnapshot) -> Self {
+        Self {
+            category_id: snapshot.category_id,
+            category_name: snapshot.category_name.clone(),
+            category_type: snapshot.category_type.clone(),
+            target: snapshot.target,
+            actual: snapshot.actual,
+            variance: snapshot.target.map(|target| snapshot.actual - i64::from(target)),
+            is_excluded: snapshot.is_excluded,
+            within_horizon: snapshot.within_horizon,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct AccountSnapshotResponse {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub account_type: String,
+    /// Balance at the end of the period's last day
+    pub closing_balance: i64,
+}
+
+impl From<&AccountSnapshot> for AccountSnapshotResponse {
+    fn from(snapshot: &AccountSnapshot) -> Self {
+        Self {
+            account_id: snapshot.account_id,
+            account_name: snapshot.account_name.clone(),
+            account_type: snapshot.account_type.clone(),
+            closing_balance: snapshot.closing_balance,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct BudgetPeriodSnapshotResponse {
+    pub period_id: Uuid,
+    pub closed_at: DateTime<Utc>,
+    pub total_budgeted: i64,
+    pub total_spent: i64,
+    pub total_income: i64,
+    pub transaction_count: i64,
+    pub categories: Vec<CategorySnapshotResponse>,
+    pub accounts: Vec<AccountSnapshotResponse>,
+}
+
+impl BudgetPeriodSnapshotResponse {
+    pub fn new(snapshot: &BudgetPeriodSnapshot, categories: &[CategorySnapshot], accounts: &[AccountSnapshot]) -> Self {
+        Self {
+            period_id: snapshot.period_id,
+            closed_at: snapshot.created_at,
+            total_budgeted: snapshot.total_budgeted,
+            total_spent: snapshot.total_spent,
+            total_income: snapshot.total_income,
+            transaction_count: snapshot.transaction_count,
+            categories: categories.iter().map(CategorySnapshotResponse::from).collect(),
+            accounts: accounts.iter().map(AccountSnapshotResponse::from).collect(),
+        }
+    }
+}
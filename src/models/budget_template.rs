@@ -0,0 +1,103 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+// ===== Budget Template Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct BudgetTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct BudgetTemplateEntry {
+    pub template_id: Uuid,
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub budgeted_value: i32,
+    pub is_excluded: bool,
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct BudgetTemplateEntryRequest {
+    pub category_id: Uuid,
+    #[validate(range(min = 0))]
+    pub budgeted_value: i32,
+    /// Exclude the category from target tracking when the template is applied
+    #[serde(default)]
+    pub is_excluded: bool,
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct BudgetTemplateRequest {
+    #[validate(length(min = 3))]
+    pub name: String,
+    pub description: Option<String>,
+    #[validate(nested)]
+    pub entries: Vec<BudgetTemplateEntryRequest>,
+}
+
+/// Save the targets and exclusions in effect for a period as a new template
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct BudgetTemplateFromPeriodRequest {
+    #[validate(length(min = 3))]
+    pub name: String,
+    pub description: Option<String>,
+    pub period_id: Uuid,
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct ApplyBudgetTemplateRequest {
+    /// Open budget periods to apply the template to
+    #[validate(length(min = 1))]
+    pub period_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct BudgetTemplateEntryResponse {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub budgeted_value: i32,
+    pub is_excluded: bool,
+}
+
+impl From<&BudgetTemplateEntry> for BudgetTemplateEntryResponse {
+    fn from(entry: &BudgetTemplateEntry) -> Self {
+        Self {
+            category_id: entry.category_id,
+            category_name: entry.category_name.clone(),
+            budgeted_value: entry.budgeted_value,
+            is_excluded: entry.is_excluded,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct BudgetTemplateResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub entries: Vec<BudgetTemplateEntryResponse>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl BudgetTemplateResponse {
+    pub fn from_parts(template: &BudgetTemplate, entries: &[BudgetTemplateEntry]) -> Self {
+        Self {
+            id: template.id,
+            name: template.name.clone(),
+            description: template.description.clone(),
+            entries: entries
+                .iter()
+                .filter(|entry| entry.template_id == template.id)
+                .map(BudgetTemplateEntryResponse::from)
+                .collect(),
+            created_at: template.created_at,
+        }
+    }
+}
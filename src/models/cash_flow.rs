@@ -0,0 +1,371 @@
+use crate::models::holiday_calendar::HolidayCalendar;
+use chrono::{Datelike, Duration, NaiveDate};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// Forecast horizons the cash flow projection can be requested for, in days.
+pub const CASH_FLOW_HORIZON_DAYS: [i64; 3] = [30, 60, 90];
+
+/// Days of history the average daily discretionary spend is taken from.
+pub const DISCRETIONARY_LOOKBACK_DAYS: i64 = 90;
+
+/// A vendor counts as a monthly recurring item of an account when it appeared in each of this many previous months.
+pub const RECURRING_LOOKBACK_MONTHS: i32 = 3;
+
+// ===== Enums =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CashFlowEventSource {
+    /// A transaction already entered with a future date
+    Scheduled,
+    /// A monthly item detected from previous months
+    Recurring,
+    /// An upcoming allowance funding run
+    PlannedTransfer,
+}
+
+// ===== Projection Inputs =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CashFlowAccount {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub account_type: String,
+    pub current_balance: i64,
+    /// Average daily Outgoing spend that is not part of a recurring item
+    pub daily_discretionary_spend: i64,
+}
+
+/// A monthly item detected on an account: same vendor and category in each of the previous months
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RecurringCashFlowItem {
+    pub account_id: Uuid,
+    pub vendor_id: Uuid,
+    pub category_id: Uuid,
+    pub description: String,
+    /// Signed effect on the account balance
+    pub amount: i64,
+    pub day_of_month: i32,
+    pub last_occurred_on: NaiveDate,
+}
+
+/// A dated change to an account balance within the forecast horizon
+#[derive(Debug, Clone)]
+pub struct CashFlowEvent {
+    pub account_id: Uuid,
+    pub date: NaiveDate,
+    /// Signed effect on the account balance
+    pub amount: i64,
+    pub description: String,
+    pub source: CashFlowEventSource,
+    pub vendor_id: Option<Uuid>,
+    pub category_id: Option<Uuid>,
+}
+
+// ===== Responses =====
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct CashFlowPoint {
+    pub date: NaiveDate,
+    pub balance: i64,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct CashFlowEventResponse {
+    pub account_id: Uuid,
+    pub date: NaiveDate,
+    pub amount: i64,
+    pub description: String,
+    pub source: CashFlowEventSource,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct AccountCashFlowResponse {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub account_type: String,
+    pub current_balance: i64,
+    pub daily_discretionary_spend: i64,
+    pub lowest_balance: i64,
+    pub lowest_balance_date: NaiveDate,
+    /// First date the projected balance is negative
+    pub first_below_zero: Option<NaiveDate>,
+    /// First date the projected balance is under the requested floor
+    pub first_below_floor: Option<NaiveDate>,
+    /// Projected end-of-day balance, starting with today
+    pub series: Vec<CashFlowPoint>,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct CashFlowShortfallResponse {
+    pub account_id: Uuid,
+    pub account_name: String,
+    pub date: NaiveDate,
+    pub balance: i64,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CashFlowForecastResponse {
+    pub as_of: NaiveDate,
+    pub days: i64,
+    pub floor: i64,
+    /// Combined balance of all projected accounts
+    pub total: Vec<CashFlowPoint>,
+    /// Earliest date any account is projected to go below zero
+    pub first_below_zero: Option<CashFlowShortfallResponse>,
+    /// Earliest date any account is projected to go below `floor`
+    pub first_below_floor: Option<CashFlowShortfallResponse>,
+    pub accounts: Vec<AccountCashFlowResponse>,
+    pub events: Vec<CashFlowEventResponse>,
+}
+
+/// Dates a recurring item is expected on after `today` and up to `until`.
+///
+/// The item lands on its usual day of the month (the last day in shorter months), or on the next business day when
+/// that day is a holiday. A month is skipped when the item already occurred in it, or when a transaction for the
+/// same vendor and category is already scheduled then.
+pub fn recurring_occurrences(
+    item: &RecurringCashFlowItem,
+    today: NaiveDate,
+    until: NaiveDate,
+    scheduled: &[CashFlowEvent],
+    holidays: &HolidayCalendar,
+) -> Vec<NaiveDate> {
+    let month_key = |date: NaiveDate| date.year() * 12 + date.month0() as i32;
+    let mut dates = Vec::new();
+    let mut month = month_key(today);
+
+    while month <= month_key(until) {
+        let (year, month0) = (month.div_euclid(12), month.rem_euclid(12) as u32);
+        if let Some(date) = day_in_month(year, month0 + 1, item.day_of_month)
+            && let Some(date) = if holidays.is_holiday(date) {
+                holidays.next_business_day(date)
+            } else {
+                Some(date)
+            }
+            && date > today
+            && date <= until
+            && month != month_key(item.last_occurred_on)
+            && !scheduled.iter().any(|event| {
+                event.account_id == item.account_id
+                    && event.vendor_id == Some(item.vendor_id)
+                    && event.category_id == Some(item.category_id)
+                    && month_key(event.date) == month
+            })
+        {
+            dates.push(date);
+        }
+        month += 1;
+    }
+
+    dates
+}
+
+fn day_in_month(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)?
+    };
+    let last_day = (next_month - first).num_days() as i32;
+    NaiveDate::from_ymd_opt(year, month, day.clamp(1, last_day) as u32)
+}
+
+/// Walk each account forward day by day from its current balance: discretionary spend leaves every day and
+/// dated events land on their date.
+pub fn project_cash_flow(accounts: &[CashFlowAccount], events: &[CashFlowEvent], today: NaiveDate, days: i64, floor: i64) -> CashFlowForecastResponse {
+    let mut events_by_day: HashMap<(Uuid, NaiveDate), i64> = HashMap::new();
+    for event in events {
+        *events_by_day.entry((event.account_id, event.date)).or_default() += event.amount;
+    }
+
+    let mut total: Vec<CashFlowPoint> = (0..=days)
+        .map(|offset| CashFlowPoint {
+            date: today + Duration::days(offset),
+            balance: 0,
+        })
+        .collect();
+
+    let mut account_responses = Vec::with_capacity(accounts.len());
+    for account in accounts {
+        let mut balance = account.current_balance;
+        let mut series = Vec::with_capacity(total.len());
+
+        for (offset, point) in total.iter_mut().enumerate() {
+            if offset > 0 {
+                balance -= account.daily_discretionary_spend;
+                balance += events_by_day.get(&(account.account_id, point.date)).copied().unwrap_or(0);
+            }
+            point.balance += balance;
+            series.push(CashFlowPoint { date: point.date, balance });
+        }
+
+        let lowest = series
+            .iter()
+            .min_by_key(|point| point.balance)
+            .cloned()
+            .unwrap_or(CashFlowPoint { date: today, balance });
+        account_responses.push(AccountCashFlowResponse {
+            account_id: account.account_id,
+            account_name: account.account_name.clone(),
+            account_type: account.account_type.clone(),
+            current_balance: account.current_balance,
+            daily_discretionary_spend: account.daily_discretionary_spend,
+            lowest_balance: lowest.balance,
+            lowest_balance_date: lowest.date,
+            first_below_zero: series.iter().find(|point| point.balance < 0).map(|point| point.date),
+            first_below_floor: series.iter().find(|point| point.balance < floor).map(|point| point.date),
+            series,
+        });
+    }
+
+    let earliest_shortfall = |threshold: i64| {
+        account_responses
+            .iter()
+            .filter_map(|account| {
+                account
+                    .series
+                    .iter()
+                    .find(|point| point.balance < threshold)
+                    .map(|point| CashFlowShortfallResponse {
+                        account_id: account.account_id,
+                        account_name: account.account_name.clone(),
+                        date: point.date,
+                        balance: point.balance,
+                    })
+            })
+            .min_by_key(|shortfall| shortfall.date)
+    };
+
+    let mut event_responses: Vec<CashFlowEventResponse> = events
+        .iter()
+        .map(|event| CashFlowEventResponse {
+            account_id: event.account_id,
+            date: event.date,
+            amount: event.amount,
+            description: event.description.clone(),
+            source: event.source,
+        })
+        .collect();
+    event_responses.sort_by_key(|event| event.date);
+
+    CashFlowForecastResponse {
+        as_of: today,
+        days,
+        floor,
+        total,
+        first_below_zero: earliest_shortfall(0),
+        first_below_floor: earliest_shortfall(floor),
+        accounts: account_responses,
+        events: event_responses,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::holiday_calendar::HolidayRule;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn account(current_balance: i64, daily_discretionary_spend: i64) -> CashFlowAccount {
+        CashFlowAccount {
+            account_id: Uuid::new_v4(),
+            account_name: "Checking".to_string(),
+            account_type: "Checking".to_string(),
+            current_balance,
+            daily_discretionary_spend,
+        }
+    }
+
+    fn event(account_id: Uuid, date: NaiveDate, amount: i64) -> CashFlowEvent {
+        CashFlowEvent {
+            account_id,
+            date,
+            amount,
+            description: "Salary".to_string(),
+            source: CashFlowEventSource::Recurring,
+            vendor_id: None,
+            category_id: None,
+        }
+    }
+
+    fn rent(day_of_month: i32, last_occurred_on: NaiveDate) -> RecurringCashFlowItem {
+        RecurringCashFlowItem {
+            account_id: Uuid::new_v4(),
+            vendor_id: Uuid::new_v4(),
+            category_id: Uuid::new_v4(),
+            description: "Rent".to_string(),
+            amount: -90_000,
+            day_of_month,
+            last_occurred_on,
+        }
+    }
+
+    #[test]
+    fn recurring_item_skips_months_it_already_occurred_in() {
+        let item = rent(1, date(2026, 4, 1));
+        assert_eq!(
+            recurring_occurrences(&item, date(2026, 4, 10), date(2026, 7, 9), &[], &HolidayCalendar::default()),
+            vec![date(2026, 5, 1), date(2026, 6, 1), date(2026, 7, 1)]
+        );
+    }
+
+    #[test]
+    fn recurring_item_uses_last_day_of_short_months() {
+        let item = rent(31, date(2026, 1, 31));
+        assert_eq!(
+            recurring_occurrences(&item, date(2026, 2, 1), date(2026, 4, 15), &[], &HolidayCalendar::default()),
+            vec![date(2026, 2, 28), date(2026, 3, 31)]
+        );
+    }
+
+    #[test]
+    fn recurring_item_on_holiday_moves_to_next_business_day() {
+        let item = rent(1, date(2026, 4, 1));
+        let holidays = HolidayCalendar::new(vec![HolidayRule::Annual {
+            name: "Labour Day".to_string(),
+            month: 5,
+            day: 1,
+        }]);
+        // 2026-05-01 is a Friday
+        assert_eq!(
+            recurring_occurrences(&item, date(2026, 4, 10), date(2026, 6, 9), &[], &holidays),
+            vec![date(2026, 5, 4), date(2026, 6, 1)]
+        );
+    }
+
+    #[test]
+    fn scheduled_transaction_replaces_recurring_occurrence() {
+        let item = rent(1, date(2026, 4, 1));
+        let mut scheduled = event(item.account_id, date(2026, 5, 2), -95_000);
+        scheduled.vendor_id = Some(item.vendor_id);
+        scheduled.category_id = Some(item.category_id);
+        assert_eq!(
+            recurring_occurrences(&item, date(2026, 4, 10), date(2026, 6, 9), &[scheduled], &HolidayCalendar::default()),
+            vec![date(2026, 6, 1)]
+        );
+    }
+
+    #[test]
+    fn projection_flags_first_shortfall_before_salary_arrives() {
+        let checking = account(10_000, 2_000);
+        let events = vec![event(checking.account_id, date(2026, 4, 8), 300_000)];
+        let forecast = project_cash_flow(std::slice::from_ref(&checking), &events, date(2026, 4, 1), 30, 5_000);
+
+        let projected = &forecast.accounts[0];
+        assert_eq!(projected.series.len(), 31);
+        assert_eq!(projected.first_below_floor, Some(date(2026, 4, 4)));
+        assert_eq!(projected.first_below_zero, Some(date(2026, 4, 7)));
+        assert_eq!(projected.lowest_balance, -2_000);
+        assert_eq!(projected.lowest_balance_date, date(2026, 4, 7));
+        assert_eq!(forecast.first_below_zero.map(|shortfall| shortfall.date), Some(date(2026, 4, 7)));
+        assert_eq!(forecast.total[7].balance, 296_000);
+    }
+}
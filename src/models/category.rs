@@ -1,3 +1,4 @@
+use crate::models::category_target::TargetAccrual;
 use crate::models::dashboard::{BudgetStabilityPeriodResponse, PeriodContextSummaryResponse};
 use rocket::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
@@ -98,6 +99,12 @@ pub struct CategoryBudgetedDiagnosticsRow {
     pub variance_value: i64,
     /// Progress in basis points (percent * 100). Example: 12_500 = 125.00%.
     pub progress_basis_points: i32,
+    pub rollover_enabled: bool,
+    /// Net unspent (positive) or overspent (negative) budget carried in from earlier periods
+    pub rolled_over_value: i64,
+    pub available_value: i64,
+    /// Accrued vs. spent in the current horizon cycle, for long-horizon targets
+    pub accrual: Option<TargetAccrual>,
     pub recent_closed_periods: Vec<BudgetStabilityPeriodResponse>,
 }
 
@@ -110,6 +117,12 @@ pub struct CategoryBudgetedDiagnosticsRowResponse {
     pub variance_value: i64,
     /// Progress in basis points (percent * 100). Example: 12_500 = 125.00%.
     pub progress_basis_points: i32,
+    pub rollover_enabled: bool,
+    /// Net unspent (positive) or overspent (negative) budget carried in from earlier periods
+    pub rolled_over_value: i64,
+    pub available_value: i64,
+    /// Accrued vs. spent in the current horizon cycle, for long-horizon targets
+    pub accrual: Option<TargetAccrual>,
     pub recent_closed_periods: Vec<BudgetStabilityPeriodResponse>,
 }
 
@@ -187,6 +200,10 @@ impl From<&CategoryBudgetedDiagnosticsRow> for CategoryBudgetedDiagnosticsRowRes
             actual_value: row.actual_value,
             variance_value: row.variance_value,
             progress_basis_points: row.progress_basis_points,
+            rollover_enabled: row.rollover_enabled,
+            rolled_over_value: row.rolled_over_value,
+            available_value: row.available_value,
+            accrual: row.accrual.clone(),
             recent_closed_periods: row.recent_closed_periods.clone(),
         }
     }
@@ -279,6 +296,11 @@ pub fn variance_value(actual_value: i64, budgeted_value: i32) -> i64 {
     actual_value.saturating_sub(i64::from(budgeted_value))
 }
 
+/// Budget left to spend once the rolled-over amount is added: `budgeted + rolled_over - actual`.
+pub fn available_value(budgeted_value: i32, rolled_over_value: i64, actual_value: i64) -> i64 {
+    i64::from(budgeted_value).saturating_add(rolled_over_value).saturating_sub(actual_value)
+}
+
 pub fn progress_basis_points(actual_value: i64, budgeted_value: i32) -> i32 {
     if budgeted_value <= 0 {
         return 0;
@@ -328,7 +350,7 @@ fn category_with_stats_response_example() -> serde_json::Value {
 
 #[cfg(test)]
 mod tests {
-    use super::{difference_vs_average_percentage, progress_basis_points, share_of_total_basis_points, variance_value};
+    use super::{available_value, difference_vs_average_percentage, progress_basis_points, share_of_total_basis_points, variance_value};
 
     #[test]
     fn test_difference_vs_average_percentage_zero_average() {
@@ -347,6 +369,12 @@ mod tests {
         assert_eq!(variance_value(8_000, 10_000), -2_000);
     }
 
+    #[test]
+    fn test_available_value_includes_rollover() {
+        assert_eq!(available_value(10_000, 2_500, 8_000), 4_500);
+        assert_eq!(available_value(10_000, -3_000, 8_000), -1_000);
+    }
+
     #[test]
     fn test_progress_basis_points() {
         assert_eq!(progress_basis_points(12_500, 10_000), 12_500);
@@ -0,0 +1,71 @@
+use crate::models::settings::validate_alert_thresholds;
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+// ===== Alert Settings =====
+
+/// Alert configuration of an Outgoing category, with the per-category override if there is one
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategoryAlertSetting {
+    pub category_id: Uuid,
+    pub category_name: String,
+    /// Thresholds in effect: the override, or the global settings
+    pub thresholds: Vec<i32>,
+    pub is_custom: bool,
+    pub is_enabled: bool,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryAlertSettingResponse {
+    pub category_id: Uuid,
+    pub category_name: String,
+    /// Share of the period target (basis points, e.g. 8000 = 80%) that raises an alert
+    pub thresholds: Vec<i32>,
+    /// `false` when the category follows the global thresholds from settings
+    pub is_custom: bool,
+    pub is_enabled: bool,
+}
+
+impl From<&CategoryAlertSetting> for CategoryAlertSettingResponse {
+    fn from(setting: &CategoryAlertSetting) -> Self {
+        Self {
+            category_id: setting.category_id,
+            category_name: setting.category_name.clone(),
+            thresholds: setting.thresholds.clone(),
+            is_custom: setting.is_custom,
+            is_enabled: setting.is_enabled,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct CategoryAlertSettingRequest {
+    /// Share of the period target (basis points, e.g. 12000 = 120%) that raises an alert
+    #[validate(length(min = 1), custom(function = "validate_alert_thresholds"))]
+    pub thresholds: Vec<i32>,
+    /// `false` silences alerts for the category
+    #[serde(default = "default_enabled")]
+    pub is_enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+// ===== Alert Evaluation =====
+
+/// Spending of an Outgoing category against its target in the period covering a date
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategorySpendInPeriod {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub target: i32,
+    pub spent: i64,
+    pub thresholds: Vec<i32>,
+    pub currency_symbol: String,
+    pub currency_decimal_places: i32,
+    pub period_id: Uuid,
+    pub period_name: String,
+}
@@ -0,0 +1,87 @@
+use crate::models::category::CategoryResponse;
+use chrono::{DateTime, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+
+/// Merge a category into another one
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct MergeCategoryRequest {
+    /// Category that takes over everything of the merged category
+    pub destination_id: Uuid,
+}
+
+/// Records pointing at the source category, by what a merge does with them. Where the destination already has
+/// a budget target, overlay cap, template entry or alert setting, it keeps its own and the source's is dropped.
+/// Target moves between the two categories are dropped too.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, JsonSchema)]
+pub struct CategoryMergeCounts {
+    pub transactions: i64,
+    pub subcategories: i64,
+    /// 0 or 1: the source's budget target, moved when the destination has none
+    pub budget_targets: i64,
+    #[serde(default)]
+    pub budget_targets_dropped: i64,
+    pub overlay_caps: i64,
+    #[serde(default)]
+    pub overlay_caps_dropped: i64,
+    /// Overlays whose rules name the source category
+    pub overlay_rules: i64,
+    /// Overlay templates whose rules or caps name the source category
+    pub overlay_templates: i64,
+    pub allowance_funding_rules: i64,
+    pub budget_template_entries: i64,
+    #[serde(default)]
+    pub budget_template_entries_dropped: i64,
+    pub target_moves: i64,
+    #[serde(default)]
+    pub target_moves_dropped: i64,
+    pub alert_settings: i64,
+    #[serde(default)]
+    pub alert_settings_dropped: i64,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryMergePreviewResponse {
+    pub source: CategoryResponse,
+    pub destination: CategoryResponse,
+    pub counts: CategoryMergeCounts,
+}
+
+/// Audit record of a merge; the source category no longer exists
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategoryMerge {
+    pub id: Uuid,
+    pub source_category_id: Uuid,
+    pub source_category_name: String,
+    pub destination_category_id: Option<Uuid>,
+    pub destination_category_name: String,
+    pub counts: sqlx::types::Json<CategoryMergeCounts>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryMergeResponse {
+    pub id: Uuid,
+    pub source_category_id: Uuid,
+    pub source_category_name: String,
+    /// `None` once the destination has been deleted as well
+    pub destination_category_id: Option<Uuid>,
+    pub destination_category_name: String,
+    pub counts: CategoryMergeCounts,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&CategoryMerge> for CategoryMergeResponse {
+    fn from(merge: &CategoryMerge) -> Self {
+        Self {
+            id: merge.id,
+            source_category_id: merge.source_category_id,
+            source_category_name: merge.source_category_name.clone(),
+            destination_category_id: merge.destination_category_id,
+            destination_category_name: merge.destination_category_name.clone(),
+            counts: merge.counts.0.clone(),
+            created_at: merge.created_at,
+        }
+    }
+}
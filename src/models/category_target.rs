@@ -1,7 +1,9 @@
+use crate::models::settings::BudgetingMode;
+use chrono::{DateTime, NaiveDate, Utc};
 use rocket::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 use uuid::Uuid;
-use validator::Validate;
+use validator::{Validate, ValidationError};
 
 /// A single row in the category targets view
 #[derive(Serialize, Debug, JsonSchema)]
@@ -13,13 +15,35 @@ pub struct CategoryTargetRow {
     pub category_icon: String,
     pub category_color: String,
     pub is_archived: bool,
+    /// Whether the category has subcategories; categories can nest at any depth
     pub is_parent: bool,
+    pub parent_category_id: Option<Uuid>,
     pub parent_category_name: Option<String>,
+    /// Target in effect for the period: the period override, otherwise the default at the period start
     pub current_target: Option<i32>,
+    /// Default target applied to new periods; `None` for income-based targets
+    pub default_target: Option<i32>,
+    /// Share of income (10000 = 100%) the default target is defined as
+    pub target_income_basis_points: Option<i32>,
+    pub target_income_source: Option<TargetIncomeSource>,
+    /// Length in months of a long-horizon target; `default_target` is then the amount for the whole horizon
+    pub target_horizon_months: Option<i32>,
+    /// Month (1-12) a long-horizon target falls due
+    pub target_due_month: Option<i32>,
+    /// Accrued vs. spent so far in the current horizon cycle, for long-horizon targets
+    pub accrual: Option<TargetAccrual>,
+    /// Whether `current_target` comes from an override for this period
+    pub is_overridden: bool,
     pub previous_target: Option<i32>,
     pub is_excluded: bool,
     pub exclusion_reason: Option<String>,
     pub projected_variance_basis_points: Option<i32>,
+    pub rollover_enabled: bool,
+    /// Net unspent (positive) or overspent (negative) budget carried in from earlier periods
+    pub rolled_over_amount: i64,
+    pub spent_amount: i64,
+    /// `current_target + rolled_over_amount - spent_amount`; `None` without a target
+    pub available_amount: Option<i64>,
 }
 
 /// Full response for the category targets page
@@ -33,17 +57,100 @@ pub struct CategoryTargetsResponse {
     pub total_targeted: i64,
     pub total_categories: i32,
     pub targeted_categories: i32,
+    /// From the user's settings
+    pub budgeting_mode: BudgetingMode,
+    /// Income received in the period from Incoming categories
+    pub period_income: i64,
+    /// Sum of targets assigned to Outgoing categories
+    pub total_assigned: i64,
+    /// `period_income - total_assigned`; only set in zero-based mode
+    pub ready_to_assign: Option<i64>,
+    /// True in zero-based mode when more has been assigned than the period's income
+    pub is_over_assigned: bool,
     pub outgoing_targets: Vec<CategoryTargetRow>,
     pub incoming_targets: Vec<CategoryTargetRow>,
     pub excluded_categories: Vec<CategoryTargetRow>,
 }
 
+/// Income a percentage-of-income target is measured against
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TargetIncomeSource {
+    /// Income received in the period so far
+    Actual,
+    /// Sum of the targets of the Incoming categories
+    Expected,
+}
+
+impl std::fmt::Display for TargetIncomeSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetIncomeSource::Actual => write!(f, "actual"),
+            TargetIncomeSource::Expected => write!(f, "expected"),
+        }
+    }
+}
+
+/// Progress of a long-horizon target through the cycle that contains the end of a period
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema, sqlx::FromRow)]
+pub struct TargetAccrual {
+    /// Amount due once per cycle
+    pub horizon_amount: i32,
+    pub horizon_months: i32,
+    pub due_month: i32,
+    pub cycle_start: NaiveDate,
+    pub cycle_end: NaiveDate,
+    /// Part of `horizon_amount` accrued from the cycle start through the period end
+    pub accrued_amount: i64,
+    /// Spent in the category from the cycle start through the period end
+    pub spent_amount: i64,
+}
+
+/// Horizon lengths (in months) that divide a year evenly, so cycles line up with the due month every year
+pub const TARGET_HORIZON_MONTHS: [i32; 5] = [2, 3, 4, 6, 12];
+
 /// A single target entry in a batch upsert request
 #[derive(Deserialize, Debug, Validate, JsonSchema)]
+#[validate(schema(function = "validate_target_entry"))]
 pub struct TargetEntry {
     pub category_id: Uuid,
+    /// Fixed target; ignored when `income_basis_points` is set
+    #[serde(default)]
     #[validate(range(min = 0))]
     pub budgeted_value: i32,
+    /// Define the target as a share of the period's income instead (10000 = 100%)
+    #[validate(range(min = 0, max = 10000))]
+    pub income_basis_points: Option<i32>,
+    /// Income the share is taken from; defaults to `actual`
+    pub income_source: Option<TargetIncomeSource>,
+    /// Spread `budgeted_value` over this many months (2, 3, 4, 6 or 12) instead of applying it to every period
+    pub horizon_months: Option<i32>,
+    /// Month (1-12) the long-horizon amount falls due; required with `horizon_months`
+    #[validate(range(min = 1, max = 12))]
+    pub due_month: Option<i32>,
+}
+
+fn validate_target_entry(entry: &TargetEntry) -> Result<(), ValidationError> {
+    if entry.horizon_months.is_some() != entry.due_month.is_some() {
+        return Err(ValidationError::new("horizon_months_and_due_month_must_be_set_together"));
+    }
+    if let Some(horizon_months) = entry.horizon_months
+        && !TARGET_HORIZON_MONTHS.contains(&horizon_months)
+    {
+        return Err(ValidationError::new("horizon_months_must_divide_a_year"));
+    }
+    if entry.horizon_months.is_some() && entry.income_basis_points.is_some() {
+        return Err(ValidationError::new("target_cannot_be_both_income_based_and_long_horizon"));
+    }
+    Ok(())
+}
+
+impl TargetEntry {
+    /// The income source to store, set only for percentage-of-income targets.
+    pub fn effective_income_source(&self) -> Option<TargetIncomeSource> {
+        self.income_basis_points.map(|_| self.income_source.unwrap_or(TargetIncomeSource::Actual))
+    }
 }
 
 /// Batch upsert request for saving targets
@@ -52,3 +159,185 @@ pub struct BatchUpsertTargetsRequest {
     #[validate(nested)]
     pub targets: Vec<TargetEntry>,
 }
+
+/// Override a category's target for a single period
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct TargetOverrideRequest {
+    pub period_id: Uuid,
+    #[validate(range(min = 0))]
+    pub budgeted_value: i32,
+}
+
+/// A version of a category's default target
+#[derive(Serialize, Debug, JsonSchema, sqlx::FromRow)]
+pub struct TargetDefaultVersion {
+    pub budgeted_value: i32,
+    pub target_income_basis_points: Option<i32>,
+    pub target_income_source: Option<TargetIncomeSource>,
+    pub target_horizon_months: Option<i32>,
+    pub target_due_month: Option<i32>,
+    /// First period start the value applies to; `None` when it has always applied
+    pub effective_from: Option<NaiveDate>,
+}
+
+/// A per-period override of a category's target
+#[derive(Serialize, Debug, JsonSchema, sqlx::FromRow)]
+pub struct TargetOverrideEntry {
+    pub period_id: Uuid,
+    pub period_name: String,
+    pub period_start_date: NaiveDate,
+    pub budgeted_value: i32,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryTargetHistoryResponse {
+    pub category_id: Uuid,
+    pub default_target: i32,
+    /// Default target versions, newest first
+    pub defaults: Vec<TargetDefaultVersion>,
+    /// Period overrides, newest period first
+    pub overrides: Vec<TargetOverrideEntry>,
+}
+
+/// Move part of one category's target to another within a period
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+#[validate(schema(function = "validate_target_move"))]
+pub struct MoveTargetRequest {
+    pub period_id: Uuid,
+    pub from_category_id: Uuid,
+    pub to_category_id: Uuid,
+    #[validate(range(min = 1))]
+    pub amount: i32,
+    pub note: Option<String>,
+}
+
+fn validate_target_move(request: &MoveTargetRequest) -> Result<(), ValidationError> {
+    if request.from_category_id == request.to_category_id {
+        return Err(ValidationError::new("from_category_must_differ_from_to_category"));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategoryTargetMove {
+    pub id: Uuid,
+    pub period_id: Uuid,
+    pub from_category_id: Uuid,
+    pub to_category_id: Uuid,
+    pub amount: i32,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryTargetMoveResponse {
+    pub id: Uuid,
+    pub period_id: Uuid,
+    pub from_category_id: Uuid,
+    pub to_category_id: Uuid,
+    pub amount: i32,
+    pub note: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&CategoryTargetMove> for CategoryTargetMoveResponse {
+    fn from(target_move: &CategoryTargetMove) -> Self {
+        Self {
+            id: target_move.id,
+            period_id: target_move.period_id,
+            from_category_id: target_move.from_category_id,
+            to_category_id: target_move.to_category_id,
+            amount: target_move.amount,
+            note: target_move.note.clone(),
+            created_at: target_move.created_at,
+        }
+    }
+}
+
+// ===== Target Prefill =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetPrefillSource {
+    /// Reuse the targets that applied to the period right before
+    PreviousPeriod,
+    /// Average actual amounts over the last `periods` closed periods
+    AverageActuals,
+}
+
+/// Fill a period's targets from an earlier period or from recent actuals
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct TargetPrefillRequest {
+    pub period_id: Uuid,
+    pub source: TargetPrefillSource,
+    /// Number of closed periods to average for `average_actuals` (defaults to 3)
+    #[validate(range(min = 1, max = 24))]
+    pub periods: Option<i32>,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema, sqlx::FromRow)]
+pub struct TargetPreviewRow {
+    pub category_id: Uuid,
+    pub category_name: String,
+    /// Target currently in effect for the period
+    pub current_target: Option<i32>,
+    pub proposed_target: i32,
+    /// Whether the category will be excluded from target tracking
+    pub is_excluded: bool,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct TargetPreviewResponse {
+    pub period_id: Uuid,
+    pub rows: Vec<TargetPreviewRow>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(income_basis_points: Option<i32>, income_source: Option<TargetIncomeSource>) -> TargetEntry {
+        TargetEntry {
+            category_id: Uuid::new_v4(),
+            budgeted_value: 0,
+            income_basis_points,
+            income_source,
+            horizon_months: None,
+            due_month: None,
+        }
+    }
+
+    #[test]
+    fn income_source_is_only_stored_for_income_based_targets() {
+        assert_eq!(entry(None, Some(TargetIncomeSource::Expected)).effective_income_source(), None);
+        assert_eq!(entry(Some(3000), None).effective_income_source(), Some(TargetIncomeSource::Actual));
+        assert_eq!(
+            entry(Some(2000), Some(TargetIncomeSource::Expected)).effective_income_source(),
+            Some(TargetIncomeSource::Expected)
+        );
+    }
+
+    #[test]
+    fn income_share_cannot_exceed_full_income() {
+        assert!(entry(Some(10_000), None).validate().is_ok());
+        assert!(entry(Some(10_001), None).validate().is_err());
+    }
+
+    #[test]
+    fn long_horizon_needs_a_due_month_and_a_whole_fraction_of_a_year() {
+        let mut yearly = entry(None, None);
+        yearly.horizon_months = Some(12);
+        assert!(yearly.validate().is_err());
+
+        yearly.due_month = Some(3);
+        assert!(yearly.validate().is_ok());
+
+        yearly.horizon_months = Some(5);
+        assert!(yearly.validate().is_err());
+
+        let mut income_based = entry(Some(2000), None);
+        income_based.horizon_months = Some(12);
+        income_based.due_month = Some(3);
+        assert!(income_based.validate().is_err());
+    }
+}
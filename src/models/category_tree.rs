@@ -0,0 +1,297 @@
+use crate::models::category::{Category, CategoryResponse};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// A category with its own figures for a period, before rolling up.
+#[derive(Debug, Clone)]
+pub struct CategoryTreeEntry {
+    pub category: Category,
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+    pub target: Option<i32>,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct CategoryTreeNode {
+    #[serde(flatten)]
+    pub category: CategoryResponse,
+    /// 0 for top-level categories
+    pub depth: i32,
+    /// Spent in this category itself, excluding subcategories
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+    /// Target of this category itself
+    pub target: Option<i32>,
+    /// Spent in this category and all its subcategories
+    pub rolled_up_spent_amount: i64,
+    pub rolled_up_transaction_count: i64,
+    /// The category's own target, or the rolled-up targets of its children when it has none
+    pub rolled_up_target: Option<i64>,
+    /// Rolled-up targets of the direct children; a parent target has to cover this
+    pub children_target_total: Option<i64>,
+    /// `rolled_up_target - rolled_up_spent_amount`; `None` without a target
+    pub available_amount: Option<i64>,
+    pub children: Vec<CategoryTreeNode>,
+}
+
+impl CategoryTreeNode {
+    /// Whether the category's own target is smaller than the targets of its children combined.
+    pub fn is_over_allocated(&self) -> bool {
+        self.over_allocation().is_some()
+    }
+
+    /// By how much the children's targets exceed the category's own target.
+    pub fn over_allocation(&self) -> Option<i64> {
+        match (self.target, self.children_target_total) {
+            (Some(target), Some(children)) if children > i64::from(target) => Some(children - i64::from(target)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryTreeResponse {
+    pub period_id: Uuid,
+    /// Top-level categories, each with its subcategories nested
+    pub categories: Vec<CategoryTreeNode>,
+}
+
+/// A parent whose target does not cover its subcategories' targets
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct OverAllocatedCategoryResponse {
+    pub category_id: Uuid,
+    pub name: String,
+    pub target: i32,
+    pub children_target_total: i64,
+}
+
+impl From<&CategoryTreeNode> for OverAllocatedCategoryResponse {
+    fn from(node: &CategoryTreeNode) -> Self {
+        Self {
+            category_id: node.category.id,
+            name: node.category.name.clone(),
+            target: node.target.unwrap_or(0),
+            children_target_total: node.children_target_total.unwrap_or(0),
+        }
+    }
+}
+
+/// Move a category, with all its subcategories, under a new parent
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct MoveCategoryRequest {
+    /// `None` makes the category top-level
+    pub parent_id: Option<Uuid>,
+}
+
+/// Nest categories under their parents and roll figures up. Categories whose parent is missing become roots;
+/// children are sorted by name.
+pub fn build_category_tree(entries: Vec<CategoryTreeEntry>) -> Vec<CategoryTreeNode> {
+    let ids: HashSet<Uuid> = entries.iter().map(|entry| entry.category.id).collect();
+    let mut children_of: HashMap<Option<Uuid>, Vec<CategoryTreeEntry>> = HashMap::new();
+    for entry in entries {
+        let parent_id = entry.category.parent_id.filter(|parent_id| ids.contains(parent_id));
+        children_of.entry(parent_id).or_default().push(entry);
+    }
+
+    let roots = children_of.remove(&None).unwrap_or_default();
+    nest(roots, 0, &mut children_of)
+}
+
+fn nest(mut entries: Vec<CategoryTreeEntry>, depth: i32, children_of: &mut HashMap<Option<Uuid>, Vec<CategoryTreeEntry>>) -> Vec<CategoryTreeNode> {
+    entries.sort_by(|a, b| a.category.name.cmp(&b.category.name));
+    entries
+        .into_iter()
+        .map(|entry| {
+            // Removing the children as they are placed means a cycle in the data cannot recurse forever
+            let children = nest(children_of.remove(&Some(entry.category.id)).unwrap_or_default(), depth + 1, children_of);
+
+            let children_target_total = children
+                .iter()
+                .filter_map(|child| child.rolled_up_target)
+                .reduce(|total, target| total + target);
+            let rolled_up_spent_amount = entry.spent_amount + children.iter().map(|child| child.rolled_up_spent_amount).sum::<i64>();
+            let rolled_up_target = entry.target.map(i64::from).or(children_target_total);
+
+            CategoryTreeNode {
+                category: CategoryResponse::from(&entry.category),
+                depth,
+                spent_amount: entry.spent_amount,
+                transaction_count: entry.transaction_count,
+                target: entry.target,
+                rolled_up_spent_amount,
+                rolled_up_transaction_count: entry.transaction_count + children.iter().map(|child| child.rolled_up_transaction_count).sum::<i64>(),
+                rolled_up_target,
+                children_target_total,
+                available_amount: rolled_up_target.map(|target| target - rolled_up_spent_amount),
+                children,
+            }
+        })
+        .collect()
+}
+
+/// Every node of the tree whose own target does not cover its children's targets.
+pub fn over_allocated_categories(nodes: &[CategoryTreeNode]) -> Vec<&CategoryTreeNode> {
+    let mut found = Vec::new();
+    for node in nodes {
+        if node.is_over_allocated() {
+            found.push(node);
+        }
+        found.extend(over_allocated_categories(&node.children));
+    }
+    found
+}
+
+/// Over-allocation of every over-allocated category, by id.
+pub fn over_allocations(nodes: &[CategoryTreeNode]) -> HashMap<Uuid, i64> {
+    over_allocated_categories(nodes)
+        .into_iter()
+        .filter_map(|node| node.over_allocation().map(|excess| (node.category.id, excess)))
+        .collect()
+}
+
+/// The first category with one of `touched` in its subtree (itself included) that is more over-allocated than
+/// it was `before` a write. Over-allocation elsewhere, or that the write did not make worse, is left alone.
+pub fn newly_over_allocated<'a>(nodes: &'a [CategoryTreeNode], touched: &HashSet<Uuid>, before: &HashMap<Uuid, i64>) -> Option<&'a CategoryTreeNode> {
+    fn visit<'a>(node: &'a CategoryTreeNode, touched: &HashSet<Uuid>, before: &HashMap<Uuid, i64>, found: &mut Option<&'a CategoryTreeNode>) -> bool {
+        let mut in_scope = touched.contains(&node.category.id);
+        for child in &node.children {
+            in_scope |= visit(child, touched, before, found);
+        }
+        if in_scope
+            && found.is_none()
+            && node
+                .over_allocation()
+                .is_some_and(|excess| excess > before.get(&node.category.id).copied().unwrap_or(0))
+        {
+            *found = Some(node);
+        }
+        in_scope
+    }
+
+    let mut found = None;
+    for node in nodes {
+        visit(node, touched, before, &mut found);
+    }
+    found
+}
+
+/// Whether putting `category_id` under `new_parent_id` would make the category its own ancestor.
+/// `parents` maps every category of the user to its current parent.
+pub fn creates_category_cycle(category_id: Uuid, new_parent_id: Uuid, parents: &HashMap<Uuid, Option<Uuid>>) -> bool {
+    let mut visited = HashSet::new();
+    let mut current = Some(new_parent_id);
+    while let Some(id) = current {
+        if id == category_id {
+            return true;
+        }
+        if !visited.insert(id) {
+            // An existing cycle that does not involve the category
+            return false;
+        }
+        current = parents.get(&id).copied().flatten();
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, parent: Option<&CategoryTreeEntry>, spent_amount: i64, target: Option<i32>) -> CategoryTreeEntry {
+        CategoryTreeEntry {
+            category: Category {
+                id: Uuid::new_v4(),
+                name: name.to_string(),
+                parent_id: parent.map(|parent| parent.category.id),
+                ..Category::default()
+            },
+            spent_amount,
+            transaction_count: i64::from(spent_amount > 0),
+            target,
+        }
+    }
+
+    #[test]
+    fn tree_rolls_spend_and_targets_up_every_level() {
+        let home = entry("Home", None, 0, Some(1_000));
+        let utilities = entry("Utilities", Some(&home), 50, None);
+        let power = entry("Power", Some(&utilities), 300, Some(400));
+        let water = entry("Water", Some(&utilities), 100, Some(200));
+        let rent = entry("Rent", Some(&home), 500, Some(500));
+        let leisure = entry("Leisure", None, 70, None);
+
+        let tree = build_category_tree(vec![water, leisure, power, rent, utilities, home]);
+
+        assert_eq!(tree.iter().map(|node| node.category.name.as_str()).collect::<Vec<_>>(), vec!["Home", "Leisure"]);
+        let home = &tree[0];
+        assert_eq!(home.rolled_up_spent_amount, 950);
+        assert_eq!(home.rolled_up_transaction_count, 4);
+        assert_eq!(home.children_target_total, Some(1_100));
+        assert_eq!(home.rolled_up_target, Some(1_000));
+        assert_eq!(home.available_amount, Some(50));
+        assert!(home.is_over_allocated());
+
+        let utilities = &home.children[1];
+        assert_eq!(utilities.category.name, "Utilities");
+        assert_eq!(utilities.depth, 1);
+        assert_eq!(utilities.target, None);
+        assert_eq!(utilities.rolled_up_target, Some(600));
+        assert_eq!(utilities.children[0].depth, 2);
+
+        let leisure = &tree[1];
+        assert_eq!(leisure.rolled_up_target, None);
+        assert_eq!(leisure.available_amount, None);
+
+        let over: Vec<_> = over_allocated_categories(&tree).iter().map(|node| node.category.name.clone()).collect();
+        assert_eq!(over, vec!["Home".to_string()]);
+    }
+
+    #[test]
+    fn only_touched_subtrees_that_got_worse_are_over_allocated() {
+        let home = entry("Home", None, 0, Some(1_000));
+        let rent = entry("Rent", Some(&home), 0, Some(900));
+        let power = entry("Power", Some(&home), 0, Some(200));
+        let travel = entry("Travel", None, 0, Some(100));
+        let flights = entry("Flights", Some(&travel), 0, Some(300));
+        let (home_id, rent_id, power_id, flights_id) = (home.category.id, rent.category.id, power.category.id, flights.category.id);
+
+        let before = over_allocations(&build_category_tree(vec![
+            home.clone(),
+            rent.clone(),
+            power.clone(),
+            travel.clone(),
+            flights.clone(),
+        ]));
+        assert_eq!(before, HashMap::from([(home_id, 100), (travel.category.id, 200)]));
+
+        // Lowering Rent still leaves Home over-allocated, but less than before
+        let lowered = CategoryTreeEntry {
+            target: Some(850),
+            ..rent.clone()
+        };
+        let tree = build_category_tree(vec![home.clone(), lowered, power.clone(), travel.clone(), flights.clone()]);
+        assert!(newly_over_allocated(&tree, &HashSet::from([rent_id]), &before).is_none());
+
+        // Raising Power makes Home worse; Travel is not touched and is ignored
+        let raised = CategoryTreeEntry { target: Some(250), ..power };
+        let tree = build_category_tree(vec![home, rent, raised, travel, flights]);
+        assert_eq!(
+            newly_over_allocated(&tree, &HashSet::from([power_id]), &before).map(|node| node.category.id),
+            Some(home_id)
+        );
+        assert!(newly_over_allocated(&tree, &HashSet::from([flights_id]), &before).is_none());
+    }
+
+    #[test]
+    fn moving_under_a_descendant_is_a_cycle() {
+        let (home, utilities, power, leisure) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let parents = HashMap::from([(home, None), (utilities, Some(home)), (power, Some(utilities)), (leisure, None)]);
+
+        assert!(creates_category_cycle(home, power, &parents));
+        assert!(creates_category_cycle(home, home, &parents));
+        assert!(!creates_category_cycle(power, leisure, &parents));
+        assert!(!creates_category_cycle(utilities, home, &parents));
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::NaiveDate;
+use rocket::serde::Serialize;
+use schemars::JsonSchema;
+use uuid::Uuid;
+
+/// Number of closed periods whose intra-period spending curves feed the forecast.
+pub const FORECAST_HISTORY_PERIODS: i64 = 6;
+
+/// A vendor counts as a recurring item of a category when it was paid in each of this many recent closed periods.
+pub const RECURRING_ITEM_PERIODS: i64 = 3;
+
+// ===== Spend Forecast Models =====
+
+/// Where a category stands in the forecast period
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CategorySpendSnapshot {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub target: Option<i32>,
+    pub spent_to_date: i64,
+    /// Expected amount of recurring items that have not been paid yet this period
+    pub recurring_pending: i64,
+}
+
+/// Spending of a category in one closed period, split at the same relative point as today in the forecast period
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct HistoricalCategorySpend {
+    pub category_id: Uuid,
+    pub spent_by_point: i64,
+    pub spent_total: i64,
+}
+
+impl HistoricalCategorySpend {
+    /// What was still spent after the comparison point.
+    pub fn remaining(&self) -> i64 {
+        self.spent_total - self.spent_by_point
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendProjection {
+    pub projected: i64,
+    pub low: i64,
+    pub high: i64,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct CategoryForecastResponse {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub target: Option<i32>,
+    pub spent_to_date: i64,
+    /// Expected amount of recurring items not yet paid this period
+    pub recurring_pending: i64,
+    /// Projected end-of-period actual
+    pub projected_spend: i64,
+    /// Lower bound of the confidence band for `projected_spend`
+    pub projected_spend_low: i64,
+    /// Upper bound of the confidence band for `projected_spend`
+    pub projected_spend_high: i64,
+    /// `projected_spend - target`; positive means projected overspending
+    pub projected_variance: Option<i64>,
+    pub projected_variance_low: Option<i64>,
+    pub projected_variance_high: Option<i64>,
+}
+
+impl CategoryForecastResponse {
+    pub fn new(snapshot: &CategorySpendSnapshot, projection: SpendProjection) -> Self {
+        let variance = |amount: i64| snapshot.target.map(|target| amount - i64::from(target));
+
+        Self {
+            category_id: snapshot.category_id,
+            category_name: snapshot.category_name.clone(),
+            target: snapshot.target,
+            spent_to_date: snapshot.spent_to_date,
+            recurring_pending: snapshot.recurring_pending,
+            projected_spend: projection.projected,
+            projected_spend_low: projection.low,
+            projected_spend_high: projection.high,
+            projected_variance: variance(projection.projected),
+            projected_variance_low: variance(projection.low),
+            projected_variance_high: variance(projection.high),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct SpendForecastTotalResponse {
+    pub target: i64,
+    pub spent_to_date: i64,
+    pub recurring_pending: i64,
+    pub projected_spend: i64,
+    pub projected_spend_low: i64,
+    pub projected_spend_high: i64,
+    /// `projected_spend - target`; positive means projected overspending
+    pub projected_variance: i64,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct SpendForecastResponse {
+    pub period_id: Uuid,
+    pub as_of: NaiveDate,
+    /// Share of the period elapsed in basis points (10000 = 100%)
+    pub elapsed_basis_points: i64,
+    /// Number of closed periods the spending curves were taken from
+    pub history_periods: i64,
+    pub total: SpendForecastTotalResponse,
+    /// Outgoing categories, largest projected spend first
+    pub categories: Vec<CategoryForecastResponse>,
+}
+
+impl SpendForecastResponse {
+    pub fn new(period_id: Uuid, as_of: NaiveDate, elapsed_basis_points: i64, history_periods: i64, categories: Vec<CategoryForecastResponse>) -> Self {
+        let total = SpendForecastTotalResponse {
+            target: categories.iter().filter_map(|row| row.target).map(i64::from).sum(),
+            spent_to_date: categories.iter().map(|row| row.spent_to_date).sum(),
+            recurring_pending: categories.iter().map(|row| row.recurring_pending).sum(),
+            projected_spend: categories.iter().map(|row| row.projected_spend).sum(),
+            projected_spend_low: categories.iter().map(|row| row.projected_spend_low).sum(),
+            projected_spend_high: categories.iter().map(|row| row.projected_spend_high).sum(),
+            projected_variance: categories.iter().filter_map(|row| row.projected_variance).sum(),
+        };
+
+        Self {
+            period_id,
+            as_of,
+            elapsed_basis_points,
+            history_periods,
+            total,
+            categories,
+        }
+    }
+}
+
+/// Share of a period that has elapsed on `today`, in basis points. Today counts as elapsed.
+pub fn elapsed_basis_points(start_date: NaiveDate, end_date: NaiveDate, today: NaiveDate) -> i64 {
+    let period_days = (end_date - start_date).num_days() + 1;
+    if period_days <= 0 {
+        return 10_000;
+    }
+    let elapsed_days = ((today - start_date).num_days() + 1).clamp(0, period_days);
+    elapsed_days * 10_000 / period_days
+}
+
+/// Project where a category's spending lands at the end of the period.
+///
+/// The remaining spend is a blend of the current burn rate and what was still spent after the same point in
+/// closed periods (`history_remaining`), with each closed period weighing as much as the burn rate.
+/// Recurring items that are still expected set a floor. The band is one standard deviation of the
+/// historical remainders, or half the projected remainder without enough history.
+pub fn project_spend(spent_to_date: i64, elapsed_basis_points: i64, history_remaining: &[i64], recurring_pending: i64) -> SpendProjection {
+    if elapsed_basis_points >= 10_000 {
+        return SpendProjection {
+            projected: spent_to_date,
+            low: spent_to_date,
+            high: spent_to_date,
+        };
+    }
+
+    let burn_remaining = (elapsed_basis_points > 0).then(|| spent_to_date.max(0).saturating_mul(10_000 - elapsed_basis_points) / elapsed_basis_points);
+    let history_count = history_remaining.len() as i64;
+    let history_mean = (history_count > 0).then(|| history_remaining.iter().sum::<i64>() / history_count);
+
+    let remaining = match (burn_remaining, history_mean) {
+        (Some(burn), Some(history)) => (burn + history * history_count) / (history_count + 1),
+        (Some(burn), None) => burn,
+        (None, Some(history)) => history,
+        (None, None) => 0,
+    }
+    .max(recurring_pending)
+    .max(0);
+
+    let spread = match history_mean {
+        Some(mean) if history_count >= 2 => {
+            let variance = history_remaining.iter().map(|value| ((value - mean) as f64).powi(2)).sum::<f64>() / history_count as f64;
+            variance.sqrt().round() as i64
+        }
+        _ => remaining / 2,
+    };
+
+    let projected = spent_to_date + remaining;
+    SpendProjection {
+        projected,
+        low: (projected - spread).max(spent_to_date + recurring_pending),
+        high: projected + spread,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn elapsed_share_counts_today_and_is_clamped() {
+        assert_eq!(elapsed_basis_points(date(2026, 4, 1), date(2026, 4, 30), date(2026, 4, 15)), 5_000);
+        assert_eq!(elapsed_basis_points(date(2026, 4, 1), date(2026, 4, 30), date(2026, 3, 20)), 0);
+        assert_eq!(elapsed_basis_points(date(2026, 4, 1), date(2026, 4, 30), date(2026, 5, 3)), 10_000);
+    }
+
+    #[test]
+    fn projection_without_history_follows_burn_rate() {
+        let projection = project_spend(10_000, 5_000, &[], 0);
+        assert_eq!(projection.projected, 20_000);
+        assert_eq!(projection.low, 15_000);
+        assert_eq!(projection.high, 25_000);
+    }
+
+    #[test]
+    fn projection_blends_burn_rate_with_historical_curve() {
+        // Burn rate says 10_000 more; three closed periods each spent 4_000 after this point
+        let projection = project_spend(10_000, 5_000, &[4_000, 4_000, 4_000], 0);
+        assert_eq!(projection.projected, 15_500);
+        assert_eq!(projection.low, 15_500);
+        assert_eq!(projection.high, 15_500);
+    }
+
+    #[test]
+    fn pending_recurring_items_are_a_floor() {
+        let projection = project_spend(1_000, 5_000, &[], 8_000);
+        assert_eq!(projection.projected, 9_000);
+        assert_eq!(projection.low, 9_000);
+    }
+
+    #[test]
+    fn finished_period_projects_its_actuals() {
+        let projection = project_spend(12_345, 10_000, &[5_000, 9_000], 3_000);
+        assert_eq!(
+            projection,
+            SpendProjection {
+                projected: 12_345,
+                low: 12_345,
+                high: 12_345,
+            }
+        );
+    }
+}
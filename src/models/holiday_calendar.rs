@@ -0,0 +1,472 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use std::sync::LazyLock;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Uploaded calendars may hold at most this many holidays.
+pub const MAX_HOLIDAY_CALENDAR_RULES: usize = 1000;
+
+/// A multi-day iCal event contributes at most this many days.
+const MAX_ICAL_EVENT_DAYS: u64 = 31;
+
+/// How many days a business day is looked for; a calendar may hold a holiday on every day of the year.
+const MAX_BUSINESS_DAY_SEARCH_DAYS: usize = 366;
+
+// ===== Enums =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HolidayCalendarFormat {
+    /// An iCalendar file; yearly recurring all-day events repeat every year
+    Ical,
+    /// A JSON array of holiday rules, the same format as the bundled calendars
+    Json,
+}
+
+// ===== Holiday Rules =====
+
+/// How a holiday's date is found in a given year
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum HolidayRule {
+    /// A single date
+    Date { name: String, date: NaiveDate },
+    /// Days relative to Western Easter Sunday, e.g. -2 for Good Friday
+    EasterOffset { name: String, easter_offset: i32 },
+    /// The nth weekday of a month; -1 is the last one
+    NthWeekday { name: String, month: u32, weekday: Weekday, nth: i32 },
+    /// The same day every year
+    Annual { name: String, month: u32, day: u32 },
+}
+
+impl HolidayRule {
+    pub fn name(&self) -> &str {
+        match self {
+            HolidayRule::Date { name, .. }
+            | HolidayRule::EasterOffset { name, .. }
+            | HolidayRule::NthWeekday { name, .. }
+            | HolidayRule::Annual { name, .. } => name,
+        }
+    }
+
+    pub fn date_in_year(&self, year: i32) -> Option<NaiveDate> {
+        match self {
+            HolidayRule::Date { date, .. } => (date.year() == year).then_some(*date),
+            HolidayRule::EasterOffset { easter_offset, .. } => {
+                let easter = easter_sunday(year)?;
+                let days = Days::new(u64::from(easter_offset.unsigned_abs()));
+                let date = if *easter_offset >= 0 {
+                    easter.checked_add_days(days)?
+                } else {
+                    easter.checked_sub_days(days)?
+                };
+                (date.year() == year).then_some(date)
+            }
+            HolidayRule::NthWeekday { month, weekday, nth, .. } => nth_weekday_of_month(year, *month, *weekday, *nth),
+            HolidayRule::Annual { month, day, .. } => NaiveDate::from_ymd_opt(year, *month, *day),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match self {
+            HolidayRule::Date { .. } | HolidayRule::EasterOffset { .. } => true,
+            HolidayRule::NthWeekday { month, nth, .. } => (1..=12).contains(month) && (-5..=5).contains(nth) && *nth != 0,
+            // Checked against a leap year so 29 February is accepted
+            HolidayRule::Annual { month, day, .. } => NaiveDate::from_ymd_opt(2024, *month, *day).is_some(),
+        }
+    }
+}
+
+/// Western (Gregorian) Easter Sunday, using the anonymous Gregorian algorithm.
+fn easter_sunday(year: i32) -> Option<NaiveDate> {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32)
+}
+
+fn nth_weekday_of_month(year: i32, month: u32, weekday: Weekday, nth: i32) -> Option<NaiveDate> {
+    if nth > 0 {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let offset = (7 + weekday.num_days_from_monday() - first.weekday().num_days_from_monday()) % 7;
+        let date = first.checked_add_days(Days::new(u64::from(offset) + 7 * (nth as u64 - 1)))?;
+        (date.month() == month).then_some(date)
+    } else {
+        let last = NaiveDate::from_ymd_opt(year, month, 1)?
+            .checked_add_months(chrono::Months::new(1))?
+            .pred_opt()?;
+        let offset = (7 + last.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+        let date = last.checked_sub_days(Days::new(u64::from(offset) + 7 * (nth.unsigned_abs() as u64 - 1)))?;
+        (date.month() == month).then_some(date)
+    }
+}
+
+// ===== Holiday Calendar =====
+
+/// The holidays a user's dates are checked against. The default calendar has no holidays,
+/// so only weekends are non-business days.
+#[derive(Debug, Clone, Default)]
+pub struct HolidayCalendar {
+    pub rules: Vec<HolidayRule>,
+}
+
+impl HolidayCalendar {
+    pub fn new(rules: Vec<HolidayRule>) -> Self {
+        Self { rules }
+    }
+
+    pub fn holidays_in_year(&self, year: i32) -> Vec<HolidayResponse> {
+        let mut holidays: Vec<HolidayResponse> = self
+            .rules
+            .iter()
+            .filter_map(|rule| {
+                rule.date_in_year(year).map(|date| HolidayResponse {
+                    date,
+                    name: rule.name().to_string(),
+                })
+            })
+            .collect();
+        holidays.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+        holidays.dedup_by(|a, b| a.date == b.date);
+        holidays
+    }
+
+    pub fn is_holiday(&self, date: NaiveDate) -> bool {
+        self.rules.iter().any(|rule| rule.date_in_year(date.year()) == Some(date))
+    }
+
+    pub fn is_business_day(&self, date: NaiveDate) -> bool {
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.is_holiday(date)
+    }
+
+    /// `date` itself when it is a business day, otherwise the closest business day before it, if there is one
+    /// within a year.
+    pub fn previous_business_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let mut date = date;
+        for _ in 0..MAX_BUSINESS_DAY_SEARCH_DAYS {
+            if self.is_business_day(date) {
+                return Some(date);
+            }
+            date = date.pred_opt()?;
+        }
+        None
+    }
+
+    /// `date` itself when it is a business day, otherwise the closest business day after it, if there is one
+    /// within a year.
+    pub fn next_business_day(&self, date: NaiveDate) -> Option<NaiveDate> {
+        let mut date = date;
+        for _ in 0..MAX_BUSINESS_DAY_SEARCH_DAYS {
+            if self.is_business_day(date) {
+                return Some(date);
+            }
+            date = date.succ_opt()?;
+        }
+        None
+    }
+}
+
+// ===== Bundled Calendars =====
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BundledHolidayCalendar {
+    pub code: String,
+    pub name: String,
+    pub holidays: Vec<HolidayRule>,
+}
+
+const BUNDLED_HOLIDAY_CALENDAR_FILES: [&str; 5] = [
+    include_str!("../../data/holidays/br.json"),
+    include_str!("../../data/holidays/de.json"),
+    include_str!("../../data/holidays/gb-eng.json"),
+    include_str!("../../data/holidays/pt.json"),
+    include_str!("../../data/holidays/us.json"),
+];
+
+static BUNDLED_HOLIDAY_CALENDARS: LazyLock<Vec<BundledHolidayCalendar>> = LazyLock::new(|| {
+    BUNDLED_HOLIDAY_CALENDAR_FILES
+        .iter()
+        .map(|file| serde_json::from_str(file).expect("bundled holiday calendars are valid"))
+        .collect()
+});
+
+pub fn bundled_holiday_calendars() -> &'static [BundledHolidayCalendar] {
+    &BUNDLED_HOLIDAY_CALENDARS
+}
+
+pub fn bundled_holiday_calendar(code: &str) -> Option<&'static BundledHolidayCalendar> {
+    BUNDLED_HOLIDAY_CALENDARS.iter().find(|calendar| calendar.code.eq_ignore_ascii_case(code))
+}
+
+// ===== Uploaded Calendars =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct UploadedHolidayCalendar {
+    pub id: Uuid,
+    pub name: String,
+    pub rules: sqlx::types::Json<Vec<HolidayRule>>,
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct HolidayCalendarRequest {
+    #[validate(length(min = 1, max = 100))]
+    pub name: String,
+    pub format: HolidayCalendarFormat,
+    #[validate(length(min = 1, max = 262144))]
+    pub data: String,
+}
+
+/// Parse an uploaded calendar into holiday rules.
+pub fn parse_holiday_calendar(format: HolidayCalendarFormat, data: &str) -> Result<Vec<HolidayRule>, String> {
+    let rules = match format {
+        HolidayCalendarFormat::Json => serde_json::from_str::<Vec<HolidayRule>>(data).map_err(|err| format!("Invalid holiday calendar JSON: {err}"))?,
+        HolidayCalendarFormat::Ical => parse_ical_holidays(data)?,
+    };
+
+    if rules.is_empty() {
+        return Err("Holiday calendar contains no holidays".to_string());
+    }
+    if rules.len() > MAX_HOLIDAY_CALENDAR_RULES {
+        return Err(format!("Holiday calendar may contain at most {MAX_HOLIDAY_CALENDAR_RULES} holidays"));
+    }
+    if let Some(rule) = rules.iter().find(|rule| !rule.is_valid()) {
+        return Err(format!("Invalid date for holiday {}", rule.name()));
+    }
+
+    Ok(rules)
+}
+
+/// Read the all-day events of an iCalendar file. Events with a yearly `RRULE` become annual holidays;
+/// multi-day events contribute each of their days.
+fn parse_ical_holidays(data: &str) -> Result<Vec<HolidayRule>, String> {
+    // Lines starting with whitespace continue the previous line
+    let mut lines: Vec<String> = Vec::new();
+    for line in data.lines() {
+        match line.strip_prefix([' ', '\t']) {
+            Some(continuation) if !lines.is_empty() => lines.last_mut().expect("checked non-empty").push_str(continuation),
+            _ => lines.push(line.trim_end_matches('\r').to_string()),
+        }
+    }
+
+    let mut rules = Vec::new();
+    let mut event: Option<IcalEvent> = None;
+
+    for line in &lines {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let property = key.split(';').next().unwrap_or(key).to_ascii_uppercase();
+
+        match (property.as_str(), event.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => event = Some(IcalEvent::default()),
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                let finished = event.take().expect("inside an event");
+                rules.extend(finished.into_rules()?);
+            }
+            ("DTSTART", Some(current)) => current.start = Some(parse_ical_date(value)?),
+            ("DTEND", Some(current)) => current.end = Some(parse_ical_date(value)?),
+            ("SUMMARY", Some(current)) => current.summary = Some(unescape_ical_text(value)),
+            ("RRULE", Some(current)) => current.yearly = value.split(';').any(|part| part.eq_ignore_ascii_case("FREQ=YEARLY")),
+            _ => {}
+        }
+    }
+
+    Ok(rules)
+}
+
+#[derive(Default)]
+struct IcalEvent {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    summary: Option<String>,
+    yearly: bool,
+}
+
+impl IcalEvent {
+    fn into_rules(self) -> Result<Vec<HolidayRule>, String> {
+        let start = self.start.ok_or_else(|| "Calendar event without DTSTART".to_string())?;
+        let name = self.summary.unwrap_or_else(|| "Holiday".to_string());
+
+        if self.yearly {
+            return Ok(vec![HolidayRule::Annual {
+                name,
+                month: start.month(),
+                day: start.day(),
+            }]);
+        }
+
+        // DTEND of an all-day event is exclusive
+        let days = self
+            .end
+            .map(|end| (end - start).num_days().clamp(1, MAX_ICAL_EVENT_DAYS as i64) as u64)
+            .unwrap_or(1);
+        Ok((0..days)
+            .filter_map(|offset| start.checked_add_days(Days::new(offset)))
+            .map(|date| HolidayRule::Date { name: name.clone(), date })
+            .collect())
+    }
+}
+
+/// `YYYYMMDD`, optionally followed by a time which is ignored.
+fn parse_ical_date(value: &str) -> Result<NaiveDate, String> {
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| format!("Invalid calendar date {value}"))
+}
+
+fn unescape_ical_text(value: &str) -> String {
+    value
+        .replace("\\n", " ")
+        .replace("\\N", " ")
+        .replace("\\,", ",")
+        .replace("\\;", ";")
+        .replace("\\\\", "\\")
+}
+
+// ===== Responses =====
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct HolidayResponse {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct HolidayCalendarResponse {
+    /// Country/region code of a bundled calendar, or the id of an uploaded one
+    pub calendar: String,
+    pub name: String,
+    pub is_bundled: bool,
+    pub is_selected: bool,
+}
+
+impl HolidayCalendarResponse {
+    pub fn bundled(calendar: &BundledHolidayCalendar, selection: &HolidayCalendarSelection) -> Self {
+        Self {
+            calendar: calendar.code.clone(),
+            name: calendar.name.clone(),
+            is_bundled: true,
+            is_selected: selection.code.as_deref().is_some_and(|code| code.eq_ignore_ascii_case(&calendar.code)),
+        }
+    }
+
+    pub fn uploaded(calendar: &UploadedHolidayCalendar, selection: &HolidayCalendarSelection) -> Self {
+        Self {
+            calendar: calendar.id.to_string(),
+            name: calendar.name.clone(),
+            is_bundled: false,
+            is_selected: selection.calendar_id == Some(calendar.id),
+        }
+    }
+}
+
+/// The calendar a user picked: a bundled one by code or an uploaded one by id
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct HolidayCalendarSelection {
+    #[sqlx(rename = "holiday_calendar_code")]
+    pub code: Option<String>,
+    #[sqlx(rename = "holiday_calendar_id")]
+    pub calendar_id: Option<Uuid>,
+}
+
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct HolidayCalendarSelectionRequest {
+    /// Code of a bundled calendar or id of an uploaded one; `null` clears the selection
+    pub calendar: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    #[test]
+    fn bundled_calendars_resolve_movable_holidays() {
+        let portugal = HolidayCalendar::new(bundled_holiday_calendar("pt").expect("bundled").holidays.clone());
+        assert!(portugal.is_holiday(date(2026, 4, 3)), "Good Friday");
+        assert!(portugal.is_holiday(date(2026, 6, 4)), "Corpus Christi");
+
+        let us = HolidayCalendar::new(bundled_holiday_calendar("US").expect("bundled").holidays.clone());
+        assert!(us.is_holiday(date(2026, 11, 26)), "Thanksgiving");
+        assert!(us.is_holiday(date(2026, 5, 25)), "Memorial Day");
+        assert_eq!(bundled_holiday_calendars().len(), BUNDLED_HOLIDAY_CALENDAR_FILES.len());
+    }
+
+    #[test]
+    fn business_days_skip_weekends_and_holidays() {
+        let calendar = HolidayCalendar::new(vec![HolidayRule::Annual {
+            name: "New Year".to_string(),
+            month: 1,
+            day: 1,
+        }]);
+
+        // 2027-01-01 is a Friday
+        assert_eq!(calendar.previous_business_day(date(2027, 1, 3)), Some(date(2026, 12, 31)));
+        assert_eq!(calendar.next_business_day(date(2027, 1, 1)), Some(date(2027, 1, 4)));
+        assert_eq!(HolidayCalendar::default().next_business_day(date(2027, 1, 1)), Some(date(2027, 1, 1)));
+    }
+
+    #[test]
+    fn business_day_search_gives_up_when_every_day_is_a_holiday() {
+        let every_day = date(2024, 1, 1)
+            .iter_days()
+            .take_while(|day| day.year() == 2024)
+            .map(|day| HolidayRule::Annual {
+                name: "Holiday".to_string(),
+                month: day.month(),
+                day: day.day(),
+            })
+            .collect();
+        let calendar = HolidayCalendar::new(every_day);
+
+        assert_eq!(calendar.next_business_day(date(2027, 3, 1)), None);
+        assert_eq!(calendar.previous_business_day(date(2027, 3, 1)), None);
+    }
+
+    #[test]
+    fn ical_upload_reads_yearly_and_multi_day_events() {
+        let data = "BEGIN:VCALENDAR\r\nBEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260101\r\nRRULE:FREQ=YEARLY\r\nSUMMARY:New \r\n Year\r\nEND:VEVENT\r\n\
+                    BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20260216\r\nDTEND;VALUE=DATE:20260218\r\nSUMMARY:Carnival\\, day off\r\nEND:VEVENT\r\nEND:VCALENDAR\r\n";
+
+        let rules = parse_holiday_calendar(HolidayCalendarFormat::Ical, data).expect("parsed");
+        let holidays = HolidayCalendar::new(rules).holidays_in_year(2027);
+        assert_eq!(
+            holidays,
+            vec![HolidayResponse {
+                date: date(2027, 1, 1),
+                name: "New Year".to_string()
+            }]
+        );
+
+        let rules = parse_holiday_calendar(HolidayCalendarFormat::Ical, data).expect("parsed");
+        let holidays = HolidayCalendar::new(rules).holidays_in_year(2026);
+        assert_eq!(
+            holidays.iter().map(|holiday| holiday.date).collect::<Vec<_>>(),
+            vec![date(2026, 1, 1), date(2026, 2, 16), date(2026, 2, 17)]
+        );
+        assert_eq!(holidays[1].name, "Carnival, day off");
+    }
+
+    #[test]
+    fn json_upload_rejects_impossible_dates() {
+        let data = r#"[{ "name": "Nope", "month": 2, "day": 30 }]"#;
+        assert!(parse_holiday_calendar(HolidayCalendarFormat::Json, data).is_err());
+    }
+}
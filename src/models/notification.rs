@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+
+// ===== Enums =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    SpendLimit,
+    CategoryBudget,
+    OverlayCap,
+}
+
+impl std::fmt::Display for NotificationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NotificationKind::SpendLimit => write!(f, "spend_limit"),
+            NotificationKind::CategoryBudget => write!(f, "category_budget"),
+            NotificationKind::OverlayCap => write!(f, "overlay_cap"),
+        }
+    }
+}
+
+// ===== Notification Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Notification {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub message: String,
+    pub metadata: sqlx::types::Json<serde_json::Value>,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A notification about to be stored. `dedupe_key` is unique per user, so raising the
+/// same alert twice is a no-op.
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    pub kind: NotificationKind,
+    pub dedupe_key: String,
+    pub title: String,
+    pub message: String,
+    pub metadata: serde_json::Value,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct NotificationResponse {
+    pub id: Uuid,
+    pub kind: NotificationKind,
+    pub title: String,
+    pub message: String,
+    pub metadata: serde_json::Value,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&Notification> for NotificationResponse {
+    fn from(notification: &Notification) -> Self {
+        Self {
+            id: notification.id,
+            kind: notification.kind,
+            title: notification.title.clone(),
+            message: notification.message.clone(),
+            metadata: notification.metadata.0.clone(),
+            is_read: notification.is_read,
+            created_at: notification.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct UnreadNotificationCountResponse {
+    pub unread_count: i64,
+}
@@ -1,7 +1,10 @@
+use crate::models::category::CategoryType;
+use crate::models::settings::validate_alert_thresholds;
 use crate::models::transaction::TransactionResponse;
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
 use rocket::serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
+use std::collections::HashMap;
 use uuid::Uuid;
 use validator::Validate;
 
@@ -26,7 +29,8 @@ impl std::fmt::Display for InclusionMode {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum InclusionSource {
     Manual,
@@ -34,16 +38,194 @@ pub enum InclusionSource {
     All,
 }
 
+impl std::fmt::Display for InclusionSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InclusionSource::Manual => write!(f, "manual"),
+            InclusionSource::Rules => write!(f, "rules"),
+            InclusionSource::All => write!(f, "all"),
+        }
+    }
+}
+
 // ===== Rules Models =====
 
-#[derive(Serialize, Deserialize, Debug, Clone, Default, JsonSchema)]
-pub struct OverlayRules {
-    #[serde(default)]
-    pub category_ids: Vec<Uuid>,
-    #[serde(default)]
-    pub vendor_ids: Vec<Uuid>,
-    #[serde(default)]
-    pub account_ids: Vec<Uuid>,
+/// Rule trees deeper than this are rejected.
+pub const MAX_OVERLAY_RULE_DEPTH: usize = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayWeekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<Weekday> for OverlayWeekday {
+    fn from(weekday: Weekday) -> Self {
+        match weekday {
+            Weekday::Mon => OverlayWeekday::Monday,
+            Weekday::Tue => OverlayWeekday::Tuesday,
+            Weekday::Wed => OverlayWeekday::Wednesday,
+            Weekday::Thu => OverlayWeekday::Thursday,
+            Weekday::Fri => OverlayWeekday::Friday,
+            Weekday::Sat => OverlayWeekday::Saturday,
+            Weekday::Sun => OverlayWeekday::Sunday,
+        }
+    }
+}
+
+/// Boolean expression deciding which transactions a `rules` overlay includes, stored as the overlay's `rules` JSON.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OverlayRule {
+    And {
+        rules: Vec<OverlayRule>,
+    },
+    Or {
+        rules: Vec<OverlayRule>,
+    },
+    Not {
+        rule: Box<OverlayRule>,
+    },
+    Category {
+        ids: Vec<Uuid>,
+    },
+    Vendor {
+        ids: Vec<Uuid>,
+    },
+    /// Matches the account the transaction is paid from
+    Account {
+        ids: Vec<Uuid>,
+    },
+    /// Inclusive bounds; at least one is required
+    Amount {
+        min: Option<i64>,
+        max: Option<i64>,
+    },
+    /// Case-insensitive substring of the description
+    Description {
+        contains: String,
+    },
+    Weekday {
+        days: Vec<OverlayWeekday>,
+    },
+    /// The type of the transaction's category
+    Direction {
+        direction: CategoryType,
+    },
+}
+
+/// The transaction fields overlay rules can look at.
+pub struct OverlayRuleSubject<'a> {
+    pub category_id: &'a Uuid,
+    pub vendor_id: Option<&'a Uuid>,
+    pub from_account_id: &'a Uuid,
+    pub amount: i64,
+    pub description: &'a str,
+    pub occurred_at: NaiveDate,
+    pub direction: CategoryType,
+}
+
+impl OverlayRule {
+    pub fn matches(&self, subject: &OverlayRuleSubject) -> bool {
+        match self {
+            OverlayRule::And { rules } => rules.iter().all(|rule| rule.matches(subject)),
+            OverlayRule::Or { rules } => rules.iter().any(|rule| rule.matches(subject)),
+            OverlayRule::Not { rule } => !rule.matches(subject),
+            OverlayRule::Category { ids } => ids.contains(subject.category_id),
+            OverlayRule::Vendor { ids } => subject.vendor_id.is_some_and(|vendor_id| ids.contains(vendor_id)),
+            OverlayRule::Account { ids } => ids.contains(subject.from_account_id),
+            OverlayRule::Amount { min, max } => min.is_none_or(|min| subject.amount >= min) && max.is_none_or(|max| subject.amount <= max),
+            OverlayRule::Description { contains } => subject.description.to_lowercase().contains(&contains.to_lowercase()),
+            OverlayRule::Weekday { days } => days.contains(&OverlayWeekday::from(subject.occurred_at.weekday())),
+            OverlayRule::Direction { direction } => subject.direction == *direction,
+        }
+    }
+
+    /// Check the tree is well formed, returning the code of the first problem found.
+    pub fn validate_tree(&self) -> Result<(), &'static str> {
+        self.validate_at_depth(1)
+    }
+
+    fn validate_at_depth(&self, depth: usize) -> Result<(), &'static str> {
+        if depth > MAX_OVERLAY_RULE_DEPTH {
+            return Err("rules_too_deep");
+        }
+
+        match self {
+            OverlayRule::And { rules } | OverlayRule::Or { rules } => {
+                if rules.is_empty() {
+                    return Err("rule_group_must_not_be_empty");
+                }
+                rules.iter().try_for_each(|rule| rule.validate_at_depth(depth + 1))
+            }
+            OverlayRule::Not { rule } => rule.validate_at_depth(depth + 1),
+            OverlayRule::Category { ids } | OverlayRule::Vendor { ids } | OverlayRule::Account { ids } if ids.is_empty() => Err("rule_ids_must_not_be_empty"),
+            OverlayRule::Amount { min: None, max: None } => Err("amount_rule_needs_a_bound"),
+            OverlayRule::Amount {
+                min: Some(min),
+                max: Some(max),
+            } if min > max => Err("amount_rule_min_must_not_exceed_max"),
+            OverlayRule::Description { contains } if contains.trim().is_empty() => Err("description_rule_must_not_be_empty"),
+            OverlayRule::Weekday { days } if days.is_empty() => Err("weekday_rule_must_not_be_empty"),
+            _ => Ok(()),
+        }
+    }
+
+    /// Point category terms at `to` instead of `from`, returning whether the tree changed.
+    pub fn replace_category_id(&mut self, from: &Uuid, to: &Uuid) -> bool {
+        self.replace_term_id(
+            |rule| match rule {
+                OverlayRule::Category { ids } => Some(ids),
+                _ => None,
+            },
+            from,
+            to,
+        )
+    }
+
+    /// Point vendor terms at `to` instead of `from`, returning whether the tree changed.
+    pub fn replace_vendor_id(&mut self, from: &Uuid, to: &Uuid) -> bool {
+        self.replace_term_id(
+            |rule| match rule {
+                OverlayRule::Vendor { ids } => Some(ids),
+                _ => None,
+            },
+            from,
+            to,
+        )
+    }
+
+    fn replace_term_id(&mut self, term_ids: fn(&mut OverlayRule) -> Option<&mut Vec<Uuid>>, from: &Uuid, to: &Uuid) -> bool {
+        match self {
+            OverlayRule::And { rules } | OverlayRule::Or { rules } => {
+                // Every term has to be visited, so no short-circuiting `any`
+                let mut changed = false;
+                for rule in rules {
+                    changed |= rule.replace_term_id(term_ids, from, to);
+                }
+                changed
+            }
+            OverlayRule::Not { rule } => rule.replace_term_id(term_ids, from, to),
+            _ => term_ids(self).is_some_and(|ids| replace_id(ids, from, to)),
+        }
+    }
+}
+
+fn replace_id(ids: &mut Vec<Uuid>, from: &Uuid, to: &Uuid) -> bool {
+    if !ids.contains(from) {
+        return false;
+    }
+    ids.retain(|id| id != from);
+    if !ids.contains(to) {
+        ids.push(*to);
+    }
+    true
 }
 
 // ===== Category Cap Models =====
@@ -54,6 +236,19 @@ pub struct OverlayCategoryCap {
     pub cap_amount: i64,
 }
 
+/// Move the cap of category `from` to `to`, returning whether anything changed. A cap `to` already has wins.
+pub fn replace_cap_category_id(caps: &mut Vec<OverlayCategoryCap>, from: &Uuid, to: &Uuid) -> bool {
+    let Some(index) = caps.iter().position(|cap| cap.category_id == *from) else {
+        return false;
+    };
+    if caps.iter().any(|cap| cap.category_id == *to) {
+        caps.remove(index);
+    } else {
+        caps[index].category_id = *to;
+    }
+    true
+}
+
 // ===== Overlay Domain Model =====
 
 #[derive(Serialize, Debug, Clone)]
@@ -66,7 +261,10 @@ pub struct Overlay {
     pub end_date: NaiveDate,
     pub inclusion_mode: InclusionMode,
     pub total_cap_amount: Option<i64>,
-    pub rules: OverlayRules,
+    pub alert_thresholds: Vec<i32>,
+    pub rules: Option<OverlayRule>,
+    /// The template this overlay is an instance of
+    pub template_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -77,12 +275,15 @@ pub struct OverlayWithMetrics {
     pub spent_amount: i64,
     pub transaction_count: i64,
     pub category_caps: Vec<OverlayCategoryCap>,
+    /// Spend of included transactions per category
+    pub spent_per_category: HashMap<Uuid, i64>,
 }
 
 // ===== Request DTOs =====
 
 #[derive(Deserialize, Debug, Validate, JsonSchema)]
 #[validate(schema(function = "validate_overlay_date_range"))]
+#[validate(schema(function = "validate_overlay_rules"))]
 pub struct OverlayRequest {
     #[validate(length(min = 1))]
     pub name: String,
@@ -93,8 +294,13 @@ pub struct OverlayRequest {
     pub total_cap_amount: Option<i64>,
     #[serde(default)]
     pub category_caps: Vec<OverlayCategoryCap>,
+    /// Share of a cap (basis points, e.g. 8000 = 80%) whose crossing raises an alert, once per cap
+    #[serde(default = "default_overlay_alert_thresholds")]
+    #[validate(custom(function = "validate_alert_thresholds"))]
+    pub alert_thresholds: Vec<i32>,
+    /// Used when `inclusion_mode` is `rules`; without it no transaction matches by rule
     #[serde(default)]
-    pub rules: OverlayRules,
+    pub rules: Option<OverlayRule>,
 }
 
 fn validate_overlay_date_range(request: &OverlayRequest) -> Result<(), validator::ValidationError> {
@@ -104,6 +310,17 @@ fn validate_overlay_date_range(request: &OverlayRequest) -> Result<(), validator
     Ok(())
 }
 
+fn default_overlay_alert_thresholds() -> Vec<i32> {
+    vec![8000, 10000]
+}
+
+fn validate_overlay_rules(request: &OverlayRequest) -> Result<(), validator::ValidationError> {
+    match &request.rules {
+        Some(rules) => rules.validate_tree().map_err(validator::ValidationError::new),
+        None => Ok(()),
+    }
+}
+
 // ===== Response DTOs =====
 
 #[derive(Serialize, Debug, JsonSchema)]
@@ -118,7 +335,9 @@ pub struct OverlayResponse {
     pub spent_amount: i64,
     pub transaction_count: i64,
     pub category_caps: Vec<OverlayCategoryCap>,
-    pub rules: OverlayRules,
+    pub alert_thresholds: Vec<i32>,
+    pub rules: Option<OverlayRule>,
+    pub template_id: Option<Uuid>,
 }
 
 impl From<&OverlayWithMetrics> for OverlayResponse {
@@ -134,11 +353,127 @@ impl From<&OverlayWithMetrics> for OverlayResponse {
             spent_amount: overlay_with_metrics.spent_amount,
             transaction_count: overlay_with_metrics.transaction_count,
             category_caps: overlay_with_metrics.category_caps.clone(),
+            alert_thresholds: overlay_with_metrics.overlay.alert_thresholds.clone(),
             rules: overlay_with_metrics.overlay.rules.clone(),
+            template_id: overlay_with_metrics.overlay.template_id,
         }
     }
 }
 
+// ===== Pacing Models =====
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CapStatus {
+    OnTrack,
+    /// Under the cap, but the current pace ends above it
+    AtRisk,
+    Exceeded,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct CapPacingResponse {
+    /// `None` for the overlay's total cap
+    pub category_id: Option<Uuid>,
+    pub cap_amount: i64,
+    pub spent_amount: i64,
+    /// The cap prorated by the elapsed share of the overlay
+    pub ideal_spent_to_date: i64,
+    pub projected_total: i64,
+    /// Spend relative to the cap in basis points
+    pub usage_basis_points: i64,
+    pub status: CapStatus,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct OverlayPacingResponse {
+    pub overlay_id: Uuid,
+    pub as_of: NaiveDate,
+    /// Days of the overlay up to and including `as_of`
+    pub elapsed_days: i64,
+    pub total_days: i64,
+    pub spent_amount: i64,
+    /// Total spend if the pace so far holds until `end_date`
+    pub projected_total: i64,
+    pub total_cap: Option<CapPacingResponse>,
+    pub category_caps: Vec<CapPacingResponse>,
+}
+
+/// Pace the overlay's spending against its caps as of `today`.
+pub fn overlay_pacing(overlay: &OverlayWithMetrics, today: NaiveDate) -> OverlayPacingResponse {
+    let total_days = (overlay.overlay.end_date - overlay.overlay.start_date).num_days() + 1;
+    let elapsed_days = ((today - overlay.overlay.start_date).num_days() + 1).clamp(0, total_days);
+
+    let cap_pacing = |category_id: Option<Uuid>, cap_amount: i64, spent_amount: i64| {
+        let projected_total = project(spent_amount, elapsed_days, total_days);
+        let status = if spent_amount > cap_amount {
+            CapStatus::Exceeded
+        } else if projected_total > cap_amount {
+            CapStatus::AtRisk
+        } else {
+            CapStatus::OnTrack
+        };
+        CapPacingResponse {
+            category_id,
+            cap_amount,
+            spent_amount,
+            ideal_spent_to_date: cap_amount.saturating_mul(elapsed_days) / total_days,
+            projected_total,
+            usage_basis_points: cap_usage_basis_points(cap_amount, spent_amount),
+            status,
+        }
+    };
+
+    OverlayPacingResponse {
+        overlay_id: overlay.overlay.id,
+        as_of: today,
+        elapsed_days,
+        total_days,
+        spent_amount: overlay.spent_amount,
+        projected_total: project(overlay.spent_amount, elapsed_days, total_days),
+        total_cap: overlay.overlay.total_cap_amount.map(|cap| cap_pacing(None, cap, overlay.spent_amount)),
+        category_caps: overlay
+            .category_caps
+            .iter()
+            .map(|cap| {
+                let spent = overlay.spent_per_category.get(&cap.category_id).copied().unwrap_or(0);
+                cap_pacing(Some(cap.category_id), cap.cap_amount, spent)
+            })
+            .collect(),
+    }
+}
+
+/// Spend relative to a cap in basis points; spending anything against a zero cap counts as exceeded.
+pub fn cap_usage_basis_points(cap_amount: i64, spent: i64) -> i64 {
+    if cap_amount <= 0 {
+        return if spent > 0 { i64::MAX } else { 0 };
+    }
+    spent.max(0).saturating_mul(10_000) / cap_amount
+}
+
+fn project(spent: i64, elapsed_days: i64, total_days: i64) -> i64 {
+    if elapsed_days == 0 {
+        spent
+    } else {
+        spent.saturating_mul(total_days) / elapsed_days
+    }
+}
+
+/// An overlay cap covering a date, with what it is alerted on
+#[derive(Debug, Clone)]
+pub struct OverlayCapSpend {
+    pub overlay_id: Uuid,
+    pub overlay_name: String,
+    /// `None` for the overlay's total cap
+    pub category_id: Option<Uuid>,
+    pub category_name: Option<String>,
+    pub cap_amount: i64,
+    pub spent: i64,
+    pub thresholds: Vec<i32>,
+    pub currency_symbol: String,
+    pub currency_decimal_places: i32,
+}
+
 // ===== Transaction Membership Models =====
 
 #[derive(Serialize, Debug, Clone, JsonSchema)]
@@ -153,3 +488,576 @@ pub struct TransactionWithMembership {
     pub transaction: TransactionResponse,
     pub membership: TransactionMembership,
 }
+
+// ===== Report Models =====
+
+/// How many of the largest transactions the overlay report lists.
+const OVERLAY_REPORT_LARGEST_TRANSACTIONS: usize = 10;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct OverlayDailySpend {
+    pub date: NaiveDate,
+    pub spent_amount: i64,
+    pub cumulative_amount: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct OverlayCategoryBreakdown {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+    pub cap_amount: Option<i64>,
+    /// Spend relative to the category cap in basis points; absent without a cap
+    pub cap_usage_basis_points: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct OverlayBreakdownEntry {
+    /// `None` groups transactions without a vendor
+    pub id: Option<Uuid>,
+    pub name: Option<String>,
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct OverlaySourceBreakdown {
+    pub inclusion_source: InclusionSource,
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct OverlayReportTransaction {
+    pub id: Uuid,
+    pub occurred_at: NaiveDate,
+    pub description: String,
+    pub amount: i64,
+    pub category_name: String,
+    pub vendor_name: Option<String>,
+    pub inclusion_source: Option<InclusionSource>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct OverlayReportResponse {
+    pub overlay_id: Uuid,
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+    /// Every day of the overlay, including days without spend
+    pub daily: Vec<OverlayDailySpend>,
+    /// Categories with spend or a cap, largest spend first
+    pub categories: Vec<OverlayCategoryBreakdown>,
+    pub vendors: Vec<OverlayBreakdownEntry>,
+    /// By the account the transaction is paid from
+    pub accounts: Vec<OverlayBreakdownEntry>,
+    pub largest_transactions: Vec<OverlayReportTransaction>,
+    /// Spend split by how transactions joined the overlay
+    pub by_source: Vec<OverlaySourceBreakdown>,
+}
+
+/// Break down the included transactions of an overlay. `category_names` names capped
+/// categories that have no included transactions.
+pub fn overlay_report(
+    overlay: &OverlayWithMetrics,
+    transactions: &[TransactionWithMembership],
+    category_names: &HashMap<Uuid, String>,
+) -> OverlayReportResponse {
+    let included: Vec<&TransactionWithMembership> = transactions.iter().filter(|transaction| transaction.membership.is_included).collect();
+
+    let mut spent_per_day: HashMap<NaiveDate, i64> = HashMap::new();
+    for transaction in &included {
+        *spent_per_day.entry(transaction.transaction.occurred_at).or_insert(0) += transaction.transaction.amount;
+    }
+    let mut cumulative_amount = 0;
+    let daily = overlay
+        .overlay
+        .start_date
+        .iter_days()
+        .take_while(|date| *date <= overlay.overlay.end_date)
+        .map(|date| {
+            let spent_amount = spent_per_day.get(&date).copied().unwrap_or(0);
+            cumulative_amount += spent_amount;
+            OverlayDailySpend {
+                date,
+                spent_amount,
+                cumulative_amount,
+            }
+        })
+        .collect();
+
+    let mut categories: Vec<OverlayCategoryBreakdown> = breakdown(&included, |transaction| {
+        (Some(transaction.category.id), Some(transaction.category.name.clone()))
+    })
+    .into_iter()
+    .map(|entry| OverlayCategoryBreakdown {
+        category_id: entry.id.unwrap_or_default(),
+        category_name: entry.name.unwrap_or_default(),
+        spent_amount: entry.spent_amount,
+        transaction_count: entry.transaction_count,
+        cap_amount: None,
+        cap_usage_basis_points: None,
+    })
+    .collect();
+    for cap in &overlay.category_caps {
+        let index = match categories.iter().position(|category| category.category_id == cap.category_id) {
+            Some(index) => index,
+            None => {
+                categories.push(OverlayCategoryBreakdown {
+                    category_id: cap.category_id,
+                    category_name: category_names.get(&cap.category_id).cloned().unwrap_or_default(),
+                    spent_amount: 0,
+                    transaction_count: 0,
+                    cap_amount: None,
+                    cap_usage_basis_points: None,
+                });
+                categories.len() - 1
+            }
+        };
+        categories[index].cap_amount = Some(cap.cap_amount);
+        categories[index].cap_usage_basis_points = Some(cap_usage_basis_points(cap.cap_amount, categories[index].spent_amount));
+    }
+
+    let mut largest: Vec<&TransactionWithMembership> = included.clone();
+    largest.sort_by(|a, b| {
+        b.transaction
+            .amount
+            .cmp(&a.transaction.amount)
+            .then_with(|| a.transaction.occurred_at.cmp(&b.transaction.occurred_at))
+    });
+
+    let by_source = [InclusionSource::Rules, InclusionSource::Manual, InclusionSource::All]
+        .into_iter()
+        .filter_map(|source| {
+            let matching: Vec<_> = included
+                .iter()
+                .filter(|transaction| transaction.membership.inclusion_source == Some(source))
+                .collect();
+            (!matching.is_empty()).then(|| OverlaySourceBreakdown {
+                inclusion_source: source,
+                spent_amount: matching.iter().map(|transaction| transaction.transaction.amount).sum(),
+                transaction_count: matching.len() as i64,
+            })
+        })
+        .collect();
+
+    OverlayReportResponse {
+        overlay_id: overlay.overlay.id,
+        spent_amount: included.iter().map(|transaction| transaction.transaction.amount).sum(),
+        transaction_count: included.len() as i64,
+        daily,
+        categories,
+        vendors: breakdown(&included, |transaction| {
+            (
+                transaction.vendor.as_ref().map(|vendor| vendor.id),
+                transaction.vendor.as_ref().map(|vendor| vendor.name.clone()),
+            )
+        }),
+        accounts: breakdown(&included, |transaction| {
+            (Some(transaction.from_account.id), Some(transaction.from_account.name.clone()))
+        }),
+        largest_transactions: largest
+            .into_iter()
+            .take(OVERLAY_REPORT_LARGEST_TRANSACTIONS)
+            .map(|transaction| OverlayReportTransaction {
+                id: transaction.transaction.id,
+                occurred_at: transaction.transaction.occurred_at,
+                description: transaction.transaction.description.clone(),
+                amount: transaction.transaction.amount,
+                category_name: transaction.transaction.category.name.clone(),
+                vendor_name: transaction.transaction.vendor.as_ref().map(|vendor| vendor.name.clone()),
+                inclusion_source: transaction.membership.inclusion_source,
+            })
+            .collect(),
+        by_source,
+    }
+}
+
+/// Sum included transactions per key, largest spend first.
+fn breakdown(included: &[&TransactionWithMembership], key: impl Fn(&TransactionResponse) -> (Option<Uuid>, Option<String>)) -> Vec<OverlayBreakdownEntry> {
+    let mut entries: Vec<OverlayBreakdownEntry> = Vec::new();
+    for transaction in included {
+        let (id, name) = key(&transaction.transaction);
+        match entries.iter_mut().find(|entry| entry.id == id) {
+            Some(entry) => {
+                entry.spent_amount += transaction.transaction.amount;
+                entry.transaction_count += 1;
+            }
+            None => entries.push(OverlayBreakdownEntry {
+                id,
+                name,
+                spent_amount: transaction.transaction.amount,
+                transaction_count: 1,
+            }),
+        }
+    }
+    entries.sort_by(|a, b| b.spent_amount.cmp(&a.spent_amount).then_with(|| a.name.cmp(&b.name)));
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn subject<'a>(category_id: &'a Uuid, account_id: &'a Uuid, amount: i64, description: &'a str) -> OverlayRuleSubject<'a> {
+        OverlayRuleSubject {
+            category_id,
+            vendor_id: None,
+            from_account_id: account_id,
+            amount,
+            description,
+            occurred_at: NaiveDate::from_ymd_opt(2026, 3, 14).expect("valid date"),
+            direction: CategoryType::Outgoing,
+        }
+    }
+
+    #[test]
+    fn rules_deserialize_from_tagged_json() {
+        let account_id = Uuid::new_v4();
+        let rule: OverlayRule = serde_json::from_value(json!({
+            "type": "and",
+            "rules": [
+                { "type": "account", "ids": [account_id] },
+                { "type": "amount", "min": 2001 },
+                { "type": "not", "rule": { "type": "weekday", "days": ["sunday"] } }
+            ]
+        }))
+        .expect("valid rule");
+
+        assert_eq!(
+            rule,
+            OverlayRule::And {
+                rules: vec![
+                    OverlayRule::Account { ids: vec![account_id] },
+                    OverlayRule::Amount { min: Some(2001), max: None },
+                    OverlayRule::Not {
+                        rule: Box::new(OverlayRule::Weekday {
+                            days: vec![OverlayWeekday::Sunday]
+                        })
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn rules_combine_conditions() {
+        let category_id = Uuid::new_v4();
+        let travel_card = Uuid::new_v4();
+        let vendor_id = Uuid::new_v4();
+        let rule = OverlayRule::Or {
+            rules: vec![
+                OverlayRule::And {
+                    rules: vec![
+                        OverlayRule::Account { ids: vec![travel_card] },
+                        OverlayRule::Amount { min: Some(2001), max: None },
+                    ],
+                },
+                OverlayRule::Description {
+                    contains: "lisbon".to_string(),
+                },
+                OverlayRule::Vendor { ids: vec![vendor_id] },
+            ],
+        };
+
+        assert!(rule.matches(&subject(&category_id, &travel_card, 5000, "Dinner")));
+        assert!(!rule.matches(&subject(&category_id, &travel_card, 1500, "Coffee")));
+        assert!(rule.matches(&subject(&category_id, &category_id, 1500, "Tram in LISBON")));
+
+        let mut with_vendor = subject(&category_id, &category_id, 100, "Snack");
+        with_vendor.vendor_id = Some(&vendor_id);
+        assert!(rule.matches(&with_vendor));
+
+        let saturday = OverlayRule::Weekday {
+            days: vec![OverlayWeekday::Saturday],
+        };
+        assert!(saturday.matches(&subject(&category_id, &travel_card, 100, "Market")));
+        assert!(
+            !OverlayRule::Direction {
+                direction: CategoryType::Incoming
+            }
+            .matches(&subject(&category_id, &travel_card, 100, "Market"))
+        );
+    }
+
+    #[test]
+    fn validation_rejects_malformed_trees() {
+        assert_eq!(OverlayRule::Or { rules: vec![] }.validate_tree(), Err("rule_group_must_not_be_empty"));
+        assert_eq!(OverlayRule::Category { ids: vec![] }.validate_tree(), Err("rule_ids_must_not_be_empty"));
+        assert_eq!(OverlayRule::Amount { min: None, max: None }.validate_tree(), Err("amount_rule_needs_a_bound"));
+        assert_eq!(
+            OverlayRule::Amount { min: Some(10), max: Some(5) }.validate_tree(),
+            Err("amount_rule_min_must_not_exceed_max")
+        );
+
+        let mut deep = OverlayRule::Description { contains: "x".to_string() };
+        for _ in 0..MAX_OVERLAY_RULE_DEPTH {
+            deep = OverlayRule::Not { rule: Box::new(deep) };
+        }
+        assert_eq!(deep.validate_tree(), Err("rules_too_deep"));
+    }
+
+    #[test]
+    fn replacing_an_id_rewrites_only_matching_terms_and_caps() {
+        let (groceries, food, travel) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let mut rule = OverlayRule::And {
+            rules: vec![
+                OverlayRule::Category { ids: vec![groceries, food] },
+                OverlayRule::Not {
+                    rule: Box::new(OverlayRule::Category { ids: vec![travel, groceries] }),
+                },
+            ],
+        };
+
+        assert!(rule.replace_category_id(&groceries, &food));
+        assert_eq!(
+            rule,
+            OverlayRule::And {
+                rules: vec![
+                    OverlayRule::Category { ids: vec![food] },
+                    OverlayRule::Not {
+                        rule: Box::new(OverlayRule::Category { ids: vec![travel, food] }),
+                    },
+                ],
+            }
+        );
+        assert!(!rule.replace_category_id(&groceries, &food));
+        assert!(!rule.replace_vendor_id(&groceries, &food));
+
+        let (shop, amazon) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut vendor_rule = OverlayRule::Or {
+            rules: vec![OverlayRule::Vendor { ids: vec![shop] }, OverlayRule::Category { ids: vec![shop] }],
+        };
+        assert!(vendor_rule.replace_vendor_id(&shop, &amazon));
+        assert_eq!(
+            vendor_rule,
+            OverlayRule::Or {
+                rules: vec![OverlayRule::Vendor { ids: vec![amazon] }, OverlayRule::Category { ids: vec![shop] }],
+            }
+        );
+
+        let cap = |category_id, cap_amount| OverlayCategoryCap { category_id, cap_amount };
+        let mut caps = vec![cap(groceries, 100), cap(travel, 300)];
+        assert!(replace_cap_category_id(&mut caps, &groceries, &food));
+        assert_eq!(
+            caps.iter().map(|cap| (cap.category_id, cap.cap_amount)).collect::<Vec<_>>(),
+            vec![(food, 100), (travel, 300)]
+        );
+        assert!(replace_cap_category_id(&mut caps, &travel, &food));
+        assert_eq!(caps.iter().map(|cap| (cap.category_id, cap.cap_amount)).collect::<Vec<_>>(), vec![(food, 100)]);
+        assert!(!replace_cap_category_id(&mut caps, &travel, &food));
+    }
+
+    fn trip(total_cap_amount: Option<i64>, category_caps: Vec<OverlayCategoryCap>, spent_per_category: HashMap<Uuid, i64>) -> OverlayWithMetrics {
+        let now = Utc::now();
+        OverlayWithMetrics {
+            overlay: Overlay {
+                id: Uuid::new_v4(),
+                user_id: Uuid::new_v4(),
+                name: "Lisbon".to_string(),
+                icon: None,
+                start_date: NaiveDate::from_ymd_opt(2026, 6, 1).expect("valid date"),
+                end_date: NaiveDate::from_ymd_opt(2026, 6, 10).expect("valid date"),
+                inclusion_mode: InclusionMode::Rules,
+                total_cap_amount,
+                alert_thresholds: vec![8000, 10000],
+                rules: None,
+                template_id: None,
+                created_at: now,
+                updated_at: now,
+            },
+            spent_amount: spent_per_category.values().sum(),
+            transaction_count: spent_per_category.len() as i64,
+            category_caps,
+            spent_per_category,
+        }
+    }
+
+    #[test]
+    fn pacing_prorates_caps_and_projects_the_current_pace() {
+        let dining = Uuid::new_v4();
+        let museums = Uuid::new_v4();
+        let overlay = trip(
+            Some(100_000),
+            vec![
+                OverlayCategoryCap {
+                    category_id: dining,
+                    cap_amount: 30_000,
+                },
+                OverlayCategoryCap {
+                    category_id: museums,
+                    cap_amount: 5_000,
+                },
+            ],
+            HashMap::from([(dining, 20_000), (museums, 6_000)]),
+        );
+
+        let pacing = overlay_pacing(&overlay, NaiveDate::from_ymd_opt(2026, 6, 4).expect("valid date"));
+
+        assert_eq!((pacing.elapsed_days, pacing.total_days), (4, 10));
+        assert_eq!(pacing.projected_total, 65_000);
+        assert_eq!(
+            pacing.total_cap,
+            Some(CapPacingResponse {
+                category_id: None,
+                cap_amount: 100_000,
+                spent_amount: 26_000,
+                ideal_spent_to_date: 40_000,
+                projected_total: 65_000,
+                usage_basis_points: 2_600,
+                status: CapStatus::OnTrack,
+            })
+        );
+        assert_eq!(pacing.category_caps[0].ideal_spent_to_date, 12_000);
+        assert_eq!(pacing.category_caps[0].status, CapStatus::AtRisk);
+        assert_eq!(pacing.category_caps[1].status, CapStatus::Exceeded);
+    }
+
+    #[test]
+    fn pacing_before_the_overlay_starts_projects_current_spend() {
+        let overlay = trip(Some(10_000), vec![], HashMap::from([(Uuid::new_v4(), 2_500)]));
+
+        let pacing = overlay_pacing(&overlay, NaiveDate::from_ymd_opt(2026, 5, 20).expect("valid date"));
+
+        assert_eq!(pacing.elapsed_days, 0);
+        assert_eq!(pacing.projected_total, 2_500);
+        assert_eq!(pacing.total_cap.map(|cap| (cap.ideal_spent_to_date, cap.status)), Some((0, CapStatus::OnTrack)));
+    }
+
+    fn report_transaction(
+        category: (Uuid, &str),
+        account: (Uuid, &str),
+        vendor: Option<(Uuid, &str)>,
+        day: u32,
+        amount: i64,
+        inclusion_source: Option<InclusionSource>,
+    ) -> TransactionWithMembership {
+        use crate::models::account::{AccountResponse, AccountType};
+        use crate::models::category::CategoryResponse;
+        use crate::models::currency::{CurrencyResponse, SymbolPosition};
+        use crate::models::vendor::VendorResponse;
+
+        TransactionWithMembership {
+            transaction: TransactionResponse {
+                id: Uuid::new_v4(),
+                amount,
+                description: format!("Purchase on day {day}"),
+                occurred_at: NaiveDate::from_ymd_opt(2026, 6, day).expect("valid date"),
+                category: CategoryResponse {
+                    id: category.0,
+                    name: category.1.to_string(),
+                    color: "#000000".to_string(),
+                    icon: String::new(),
+                    parent_id: None,
+                    category_type: CategoryType::Outgoing,
+                    is_archived: false,
+                    description: None,
+                },
+                from_account: AccountResponse {
+                    id: account.0,
+                    name: account.1.to_string(),
+                    color: "#000000".to_string(),
+                    icon: String::new(),
+                    account_type: AccountType::Checking,
+                    currency: CurrencyResponse {
+                        id: Uuid::new_v4(),
+                        name: "Euro".to_string(),
+                        symbol: "€".to_string(),
+                        currency: "EUR".to_string(),
+                        decimal_places: 2,
+                        symbol_position: SymbolPosition::Before,
+                    },
+                    balance: 0,
+                    spend_limit: None,
+                    is_archived: false,
+                    next_transfer_amount: None,
+                },
+                to_account: None,
+                vendor: vendor.map(|(id, name)| VendorResponse {
+                    id,
+                    name: name.to_string(),
+                    description: None,
+                    archived: false,
+                }),
+            },
+            membership: TransactionMembership {
+                is_included: inclusion_source.is_some(),
+                inclusion_source,
+            },
+        }
+    }
+
+    #[test]
+    fn report_breaks_down_included_spend() {
+        let food = (Uuid::new_v4(), "Food");
+        let hotel = (Uuid::new_v4(), "Hotel");
+        let museums = Uuid::new_v4();
+        let card = (Uuid::new_v4(), "Card");
+        let cash = (Uuid::new_v4(), "Cash");
+        let cafe = (Uuid::new_v4(), "Cafe");
+        let overlay = trip(
+            None,
+            vec![
+                OverlayCategoryCap {
+                    category_id: food.0,
+                    cap_amount: 10_000,
+                },
+                OverlayCategoryCap {
+                    category_id: museums,
+                    cap_amount: 5_000,
+                },
+            ],
+            HashMap::new(),
+        );
+        let transactions = vec![
+            report_transaction(food, card, Some(cafe), 1, 2_000, Some(InclusionSource::Rules)),
+            report_transaction(food, cash, None, 3, 500, Some(InclusionSource::Manual)),
+            report_transaction(hotel, card, None, 3, 30_000, Some(InclusionSource::Rules)),
+            report_transaction(food, card, Some(cafe), 4, 9_999, None),
+        ];
+        let names = HashMap::from([(museums, "Museums".to_string())]);
+
+        let report = overlay_report(&overlay, &transactions, &names);
+
+        assert_eq!(report.spent_amount, 32_500);
+        assert_eq!(report.transaction_count, 3);
+
+        assert_eq!(report.daily.len(), 10);
+        assert_eq!(report.daily[2].spent_amount, 30_500);
+        assert_eq!(report.daily[2].cumulative_amount, 32_500);
+        assert_eq!(report.daily[9].cumulative_amount, 32_500);
+
+        let categories: Vec<_> = report
+            .categories
+            .iter()
+            .map(|category| (category.category_name.as_str(), category.spent_amount, category.cap_usage_basis_points))
+            .collect();
+        assert_eq!(categories, vec![("Hotel", 30_000, None), ("Food", 2_500, Some(2_500)), ("Museums", 0, Some(0))]);
+
+        assert_eq!(report.vendors[0].id, None);
+        assert_eq!(report.vendors[0].spent_amount, 30_500);
+        assert_eq!(report.vendors[1].name.as_deref(), Some("Cafe"));
+        assert_eq!(report.accounts[0].name.as_deref(), Some("Card"));
+        assert_eq!(report.accounts[0].transaction_count, 2);
+
+        assert_eq!(report.largest_transactions[0].amount, 30_000);
+        assert_eq!(report.largest_transactions.len(), 3);
+
+        assert_eq!(
+            report.by_source,
+            vec![
+                OverlaySourceBreakdown {
+                    inclusion_source: InclusionSource::Rules,
+                    spent_amount: 32_000,
+                    transaction_count: 2,
+                },
+                OverlaySourceBreakdown {
+                    inclusion_source: InclusionSource::Manual,
+                    spent_amount: 500,
+                    transaction_count: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,386 @@
+use crate::models::overlay::{InclusionMode, OverlayCategoryCap, OverlayRequest, OverlayRule};
+use crate::models::period_comparison::AmountDeltaResponse;
+use crate::models::settings::validate_alert_thresholds;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Longest overlay a fixed-length date rule can describe.
+const MAX_FIXED_LENGTH_DAYS: i32 = 366;
+
+/// How many earlier instances the template report compares an instance with.
+pub const OVERLAY_TEMPLATE_REPORT_INSTANCES: i64 = 5;
+
+// ===== Date Rules =====
+
+/// When the overlays of a template run
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OverlayDateRule {
+    /// The same calendar dates every year, e.g. Dec 1 – Dec 31. An end before the start runs into the next
+    /// year; Feb 29 falls back to Feb 28 in common years.
+    Yearly {
+        start_month: u32,
+        start_day: u32,
+        end_month: u32,
+        end_day: u32,
+    },
+    /// `duration_days` starting on a chosen date. Automatic instances start a year after the previous one.
+    FixedLength { duration_days: i32 },
+}
+
+impl OverlayDateRule {
+    pub fn is_valid(&self) -> bool {
+        match *self {
+            OverlayDateRule::Yearly {
+                start_month,
+                start_day,
+                end_month,
+                end_day,
+            } => {
+                // 2024 is a leap year, so Feb 29 is accepted
+                let start = NaiveDate::from_ymd_opt(2024, start_month, start_day);
+                let end = NaiveDate::from_ymd_opt(2024, end_month, end_day);
+                start.is_some() && end.is_some() && start != end
+            }
+            OverlayDateRule::FixedLength { duration_days } => (2..=MAX_FIXED_LENGTH_DAYS).contains(&duration_days),
+        }
+    }
+
+    /// The occurrence that has not ended by `date`: the one covering it, or else the next one.
+    /// Fixed-length rules start on `date` itself.
+    pub fn occurrence_for(&self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match *self {
+            OverlayDateRule::Yearly {
+                start_month,
+                start_day,
+                end_month,
+                end_day,
+            } => {
+                let occurrence_in = |year: i32| {
+                    let start = yearly_date(year, start_month, start_day);
+                    let end_year = if (end_month, end_day) < (start_month, start_day) { year + 1 } else { year };
+                    (start, yearly_date(end_year, end_month, end_day))
+                };
+                // An occurrence wrapping into the new year may have started the year before
+                (date.year() - 1..=date.year() + 1)
+                    .map(occurrence_in)
+                    .find(|(_, end)| *end >= date)
+                    .expect("a yearly occurrence ends within two years")
+            }
+            OverlayDateRule::FixedLength { duration_days } => (date, date + Duration::days(i64::from(duration_days) - 1)),
+        }
+    }
+
+    /// The occurrence following one that started on `previous_start`.
+    pub fn next_occurrence(&self, previous_start: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            OverlayDateRule::Yearly { .. } => {
+                let (_, previous_end) = self.occurrence_for(previous_start);
+                self.occurrence_for(previous_end + Duration::days(1))
+            }
+            OverlayDateRule::FixedLength { .. } => {
+                let start = previous_start
+                    .checked_add_months(Months::new(12))
+                    .unwrap_or(previous_start + Duration::days(365));
+                self.occurrence_for(start)
+            }
+        }
+    }
+}
+
+/// The occurrence automatic instantiation should create on `today`, if one is due: the first occurrence after
+/// the latest instance that has not ended yet and starts within `lead_days`. Fixed-length templates need a first,
+/// manually created instance.
+pub fn due_occurrence(rule: &OverlayDateRule, latest_start: Option<NaiveDate>, lead_days: i32, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let mut occurrence = match (rule, latest_start) {
+        (_, Some(latest_start)) => rule.next_occurrence(latest_start),
+        (OverlayDateRule::Yearly { .. }, None) => rule.occurrence_for(today),
+        (OverlayDateRule::FixedLength { .. }, None) => return None,
+    };
+    while occurrence.1 < today {
+        occurrence = rule.next_occurrence(occurrence.0);
+    }
+
+    (occurrence.0 - Duration::days(i64::from(lead_days)) <= today).then_some(occurrence)
+}
+
+fn yearly_date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day)
+        .or_else(|| NaiveDate::from_ymd_opt(year, month, day - 1))
+        .expect("validated month and day")
+}
+
+// ===== Overlay Template Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct OverlayTemplate {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub icon: Option<String>,
+    pub inclusion_mode: InclusionMode,
+    pub total_cap_amount: Option<i64>,
+    pub category_caps: sqlx::types::Json<Vec<OverlayCategoryCap>>,
+    pub alert_thresholds: Vec<i32>,
+    pub rules: Option<sqlx::types::Json<OverlayRule>>,
+    pub date_rule: sqlx::types::Json<OverlayDateRule>,
+    pub auto_create: bool,
+    pub lead_days: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl OverlayTemplate {
+    /// The overlay this template creates for the given dates; instances are named after the year they start in.
+    pub fn overlay_request(&self, start_date: NaiveDate, end_date: NaiveDate) -> OverlayRequest {
+        OverlayRequest {
+            name: format!("{} {}", self.name, start_date.year()),
+            icon: self.icon.clone(),
+            start_date,
+            end_date,
+            inclusion_mode: self.inclusion_mode,
+            total_cap_amount: self.total_cap_amount,
+            category_caps: self.category_caps.0.clone(),
+            alert_thresholds: self.alert_thresholds.clone(),
+            rules: self.rules.as_ref().map(|rules| rules.0.clone()),
+        }
+    }
+}
+
+// ===== Request DTOs =====
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+#[validate(schema(function = "validate_overlay_template_request"))]
+pub struct OverlayTemplateRequest {
+    #[validate(length(min = 1))]
+    pub name: String,
+    pub icon: Option<String>,
+    pub inclusion_mode: InclusionMode,
+    pub total_cap_amount: Option<i64>,
+    #[serde(default)]
+    pub category_caps: Vec<OverlayCategoryCap>,
+    #[serde(default = "default_template_alert_thresholds")]
+    #[validate(custom(function = "validate_alert_thresholds"))]
+    pub alert_thresholds: Vec<i32>,
+    #[serde(default)]
+    pub rules: Option<OverlayRule>,
+    pub date_rule: OverlayDateRule,
+    /// Create each instance automatically `lead_days` before it starts
+    #[serde(default)]
+    pub auto_create: bool,
+    #[serde(default = "default_lead_days")]
+    #[validate(range(min = 0, max = 365))]
+    pub lead_days: i32,
+}
+
+fn validate_overlay_template_request(request: &OverlayTemplateRequest) -> Result<(), validator::ValidationError> {
+    if !request.date_rule.is_valid() {
+        return Err(validator::ValidationError::new("invalid_date_rule"));
+    }
+    match &request.rules {
+        Some(rules) => rules.validate_tree().map_err(validator::ValidationError::new),
+        None => Ok(()),
+    }
+}
+
+/// Save an existing overlay's rules and caps as a template
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+#[validate(schema(function = "validate_template_from_overlay_request"))]
+pub struct OverlayTemplateFromOverlayRequest {
+    pub overlay_id: Uuid,
+    #[validate(length(min = 1))]
+    pub name: String,
+    pub date_rule: OverlayDateRule,
+    #[serde(default)]
+    pub auto_create: bool,
+    #[serde(default = "default_lead_days")]
+    #[validate(range(min = 0, max = 365))]
+    pub lead_days: i32,
+}
+
+fn validate_template_from_overlay_request(request: &OverlayTemplateFromOverlayRequest) -> Result<(), validator::ValidationError> {
+    if request.date_rule.is_valid() {
+        Ok(())
+    } else {
+        Err(validator::ValidationError::new("invalid_date_rule"))
+    }
+}
+
+#[derive(Deserialize, Debug, Default, JsonSchema)]
+pub struct InstantiateOverlayTemplateRequest {
+    /// The instance is the occurrence that has not ended by this date (today by default);
+    /// fixed-length templates start on it
+    pub start_date: Option<NaiveDate>,
+}
+
+fn default_template_alert_thresholds() -> Vec<i32> {
+    vec![8000, 10000]
+}
+
+fn default_lead_days() -> i32 {
+    14
+}
+
+// ===== Response DTOs =====
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct OverlayTemplateResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub icon: Option<String>,
+    pub inclusion_mode: InclusionMode,
+    pub total_cap_amount: Option<i64>,
+    pub category_caps: Vec<OverlayCategoryCap>,
+    pub alert_thresholds: Vec<i32>,
+    pub rules: Option<OverlayRule>,
+    pub date_rule: OverlayDateRule,
+    pub auto_create: bool,
+    pub lead_days: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&OverlayTemplate> for OverlayTemplateResponse {
+    fn from(template: &OverlayTemplate) -> Self {
+        Self {
+            id: template.id,
+            name: template.name.clone(),
+            icon: template.icon.clone(),
+            inclusion_mode: template.inclusion_mode,
+            total_cap_amount: template.total_cap_amount,
+            category_caps: template.category_caps.0.clone(),
+            alert_thresholds: template.alert_thresholds.clone(),
+            rules: template.rules.as_ref().map(|rules| rules.0.clone()),
+            date_rule: template.date_rule.0,
+            auto_create: template.auto_create,
+            lead_days: template.lead_days,
+            created_at: template.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct OverlayInstanceSummary {
+    pub overlay_id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub spent_amount: i64,
+    pub transaction_count: i64,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct OverlayInstanceCategoryDelta {
+    pub category_id: Uuid,
+    pub category_name: String,
+    /// Baseline is the mean over the previous instances
+    #[serde(flatten)]
+    pub amounts: AmountDeltaResponse,
+}
+
+/// An instance of a template compared with the instances before it
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct OverlayTemplateReportResponse {
+    pub template_id: Uuid,
+    pub instance: OverlayInstanceSummary,
+    /// Most recent first
+    pub previous_instances: Vec<OverlayInstanceSummary>,
+    /// Against the instance right before this one
+    pub spent_vs_previous: Option<AmountDeltaResponse>,
+    /// Against the mean of `previous_instances`
+    pub spent_vs_average: Option<AmountDeltaResponse>,
+    pub categories: Vec<OverlayInstanceCategoryDelta>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OverlayTemplateRunResult {
+    pub templates_processed: i64,
+    pub overlays_created: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    #[test]
+    fn yearly_rule_finds_current_or_next_occurrence() {
+        let christmas = OverlayDateRule::Yearly {
+            start_month: 12,
+            start_day: 1,
+            end_month: 12,
+            end_day: 31,
+        };
+
+        assert_eq!(christmas.occurrence_for(date(2026, 3, 10)), (date(2026, 12, 1), date(2026, 12, 31)));
+        assert_eq!(christmas.occurrence_for(date(2026, 12, 15)), (date(2026, 12, 1), date(2026, 12, 31)));
+        assert_eq!(christmas.next_occurrence(date(2026, 12, 1)), (date(2027, 12, 1), date(2027, 12, 31)));
+    }
+
+    #[test]
+    fn yearly_rule_wraps_into_the_next_year() {
+        let winter = OverlayDateRule::Yearly {
+            start_month: 12,
+            start_day: 20,
+            end_month: 1,
+            end_day: 6,
+        };
+
+        assert_eq!(winter.occurrence_for(date(2027, 1, 3)), (date(2026, 12, 20), date(2027, 1, 6)));
+        assert_eq!(winter.occurrence_for(date(2027, 1, 7)), (date(2027, 12, 20), date(2028, 1, 6)));
+    }
+
+    #[test]
+    fn fixed_length_rule_repeats_a_year_later() {
+        let summer = OverlayDateRule::FixedLength { duration_days: 14 };
+
+        assert_eq!(summer.occurrence_for(date(2026, 7, 25)), (date(2026, 7, 25), date(2026, 8, 7)));
+        assert_eq!(summer.next_occurrence(date(2026, 7, 25)), (date(2027, 7, 25), date(2027, 8, 7)));
+    }
+
+    #[test]
+    fn due_occurrence_respects_lead_days_and_skips_missed_years() {
+        let christmas = OverlayDateRule::Yearly {
+            start_month: 12,
+            start_day: 1,
+            end_month: 12,
+            end_day: 31,
+        };
+
+        assert_eq!(due_occurrence(&christmas, None, 14, date(2026, 11, 16)), None);
+        assert_eq!(
+            due_occurrence(&christmas, None, 14, date(2026, 11, 17)),
+            Some((date(2026, 12, 1), date(2026, 12, 31)))
+        );
+        assert_eq!(due_occurrence(&christmas, Some(date(2026, 12, 1)), 14, date(2026, 12, 10)), None);
+        assert_eq!(
+            due_occurrence(&christmas, Some(date(2023, 12, 1)), 0, date(2026, 12, 10)),
+            Some((date(2026, 12, 1), date(2026, 12, 31)))
+        );
+
+        let summer = OverlayDateRule::FixedLength { duration_days: 10 };
+        assert_eq!(due_occurrence(&summer, None, 30, date(2026, 7, 1)), None);
+        assert_eq!(
+            due_occurrence(&summer, Some(date(2025, 7, 20)), 30, date(2026, 7, 1)),
+            Some((date(2026, 7, 20), date(2026, 7, 29)))
+        );
+    }
+
+    #[test]
+    fn leap_day_falls_back_in_common_years() {
+        let leap = OverlayDateRule::Yearly {
+            start_month: 2,
+            start_day: 29,
+            end_month: 3,
+            end_day: 5,
+        };
+
+        assert!(leap.is_valid());
+        assert_eq!(leap.occurrence_for(date(2027, 1, 1)), (date(2027, 2, 28), date(2027, 3, 5)));
+        assert!(!OverlayDateRule::FixedLength { duration_days: 1 }.is_valid());
+    }
+}
@@ -0,0 +1,378 @@
+use chrono::NaiveDate;
+use rocket::serde::Serialize;
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+/// At most this many closed periods can be averaged into a baseline.
+pub const MAX_COMPARISON_AVERAGE_PERIODS: i64 = 12;
+
+/// How many transactions are listed as contributors to each delta.
+const TOP_CONTRIBUTING_TRANSACTIONS: usize = 3;
+
+// ===== Enums =====
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ComparisonStatus {
+    /// Only the compared period has transactions
+    New,
+    /// Only the baseline has transactions
+    Disappeared,
+    Changed,
+    Unchanged,
+}
+
+// ===== Comparison Inputs =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ComparedPeriod {
+    pub id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+/// An Incoming or Outgoing transaction of one side of the comparison
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ComparisonTransaction {
+    pub id: Uuid,
+    pub occurred_at: NaiveDate,
+    pub description: String,
+    pub amount: i64,
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub category_type: String,
+    pub vendor_id: Option<Uuid>,
+    pub vendor_name: Option<String>,
+}
+
+// ===== Responses =====
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct ComparedPeriodResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+}
+
+impl From<&ComparedPeriod> for ComparedPeriodResponse {
+    fn from(period: &ComparedPeriod) -> Self {
+        Self {
+            id: period.id,
+            name: period.name.clone(),
+            start_date: period.start_date,
+            end_date: period.end_date,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, JsonSchema)]
+pub struct AmountDeltaResponse {
+    pub current: i64,
+    /// Baseline amount; the rounded mean when averaging several periods
+    pub baseline: i64,
+    pub delta: i64,
+    /// `delta` relative to `baseline`; absent when the baseline is zero
+    pub delta_basis_points: Option<i64>,
+}
+
+impl AmountDeltaResponse {
+    pub fn new(current: i64, baseline: i64) -> Self {
+        let delta = current - baseline;
+        Self {
+            current,
+            baseline,
+            delta,
+            delta_basis_points: (baseline != 0).then(|| delta * 10_000 / baseline.abs()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct ContributingTransactionResponse {
+    pub id: Uuid,
+    pub occurred_at: NaiveDate,
+    pub description: String,
+    pub amount: i64,
+    /// Whether the transaction belongs to the compared period or the baseline
+    pub from_baseline: bool,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct CategoryDeltaResponse {
+    pub category_id: Uuid,
+    pub category_name: String,
+    pub category_type: String,
+    pub status: ComparisonStatus,
+    #[serde(flatten)]
+    pub amounts: AmountDeltaResponse,
+    /// Largest transactions on the side that grew: the compared period for increases, the baseline for decreases
+    pub top_transactions: Vec<ContributingTransactionResponse>,
+}
+
+#[derive(Serialize, Debug, Clone, JsonSchema)]
+pub struct VendorDeltaResponse {
+    pub vendor_id: Uuid,
+    pub vendor_name: String,
+    pub status: ComparisonStatus,
+    #[serde(flatten)]
+    pub amounts: AmountDeltaResponse,
+    pub top_transactions: Vec<ContributingTransactionResponse>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct PeriodComparisonResponse {
+    pub period: ComparedPeriodResponse,
+    /// One period, or the closed periods averaged into the baseline
+    pub baseline_periods: Vec<ComparedPeriodResponse>,
+    pub income: AmountDeltaResponse,
+    pub expense: AmountDeltaResponse,
+    /// Categories ordered by the size of their change
+    pub categories: Vec<CategoryDeltaResponse>,
+    /// Outgoing spend per vendor, ordered by the size of its change
+    pub vendors: Vec<VendorDeltaResponse>,
+}
+
+// ===== Comparison =====
+
+/// Sums per key for both sides, with the transactions behind each.
+struct Side<'a> {
+    total: i64,
+    transactions: Vec<&'a ComparisonTransaction>,
+}
+
+/// Compare a period with a baseline made of one or more periods. Baseline amounts are averaged over
+/// `baseline_periods`; Transfer transactions are ignored.
+pub fn compare_periods(
+    period: &ComparedPeriod,
+    baseline_periods: &[ComparedPeriod],
+    current: &[ComparisonTransaction],
+    baseline: &[ComparisonTransaction],
+) -> PeriodComparisonResponse {
+    let divisor = (baseline_periods.len() as i64).max(1);
+    let average = |total: i64| (total as f64 / divisor as f64).round() as i64;
+    let sum_of = |transactions: &[ComparisonTransaction], category_type: &str| -> i64 {
+        transactions
+            .iter()
+            .filter(|transaction| transaction.category_type == category_type)
+            .map(|transaction| transaction.amount)
+            .sum()
+    };
+
+    let mut categories: Vec<CategoryDeltaResponse> = group_deltas(current, baseline, |transaction| {
+        (transaction.category_type != "Transfer").then_some(transaction.category_id)
+    })
+    .into_iter()
+    .map(|(category_id, current_side, baseline_side)| {
+        let sample = current_side.transactions.first().or(baseline_side.transactions.first()).copied();
+        CategoryDeltaResponse {
+            category_id,
+            category_name: sample.map(|transaction| transaction.category_name.clone()).unwrap_or_default(),
+            category_type: sample.map(|transaction| transaction.category_type.clone()).unwrap_or_default(),
+            status: status_of(&current_side, &baseline_side, average(baseline_side.total)),
+            amounts: AmountDeltaResponse::new(current_side.total, average(baseline_side.total)),
+            top_transactions: top_contributors(&current_side, &baseline_side, average(baseline_side.total)),
+        }
+    })
+    .collect();
+
+    let mut vendors: Vec<VendorDeltaResponse> = group_deltas(current, baseline, |transaction| {
+        (transaction.category_type == "Outgoing").then_some(transaction.vendor_id).flatten()
+    })
+    .into_iter()
+    .map(|(vendor_id, current_side, baseline_side)| {
+        let sample = current_side.transactions.first().or(baseline_side.transactions.first()).copied();
+        VendorDeltaResponse {
+            vendor_id,
+            vendor_name: sample.and_then(|transaction| transaction.vendor_name.clone()).unwrap_or_default(),
+            status: status_of(&current_side, &baseline_side, average(baseline_side.total)),
+            amounts: AmountDeltaResponse::new(current_side.total, average(baseline_side.total)),
+            top_transactions: top_contributors(&current_side, &baseline_side, average(baseline_side.total)),
+        }
+    })
+    .collect();
+
+    categories.sort_by(|a, b| {
+        b.amounts
+            .delta
+            .abs()
+            .cmp(&a.amounts.delta.abs())
+            .then_with(|| a.category_name.cmp(&b.category_name))
+    });
+    vendors.sort_by(|a, b| {
+        b.amounts
+            .delta
+            .abs()
+            .cmp(&a.amounts.delta.abs())
+            .then_with(|| a.vendor_name.cmp(&b.vendor_name))
+    });
+
+    PeriodComparisonResponse {
+        period: ComparedPeriodResponse::from(period),
+        baseline_periods: baseline_periods.iter().map(ComparedPeriodResponse::from).collect(),
+        income: AmountDeltaResponse::new(sum_of(current, "Incoming"), average(sum_of(baseline, "Incoming"))),
+        expense: AmountDeltaResponse::new(sum_of(current, "Outgoing"), average(sum_of(baseline, "Outgoing"))),
+        categories,
+        vendors,
+    }
+}
+
+/// Group both sides by `key`.
+fn group_deltas<'a>(
+    current: &'a [ComparisonTransaction],
+    baseline: &'a [ComparisonTransaction],
+    key: impl Fn(&ComparisonTransaction) -> Option<Uuid>,
+) -> Vec<(Uuid, Side<'a>, Side<'a>)> {
+    let mut groups: HashMap<Uuid, (Side<'a>, Side<'a>)> = HashMap::new();
+    let empty = || Side {
+        total: 0,
+        transactions: Vec::new(),
+    };
+
+    for (transactions, is_baseline) in [(current, false), (baseline, true)] {
+        for transaction in transactions {
+            let Some(id) = key(transaction) else {
+                continue;
+            };
+            let (current_side, baseline_side) = groups.entry(id).or_insert_with(|| (empty(), empty()));
+            let side = if is_baseline { baseline_side } else { current_side };
+            side.total += transaction.amount;
+            side.transactions.push(transaction);
+        }
+    }
+
+    groups.into_iter().map(|(id, (current, baseline))| (id, current, baseline)).collect()
+}
+
+fn status_of(current: &Side, baseline: &Side, baseline_amount: i64) -> ComparisonStatus {
+    match (current.transactions.is_empty(), baseline.transactions.is_empty()) {
+        (false, true) => ComparisonStatus::New,
+        (true, false) => ComparisonStatus::Disappeared,
+        _ if current.total == baseline_amount => ComparisonStatus::Unchanged,
+        _ => ComparisonStatus::Changed,
+    }
+}
+
+fn top_contributors(current: &Side, baseline: &Side, baseline_amount: i64) -> Vec<ContributingTransactionResponse> {
+    let (side, from_baseline) = if current.total >= baseline_amount {
+        (current, false)
+    } else {
+        (baseline, true)
+    };
+
+    let mut transactions = side.transactions.clone();
+    transactions.sort_by(|a, b| b.amount.cmp(&a.amount).then_with(|| a.occurred_at.cmp(&b.occurred_at)));
+    transactions
+        .into_iter()
+        .take(TOP_CONTRIBUTING_TRANSACTIONS)
+        .map(|transaction| ContributingTransactionResponse {
+            id: transaction.id,
+            occurred_at: transaction.occurred_at,
+            description: transaction.description.clone(),
+            amount: transaction.amount,
+            from_baseline,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).expect("valid date")
+    }
+
+    fn period(name: &str, month: u32) -> ComparedPeriod {
+        ComparedPeriod {
+            id: Uuid::new_v4(),
+            name: name.to_string(),
+            start_date: date(2026, month, 1),
+            end_date: date(2026, month, 28),
+        }
+    }
+
+    fn transaction(category: (Uuid, &str, &str), vendor: Option<(Uuid, &str)>, amount: i64) -> ComparisonTransaction {
+        ComparisonTransaction {
+            id: Uuid::new_v4(),
+            occurred_at: date(2026, 3, 10),
+            description: format!("{} {amount}", category.1),
+            amount,
+            category_id: category.0,
+            category_name: category.1.to_string(),
+            category_type: category.2.to_string(),
+            vendor_id: vendor.map(|vendor| vendor.0),
+            vendor_name: vendor.map(|vendor| vendor.1.to_string()),
+        }
+    }
+
+    #[test]
+    fn comparison_reports_deltas_and_new_or_disappeared_categories() {
+        let groceries = (Uuid::new_v4(), "Groceries", "Outgoing");
+        let travel = (Uuid::new_v4(), "Travel", "Outgoing");
+        let gym = (Uuid::new_v4(), "Gym", "Outgoing");
+        let salary = (Uuid::new_v4(), "Salary", "Incoming");
+        let market = (Uuid::new_v4(), "Market");
+
+        let current = vec![
+            transaction(groceries, Some(market), 30_000),
+            transaction(groceries, Some(market), 5_000),
+            transaction(travel, None, 50_000),
+            transaction(salary, None, 300_000),
+        ];
+        let baseline = vec![
+            transaction(groceries, Some(market), 25_000),
+            transaction(gym, None, 4_000),
+            transaction(salary, None, 300_000),
+        ];
+
+        let comparison = compare_periods(&period("March", 3), &[period("February", 2)], &current, &baseline);
+
+        assert_eq!(comparison.expense, AmountDeltaResponse::new(85_000, 29_000));
+        assert_eq!(comparison.income.delta, 0);
+
+        let by_name = |name: &str| comparison.categories.iter().find(|category| category.category_name == name).expect("category");
+        assert_eq!(comparison.categories[0].category_name, "Travel");
+        assert_eq!(by_name("Travel").status, ComparisonStatus::New);
+        assert_eq!(by_name("Gym").status, ComparisonStatus::Disappeared);
+        assert!(by_name("Gym").top_transactions[0].from_baseline);
+        assert_eq!(by_name("Salary").status, ComparisonStatus::Unchanged);
+
+        let groceries_delta = by_name("Groceries");
+        assert_eq!(groceries_delta.amounts.delta, 10_000);
+        assert_eq!(groceries_delta.amounts.delta_basis_points, Some(4_000));
+        assert_eq!(
+            groceries_delta
+                .top_transactions
+                .iter()
+                .map(|transaction| transaction.amount)
+                .collect::<Vec<_>>(),
+            vec![30_000, 5_000]
+        );
+
+        assert_eq!(comparison.vendors.len(), 1);
+        assert_eq!(comparison.vendors[0].amounts.delta, 10_000);
+    }
+
+    #[test]
+    fn averaged_baseline_divides_by_period_count() {
+        let rent = (Uuid::new_v4(), "Rent", "Outgoing");
+        let baseline = vec![
+            transaction(rent, None, 90_000),
+            transaction(rent, None, 90_000),
+            transaction(rent, None, 93_000),
+        ];
+
+        let comparison = compare_periods(
+            &period("April", 4),
+            &[period("January", 1), period("February", 2), period("March", 3)],
+            &[transaction(rent, None, 93_000)],
+            &baseline,
+        );
+
+        assert_eq!(comparison.categories[0].amounts, AmountDeltaResponse::new(93_000, 91_000));
+        assert_eq!(comparison.categories[0].amounts.delta_basis_points, Some(219));
+    }
+}
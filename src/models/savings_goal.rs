@@ -0,0 +1,245 @@
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// Number of days of transfer history used to estimate the current contribution rate.
+pub const RECENT_CONTRIBUTION_WINDOW_DAYS: i64 = 90;
+
+const DAYS_PER_YEAR: i64 = 365;
+
+// ===== Enums =====
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum GoalTrackingMode {
+    /// Progress is the combined current balance of the linked accounts
+    Balance,
+    /// Progress is the net amount transferred into the linked accounts since `starts_on`
+    Contributions,
+}
+
+impl std::fmt::Display for GoalTrackingMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoalTrackingMode::Balance => write!(f, "balance"),
+            GoalTrackingMode::Contributions => write!(f, "contributions"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalStatus {
+    InProgress,
+    Completed,
+    Overdue,
+}
+
+// ===== Savings Goal Models =====
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SavingsGoal {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub target_amount: i64,
+    pub target_date: Option<NaiveDate>,
+    pub tracking_mode: GoalTrackingMode,
+    pub starts_on: NaiveDate,
+    pub account_ids: Vec<Uuid>,
+    pub current_amount: i64,
+    /// Net transfers into the linked accounts over the recent contribution window
+    pub recent_contributions: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct SavingsGoalRequest {
+    #[validate(length(min = 3))]
+    pub name: String,
+    pub description: Option<String>,
+    #[validate(range(min = 1))]
+    pub target_amount: i64,
+    pub target_date: Option<NaiveDate>,
+    pub tracking_mode: GoalTrackingMode,
+    /// First day contributions count towards the goal; defaults to today
+    pub starts_on: Option<NaiveDate>,
+    #[validate(length(min = 1))]
+    pub account_ids: Vec<Uuid>,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct SavingsGoalResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub target_amount: i64,
+    pub target_date: Option<NaiveDate>,
+    pub tracking_mode: GoalTrackingMode,
+    pub starts_on: NaiveDate,
+    pub account_ids: Vec<Uuid>,
+    pub current_amount: i64,
+    pub remaining_amount: i64,
+    /// Progress towards the target in basis points (10000 = 100%)
+    pub progress_basis_points: i64,
+    /// Average monthly contribution over the recent contribution window
+    pub monthly_contribution_rate: i64,
+    /// Monthly amount needed to reach the target by `target_date`
+    pub required_monthly_contribution: Option<i64>,
+    /// Date the target is reached at the recent contribution rate
+    pub projected_completion_date: Option<NaiveDate>,
+    /// Whether the projected completion lands on or before `target_date`
+    pub on_track: Option<bool>,
+    pub status: GoalStatus,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SavingsGoalResponse {
+    pub fn from_goal(goal: &SavingsGoal, today: NaiveDate) -> Self {
+        let remaining_amount = (goal.target_amount - goal.current_amount).max(0);
+        let completed = remaining_amount == 0;
+        let window_days = contribution_window_days(goal.starts_on, today);
+        let projected_completion_date = if completed {
+            None
+        } else {
+            projected_completion_date(remaining_amount, goal.recent_contributions, window_days, today)
+        };
+        let status = if completed {
+            GoalStatus::Completed
+        } else if goal.target_date.is_some_and(|target_date| target_date < today) {
+            GoalStatus::Overdue
+        } else {
+            GoalStatus::InProgress
+        };
+        let on_track = goal
+            .target_date
+            .map(|target_date| completed || projected_completion_date.is_some_and(|projected| projected <= target_date));
+
+        Self {
+            id: goal.id,
+            name: goal.name.clone(),
+            description: goal.description.clone(),
+            target_amount: goal.target_amount,
+            target_date: goal.target_date,
+            tracking_mode: goal.tracking_mode,
+            starts_on: goal.starts_on,
+            account_ids: goal.account_ids.clone(),
+            current_amount: goal.current_amount,
+            remaining_amount,
+            progress_basis_points: progress_basis_points(goal.current_amount, goal.target_amount),
+            monthly_contribution_rate: goal.recent_contributions * DAYS_PER_YEAR / (12 * window_days),
+            required_monthly_contribution: goal
+                .target_date
+                .map(|target_date| required_monthly_contribution(remaining_amount, target_date, today)),
+            projected_completion_date,
+            on_track,
+            status,
+            created_at: goal.created_at,
+        }
+    }
+}
+
+/// Number of days the recent contribution rate is measured over: the window, shortened for goals started within it.
+pub fn contribution_window_days(starts_on: NaiveDate, today: NaiveDate) -> i64 {
+    (today - starts_on).num_days().clamp(0, RECENT_CONTRIBUTION_WINDOW_DAYS - 1) + 1
+}
+
+/// First day of the recent contribution window ending on `today`.
+pub fn contribution_window_start(today: NaiveDate) -> NaiveDate {
+    today - Duration::days(RECENT_CONTRIBUTION_WINDOW_DAYS - 1)
+}
+
+/// Progress in basis points, capped at 100%.
+pub fn progress_basis_points(current_amount: i64, target_amount: i64) -> i64 {
+    if target_amount <= 0 {
+        return 10_000;
+    }
+    (current_amount.max(0).saturating_mul(10_000) / target_amount).min(10_000)
+}
+
+/// Amount to put aside each month to close `remaining_amount` by `target_date`.
+/// A deadline that is less than a month away (or already passed) requires the full remainder now.
+pub fn required_monthly_contribution(remaining_amount: i64, target_date: NaiveDate, today: NaiveDate) -> i64 {
+    let days_left = (target_date - today).num_days().max(0);
+    let months_left = (days_left * 12 / DAYS_PER_YEAR).max(1);
+    (remaining_amount + months_left - 1) / months_left
+}
+
+/// Date `remaining_amount` is covered if contributions keep coming in at the recent rate.
+pub fn projected_completion_date(remaining_amount: i64, recent_contributions: i64, window_days: i64, today: NaiveDate) -> Option<NaiveDate> {
+    if recent_contributions <= 0 {
+        return None;
+    }
+    let days_needed = (remaining_amount.saturating_mul(window_days) + recent_contributions - 1) / recent_contributions;
+    today.checked_add_signed(Duration::days(days_needed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn sample_goal(current_amount: i64, recent_contributions: i64, target_date: Option<NaiveDate>) -> SavingsGoal {
+        SavingsGoal {
+            id: Uuid::new_v4(),
+            name: "Vacation".to_string(),
+            description: None,
+            target_amount: 120_000,
+            target_date,
+            tracking_mode: GoalTrackingMode::Contributions,
+            starts_on: date(2026, 1, 1),
+            account_ids: vec![Uuid::new_v4()],
+            current_amount,
+            recent_contributions,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn required_monthly_contribution_spreads_remainder_over_months_left() {
+        assert_eq!(required_monthly_contribution(60_000, date(2026, 12, 31), date(2026, 7, 1)), 10_000);
+        assert_eq!(required_monthly_contribution(60_000, date(2026, 7, 10), date(2026, 7, 1)), 60_000);
+        assert_eq!(required_monthly_contribution(60_000, date(2026, 6, 1), date(2026, 7, 1)), 60_000);
+    }
+
+    #[test]
+    fn projected_completion_uses_recent_daily_rate() {
+        assert_eq!(projected_completion_date(9_000, 9_000, 90, date(2026, 7, 1)), Some(date(2026, 9, 29)));
+        assert_eq!(projected_completion_date(9_000, 0, 90, date(2026, 7, 1)), None);
+        assert_eq!(projected_completion_date(9_000, -500, 90, date(2026, 7, 1)), None);
+    }
+
+    #[test]
+    fn contribution_window_is_shortened_for_new_goals() {
+        assert_eq!(contribution_window_days(date(2026, 7, 1), date(2026, 7, 1)), 1);
+        assert_eq!(contribution_window_days(date(2026, 6, 22), date(2026, 7, 1)), 10);
+        assert_eq!(contribution_window_days(date(2025, 1, 1), date(2026, 7, 1)), RECENT_CONTRIBUTION_WINDOW_DAYS);
+    }
+
+    #[test]
+    fn goal_status_reflects_completion_and_deadline() {
+        let today = date(2026, 7, 1);
+
+        let completed = SavingsGoalResponse::from_goal(&sample_goal(130_000, 0, Some(date(2026, 6, 1))), today);
+        assert_eq!(completed.status, GoalStatus::Completed);
+        assert_eq!(completed.progress_basis_points, 10_000);
+        assert_eq!(completed.remaining_amount, 0);
+        assert_eq!(completed.on_track, Some(true));
+
+        let overdue = SavingsGoalResponse::from_goal(&sample_goal(60_000, 9_000, Some(date(2026, 6, 1))), today);
+        assert_eq!(overdue.status, GoalStatus::Overdue);
+        assert_eq!(overdue.on_track, Some(false));
+
+        let open_ended = SavingsGoalResponse::from_goal(&sample_goal(30_000, 0, None), today);
+        assert_eq!(open_ended.status, GoalStatus::InProgress);
+        assert_eq!(open_ended.progress_basis_points, 2_500);
+        assert_eq!(open_ended.required_monthly_contribution, None);
+        assert_eq!(open_ended.on_track, None);
+    }
+}
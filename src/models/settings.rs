@@ -6,6 +6,16 @@ use validator::{Validate, ValidationError};
 
 // ── Existing general settings ─────────────────────────────────────────────────
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default, JsonSchema, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetingMode {
+    #[default]
+    Standard,
+    /// Every unit of income is assigned to a category; what is left is ready to assign
+    ZeroBased,
+}
+
 #[derive(Serialize, Debug, Clone, sqlx::FromRow)]
 pub struct Settings {
     pub id: Uuid,
@@ -14,6 +24,11 @@ pub struct Settings {
     pub language: String,
     pub default_currency_id: Option<Uuid>,
     pub budget_stability_tolerance_basis_points: i32,
+    pub spend_limit_alert_thresholds: Vec<i32>,
+    pub spend_limit_email_alerts: bool,
+    pub category_alert_thresholds: Vec<i32>,
+    pub category_email_alerts: bool,
+    pub budgeting_mode: BudgetingMode,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -25,6 +40,11 @@ pub struct SettingsResponse {
     pub language: String,
     pub default_currency_id: Option<Uuid>,
     pub budget_stability_tolerance_basis_points: i32,
+    pub spend_limit_alert_thresholds: Vec<i32>,
+    pub spend_limit_email_alerts: bool,
+    pub category_alert_thresholds: Vec<i32>,
+    pub category_email_alerts: bool,
+    pub budgeting_mode: BudgetingMode,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -36,6 +56,11 @@ impl From<&Settings> for SettingsResponse {
             language: value.language.clone(),
             default_currency_id: value.default_currency_id,
             budget_stability_tolerance_basis_points: value.budget_stability_tolerance_basis_points,
+            spend_limit_alert_thresholds: value.spend_limit_alert_thresholds.clone(),
+            spend_limit_email_alerts: value.spend_limit_email_alerts,
+            category_alert_thresholds: value.category_alert_thresholds.clone(),
+            category_email_alerts: value.category_email_alerts,
+            budgeting_mode: value.budgeting_mode,
             updated_at: value.updated_at,
         }
     }
@@ -55,6 +80,29 @@ pub struct SettingsRequest {
 
     #[validate(range(min = 0, max = 10000))]
     pub budget_stability_tolerance_basis_points: Option<i32>,
+
+    /// Spend limit usage levels (basis points, e.g. 8000 = 80%) that raise an alert
+    #[validate(custom(function = "validate_alert_thresholds"))]
+    pub spend_limit_alert_thresholds: Option<Vec<i32>>,
+
+    pub spend_limit_email_alerts: Option<bool>,
+
+    /// Default share of a category's period target (basis points, e.g. 5000 = 50%) that raises an alert
+    #[validate(custom(function = "validate_alert_thresholds"))]
+    pub category_alert_thresholds: Option<Vec<i32>>,
+
+    /// Also email category budget alerts
+    pub category_email_alerts: Option<bool>,
+
+    /// Standard budgeting, or zero-based where every unit of income is assigned to a category
+    pub budgeting_mode: Option<BudgetingMode>,
+}
+
+pub(crate) fn validate_alert_thresholds(thresholds: &[i32]) -> Result<(), ValidationError> {
+    if thresholds.len() > 10 || thresholds.iter().any(|threshold| !(1..=50000).contains(threshold)) {
+        return Err(ValidationError::new("invalid_alert_thresholds"));
+    }
+    Ok(())
 }
 
 // ── Profile ───────────────────────────────────────────────────────────────────
@@ -60,6 +60,29 @@ impl From<&Vendor> for VendorResponse {
     }
 }
 
+/// Merge a vendor into another one
+#[derive(Deserialize, Debug, JsonSchema)]
+pub struct VendorMergeRequest {
+    /// Vendor that takes over the transactions, rules and aliases of the merged vendor
+    pub destination_id: Uuid,
+    /// Keep the merged vendor as archived instead of deleting it
+    #[serde(default)]
+    pub archive_source: bool,
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct VendorMergeResponse {
+    pub destination: VendorResponse,
+    pub transactions: i64,
+    /// Overlays whose rules named the merged vendor
+    pub overlay_rules: i64,
+    /// Overlay templates whose rules named the merged vendor
+    pub overlay_templates: i64,
+    pub aliases: i64,
+    /// `false` when the merged vendor was deleted
+    pub source_archived: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct VendorWithStats {
     pub vendor: Vendor,
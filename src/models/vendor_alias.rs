@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use rocket::serde::{Deserialize, Serialize};
+use schemars::JsonSchema;
+use uuid::Uuid;
+use validator::Validate;
+
+/// How an alias is compared with a transaction description. Both ignore case and surrounding whitespace.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, JsonSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+pub enum VendorAliasMatchType {
+    /// The whole description equals the alias
+    Exact,
+    /// The whole description matches the alias, where `*` stands for any text and `?` for one character
+    Pattern,
+}
+
+/// A raw description, as it appears on card statements, that stands for a vendor
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct VendorAlias {
+    pub id: Uuid,
+    pub vendor_id: Uuid,
+    pub pattern: String,
+    pub match_type: VendorAliasMatchType,
+    pub created_at: DateTime<Utc>,
+}
+
+impl VendorAlias {
+    pub fn matches(&self, description: &str) -> bool {
+        let description = description.trim().to_lowercase();
+        let pattern = self.pattern.trim().to_lowercase();
+        match self.match_type {
+            VendorAliasMatchType::Exact => description == pattern,
+            VendorAliasMatchType::Pattern => glob_matches(&pattern.chars().collect::<Vec<_>>(), &description.chars().collect::<Vec<_>>()),
+        }
+    }
+
+    /// Number of literal characters; the more of them an alias has, the more specific it is.
+    fn specificity(&self) -> usize {
+        match self.match_type {
+            VendorAliasMatchType::Exact => usize::MAX,
+            VendorAliasMatchType::Pattern => self.pattern.trim().chars().filter(|c| *c != '*' && *c != '?').count(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Validate, JsonSchema)]
+pub struct VendorAliasRequest {
+    #[validate(length(min = 1, max = 200), custom(function = "validate_alias_pattern"))]
+    pub pattern: String,
+    pub match_type: VendorAliasMatchType,
+}
+
+fn validate_alias_pattern(pattern: &str) -> Result<(), validator::ValidationError> {
+    if pattern.trim().chars().all(|c| c == '*' || c == '?') {
+        return Err(validator::ValidationError::new("alias_pattern_must_contain_text"));
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Debug, JsonSchema)]
+pub struct VendorAliasResponse {
+    pub id: Uuid,
+    pub vendor_id: Uuid,
+    pub pattern: String,
+    pub match_type: VendorAliasMatchType,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<&VendorAlias> for VendorAliasResponse {
+    fn from(alias: &VendorAlias) -> Self {
+        Self {
+            id: alias.id,
+            vendor_id: alias.vendor_id,
+            pattern: alias.pattern.clone(),
+            match_type: alias.match_type,
+            created_at: alias.created_at,
+        }
+    }
+}
+
+/// The vendor a description stands for. Exact aliases win over patterns, and a pattern with more literal
+/// characters over a looser one; ties go to the alias listed first.
+pub fn resolve_vendor_alias<'a>(aliases: &'a [VendorAlias], description: &str) -> Option<&'a VendorAlias> {
+    aliases
+        .iter()
+        .filter(|alias| alias.matches(description))
+        .fold(None, |best: Option<&VendorAlias>, alias| match best {
+            Some(best) if best.specificity() >= alias.specificity() => Some(best),
+            _ => Some(alias),
+        })
+}
+
+fn glob_matches(pattern: &[char], text: &[char]) -> bool {
+    // Iterative matching with backtracking to the last `*`
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    p = star_p + 1;
+                    t = star_t + 1;
+                    star = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(pattern: &str, match_type: VendorAliasMatchType) -> VendorAlias {
+        VendorAlias {
+            id: Uuid::new_v4(),
+            vendor_id: Uuid::new_v4(),
+            pattern: pattern.to_string(),
+            match_type,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn aliases_match_ignoring_case_and_whitespace() {
+        let exact = alias("Amazon.de", VendorAliasMatchType::Exact);
+        assert!(exact.matches("  AMAZON.DE "));
+        assert!(!exact.matches("Amazon.de Marketplace"));
+
+        let pattern = alias("AMZN Mktp*", VendorAliasMatchType::Pattern);
+        assert!(pattern.matches("amzn mktp DE*2X4Y"));
+        assert!(pattern.matches("AMZN Mktp"));
+        assert!(!pattern.matches("Paypal *AMZN Mktp"));
+
+        let wildcards = alias("*amazon ?u*", VendorAliasMatchType::Pattern);
+        assert!(wildcards.matches("PAYPAL AMAZON EU SARL"));
+        assert!(!wildcards.matches("AMAZON DE"));
+    }
+
+    #[test]
+    fn the_most_specific_alias_resolves_the_vendor() {
+        let aliases = vec![
+            alias("amazon*", VendorAliasMatchType::Pattern),
+            alias("amazon prime*", VendorAliasMatchType::Pattern),
+            alias("Amazon Prime Video", VendorAliasMatchType::Exact),
+        ];
+
+        assert_eq!(resolve_vendor_alias(&aliases, "Amazon EU").map(|a| a.id), Some(aliases[0].id));
+        assert_eq!(resolve_vendor_alias(&aliases, "Amazon Prime Membership").map(|a| a.id), Some(aliases[1].id));
+        assert_eq!(resolve_vendor_alias(&aliases, "amazon prime video").map(|a| a.id), Some(aliases[2].id));
+        assert!(resolve_vendor_alias(&aliases, "Netflix").is_none());
+    }
+}
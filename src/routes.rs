@@ -1,14 +1,21 @@
 pub mod account;
+pub mod allowance_funding;
 pub mod budget_category;
 pub mod budget_period;
+pub mod budget_template;
 pub mod category;
+pub mod category_alert;
 pub mod category_target;
 pub mod currency;
 pub mod dashboard;
 pub mod error;
 pub mod health;
+pub mod holiday_calendar;
+pub mod notification;
 pub mod overlay;
+pub mod overlay_template;
 pub mod password_reset;
+pub mod savings_goal;
 pub mod settings;
 pub mod transaction;
 pub mod two_factor;
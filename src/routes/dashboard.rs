@@ -2,12 +2,15 @@ use crate::auth::CurrentUser;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
 use crate::middleware::rate_limit::RateLimit;
+use crate::models::cash_flow::{CASH_FLOW_HORIZON_DAYS, CashFlowForecastResponse};
 use crate::models::dashboard::{
     BudgetPerDayResponse, BudgetStabilityResponse, MonthProgressResponse, MonthlyBurnInResponse, NetPositionResponse, SpentPerCategoryListResponse,
     TotalAssetsResponse,
 };
+use crate::models::forecast::SpendForecastResponse;
 use crate::models::pagination::{CursorParams, TransactionFilters};
 use crate::models::transaction::TransactionResponse;
+use chrono::Utc;
 use rocket::serde::json::Json;
 use rocket::{State, get};
 use rocket_okapi::openapi;
@@ -82,6 +85,47 @@ pub async fn get_month_progress(
     Ok(Json(repo.month_progress(&budget_period_uuid, &current_user.id).await?))
 }
 
+/// Forecast end-of-period spending per category and in total, with the projected variance against targets.
+/// Projections blend the current burn rate with spending curves of recent closed periods, and never fall
+/// below recurring items that are still expected this period.
+/// Returns 400 if `period_id` is missing ("Missing period_id query parameter") or invalid.
+#[openapi(tag = "Dashboard")]
+#[get("/spend-forecast?<period_id>")]
+pub async fn get_spend_forecast(
+    pool: &State<PgPool>,
+    _rate_limit: RateLimit,
+    current_user: CurrentUser,
+    period_id: Option<String>,
+) -> Result<Json<SpendForecastResponse>, AppError> {
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let budget_period_uuid = parse_period_id(period_id)?;
+    let today = Utc::now().date_naive();
+    Ok(Json(repo.spend_forecast(&budget_period_uuid, today, &current_user.id).await?))
+}
+
+/// Project daily balances of every cash account over the next `days` days (30, 60 or 90; default 30).
+/// Starts from today's balance and applies future-dated and recurring transactions, upcoming allowance
+/// funding runs and the average daily discretionary spend. Flags the first date an account drops below
+/// zero and below `floor` (default 0).
+/// Returns 400 if `days` is not one of the supported horizons.
+#[openapi(tag = "Dashboard")]
+#[get("/cash-flow-forecast?<days>&<floor>")]
+pub async fn get_cash_flow_forecast(
+    pool: &State<PgPool>,
+    _rate_limit: RateLimit,
+    current_user: CurrentUser,
+    days: Option<i64>,
+    floor: Option<i64>,
+) -> Result<Json<CashFlowForecastResponse>, AppError> {
+    let days = days.unwrap_or(CASH_FLOW_HORIZON_DAYS[0]);
+    if !CASH_FLOW_HORIZON_DAYS.contains(&days) {
+        return Err(AppError::BadRequest("days must be one of 30, 60 or 90".to_string()));
+    }
+    let repo = PostgresRepository { pool: pool.inner().clone() };
+    let today = Utc::now().date_naive();
+    Ok(Json(repo.cash_flow_forecast(today, days, floor.unwrap_or(0), &current_user.id).await?))
+}
+
 /// Get recent transactions for a budget period.
 /// Returns 400 if `period_id` is missing ("Missing period_id query parameter") or invalid.
 #[openapi(tag = "Dashboard")]
@@ -140,6 +184,8 @@ pub fn routes() -> (Vec<rocket::Route>, okapi::openapi3::OpenApi) {
         get_spent_per_category,
         get_monthly_burn_in,
         get_month_progress,
+        get_spend_forecast,
+        get_cash_flow_forecast,
         get_recent_transactions,
         get_total_assets,
         get_budget_stability,
@@ -1,3 +1,4 @@
+use crate::Config;
 use crate::auth::CurrentUser;
 use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
@@ -6,6 +7,7 @@ use crate::middleware::rate_limit::RateLimit;
 use crate::models::pagination::{CursorPaginatedResponse, CursorParams, TransactionDirection, TransactionFilters};
 use crate::models::transaction::{TransactionRequest, TransactionResponse};
 use crate::models::transaction_summary::TransactionSummaryResponse;
+use crate::service::alert::AlertService;
 use chrono::NaiveDate;
 use rocket::http::Status;
 use rocket::serde::json::Json;
@@ -20,6 +22,7 @@ use validator::Validate;
 #[post("/", data = "<payload>")]
 pub async fn create_transaction(
     pool: &State<PgPool>,
+    config: &State<Config>,
     _rate_limit: RateLimit,
     current_user: CurrentUser,
     payload: JsonBody<TransactionRequest>,
@@ -28,6 +31,7 @@ pub async fn create_transaction(
 
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let tx = repo.create_transaction(&payload, &current_user.id).await?;
+    raise_alerts(&repo, config, &current_user.id, tx.occurred_at).await;
 
     Ok((Status::Created, Json(TransactionResponse::from(&tx))))
 }
@@ -150,6 +154,7 @@ pub async fn delete_transaction(pool: &State<PgPool>, _rate_limit: RateLimit, cu
 #[put("/<id>", data = "<payload>")]
 pub async fn put_transaction(
     pool: &State<PgPool>,
+    config: &State<Config>,
     _rate_limit: RateLimit,
     current_user: CurrentUser,
     id: &str,
@@ -158,9 +163,19 @@ pub async fn put_transaction(
     let repo = PostgresRepository { pool: pool.inner().clone() };
     let uuid = Uuid::parse_str(id).map_err(|e| AppError::uuid("Invalid transaction id", e))?;
     let tx = repo.update_transaction(&uuid, &payload, &current_user.id).await?;
+    raise_alerts(&repo, config, &current_user.id, tx.occurred_at).await;
     Ok(Json(TransactionResponse::from(&tx)))
 }
 
+/// Alerts are best-effort: a failure to evaluate them must not fail the transaction write.
+/// They are evaluated for the period and overlays covering the transaction's date, which may not be today.
+async fn raise_alerts(repo: &PostgresRepository, config: &Config, user_id: &Uuid, occurred_at: NaiveDate) {
+    let alerts = AlertService::new(repo, &config.email);
+    if let Err(err) = alerts.evaluate(user_id, occurred_at).await {
+        tracing::warn!("Failed to evaluate alerts: {:?}", err);
+    }
+}
+
 pub fn routes() -> (Vec<rocket::Route>, okapi::openapi3::OpenApi) {
     rocket_okapi::openapi_get_routes_spec![
         create_transaction,
@@ -1,4 +1,5 @@
 pub mod account;
+pub mod alert;
 pub mod dashboard;
 pub mod email;
 mod service_util;
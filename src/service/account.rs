@@ -1,6 +1,6 @@
 use crate::database::postgres_repository::PostgresRepository;
 use crate::error::app_error::AppError;
-use crate::models::account::{AccountBalancePerDay, AccountListResponse, AccountWithMetrics};
+use crate::models::account::{AccountBalancePerDay, AccountListResponse, AccountWithMetrics, spend_limit_remaining};
 use crate::models::currency::CurrencyResponse;
 use crate::models::dashboard::BudgetPerDayResponse;
 use crate::models::pagination::CursorParams;
@@ -52,6 +52,8 @@ fn account_responses(accounts: &[AccountWithMetrics], balance_per_day: &[Account
                 balance_per_day: per_day,
                 balance_change_this_period: account.balance_change_this_period,
                 transaction_count: account.transaction_count,
+                spent_this_period: account.spent_this_period,
+                spend_limit_remaining: spend_limit_remaining(account_data.spend_limit, account.spent_this_period),
             }
         })
         .collect()
@@ -83,6 +85,7 @@ mod tests {
             account: account.clone(),
             current_balance: 1200,
             balance_change_this_period: 200,
+            spent_this_period: 0,
             transaction_count: 2,
         };
         let balances = vec![
@@ -115,6 +118,7 @@ mod tests {
             account: account.clone(),
             current_balance: 1000,
             balance_change_this_period: 0,
+            spent_this_period: 0,
             transaction_count: 0,
         };
         let responses = account_responses(&[metrics], &[]);
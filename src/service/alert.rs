@@ -0,0 +1,371 @@
+use crate::config::EmailConfig;
+use crate::database::postgres_repository::PostgresRepository;
+use crate::error::app_error::AppError;
+use crate::models::account::{AccountSpendInPeriod, spend_limit_usage_basis_points};
+use crate::models::category_alert::CategorySpendInPeriod;
+use crate::models::notification::{NewNotification, NotificationKind};
+use crate::models::overlay::{OverlayCapSpend, cap_usage_basis_points};
+use crate::service::email::EmailService;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+const DEFAULT_SPEND_LIMIT_THRESHOLDS: [i32; 2] = [8000, 10000];
+
+/// How many alerts an evaluation raised, by kind.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RaisedAlerts {
+    pub spend_limits: usize,
+    pub category_budgets: usize,
+    pub overlay_caps: usize,
+}
+
+impl RaisedAlerts {
+    pub fn total(&self) -> usize {
+        self.spend_limits + self.category_budgets + self.overlay_caps
+    }
+}
+
+pub struct AlertService<'a> {
+    repository: &'a PostgresRepository,
+    email_config: &'a EmailConfig,
+}
+
+impl<'a> AlertService<'a> {
+    pub fn new(repository: &'a PostgresRepository, email_config: &'a EmailConfig) -> Self {
+        AlertService { repository, email_config }
+    }
+
+    /// Raises every kind of alert for the budget period and overlays covering `date`.
+    pub async fn evaluate(&self, user_id: &Uuid, date: NaiveDate) -> Result<RaisedAlerts, AppError> {
+        Ok(RaisedAlerts {
+            spend_limits: self.evaluate_spend_limits(user_id, date).await?,
+            category_budgets: self.evaluate_category_budgets(user_id, date).await?,
+            overlay_caps: self.evaluate_overlay_caps(user_id, date).await?,
+        })
+    }
+
+    /// Raises spend limit alerts for the budget period covering `date`.
+    /// Each (account, period, threshold) alert is stored once; returns how many new alerts were raised.
+    pub async fn evaluate_spend_limits(&self, user_id: &Uuid, date: NaiveDate) -> Result<usize, AppError> {
+        let (thresholds, email_alerts) = match self.repository.get_settings(user_id).await {
+            Ok(settings) => (settings.spend_limit_alert_thresholds, settings.spend_limit_email_alerts),
+            Err(AppError::NotFound(_)) => (DEFAULT_SPEND_LIMIT_THRESHOLDS.to_vec(), true),
+            Err(err) => return Err(err),
+        };
+
+        let accounts = self.repository.list_account_spend_for_date(user_id, date).await?;
+        let mut raised = 0;
+
+        for account in &accounts {
+            let usage = spend_limit_usage_basis_points(account.spend_limit, account.spent);
+            let mut highest_new_threshold = None;
+
+            for threshold in crossed_thresholds(usage, &thresholds) {
+                let notification = spend_limit_notification(account, threshold);
+                if self.repository.create_notification(&notification, user_id).await? {
+                    raised += 1;
+                    highest_new_threshold = Some(threshold);
+                }
+            }
+
+            if let Some(threshold) = highest_new_threshold
+                && email_alerts
+            {
+                self.send_alert_email(user_id, &spend_limit_notification(account, threshold)).await;
+            }
+        }
+
+        Ok(raised)
+    }
+
+    /// Raises alerts for Outgoing categories whose spending reached a threshold of their period target.
+    /// Each (category, period, threshold) alert is stored once; returns how many new alerts were raised.
+    pub async fn evaluate_category_budgets(&self, user_id: &Uuid, date: NaiveDate) -> Result<usize, AppError> {
+        let email_alerts = match self.repository.get_settings(user_id).await {
+            Ok(settings) => settings.category_email_alerts,
+            Err(AppError::NotFound(_)) => false,
+            Err(err) => return Err(err),
+        };
+
+        let categories = self.repository.list_category_spend_for_date(user_id, date).await?;
+        let mut raised = 0;
+
+        for category in &categories {
+            let usage = spend_limit_usage_basis_points(category.target, category.spent);
+            let mut highest_new_notification = None;
+
+            for threshold in crossed_thresholds(usage, &category.thresholds) {
+                let notification = category_budget_notification(category, threshold);
+                if self.repository.create_notification(&notification, user_id).await? {
+                    raised += 1;
+                    highest_new_notification = Some(notification);
+                }
+            }
+
+            if let Some(notification) = highest_new_notification
+                && email_alerts
+            {
+                self.send_alert_email(user_id, &notification).await;
+            }
+        }
+
+        Ok(raised)
+    }
+
+    /// Raises in-app alerts for overlay caps whose spending reached one of the overlay's thresholds.
+    /// Each (overlay, cap, threshold) alert is stored once; returns how many new alerts were raised.
+    pub async fn evaluate_overlay_caps(&self, user_id: &Uuid, date: NaiveDate) -> Result<usize, AppError> {
+        let caps = self.repository.list_overlay_cap_spend_for_date(user_id, date).await?;
+        let mut raised = 0;
+
+        for cap in &caps {
+            let usage = cap_usage_basis_points(cap.cap_amount, cap.spent);
+            for threshold in crossed_thresholds(usage, &cap.thresholds) {
+                if self.repository.create_notification(&overlay_cap_notification(cap, threshold), user_id).await? {
+                    raised += 1;
+                }
+            }
+        }
+
+        Ok(raised)
+    }
+
+    async fn send_alert_email(&self, user_id: &Uuid, notification: &NewNotification) {
+        let user = match self.repository.get_user_by_id(user_id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return,
+            Err(err) => {
+                tracing::warn!("Failed to load user for {} alert email: {:?}", notification.kind, err);
+                return;
+            }
+        };
+
+        let email_service = EmailService::new(self.email_config.clone());
+        if let Err(err) = email_service
+            .send_alert_email(&user.email, &user.name, &notification.title, &notification.title, &notification.message)
+            .await
+        {
+            tracing::warn!("Failed to send {} alert email: {:?}", notification.kind, err);
+        }
+    }
+}
+
+/// Thresholds (basis points) that the given usage has reached, in ascending order.
+fn crossed_thresholds(usage_basis_points: i64, thresholds: &[i32]) -> Vec<i32> {
+    let mut crossed: Vec<i32> = thresholds
+        .iter()
+        .copied()
+        .filter(|threshold| usage_basis_points >= i64::from(*threshold))
+        .collect();
+    crossed.sort_unstable();
+    crossed.dedup();
+    crossed
+}
+
+fn spend_limit_notification(account: &AccountSpendInPeriod, threshold: i32) -> NewNotification {
+    let percent = format_basis_points_as_percent(threshold);
+    let title = if threshold >= 10_000 {
+        format!("{} reached {} of its spend limit", account.account_name, percent)
+    } else {
+        format!("{} is at {} of its spend limit", account.account_name, percent)
+    };
+    let message = format!(
+        "You have spent {} of the {} limit on {} in {}.",
+        format_amount(account.spent, account.currency_decimal_places, &account.currency_symbol),
+        format_amount(i64::from(account.spend_limit), account.currency_decimal_places, &account.currency_symbol),
+        account.account_name,
+        account.period_name
+    );
+
+    NewNotification {
+        kind: NotificationKind::SpendLimit,
+        dedupe_key: format!("spend_limit:{}:{}:{}", account.account_id, account.period_id, threshold),
+        title,
+        message,
+        metadata: serde_json::json!({
+            "account_id": account.account_id,
+            "period_id": account.period_id,
+            "threshold_basis_points": threshold,
+            "spent": account.spent,
+            "spend_limit": account.spend_limit,
+        }),
+    }
+}
+
+fn category_budget_notification(category: &CategorySpendInPeriod, threshold: i32) -> NewNotification {
+    let percent = format_basis_points_as_percent(threshold);
+    let title = if threshold >= 10_000 {
+        format!("{} reached {} of its budget", category.category_name, percent)
+    } else {
+        format!("{} is at {} of its budget", category.category_name, percent)
+    };
+    let message = format!(
+        "You have spent {} of the {} budgeted for {} in {}.",
+        format_amount(category.spent, category.currency_decimal_places, &category.currency_symbol),
+        format_amount(i64::from(category.target), category.currency_decimal_places, &category.currency_symbol),
+        category.category_name,
+        category.period_name
+    );
+
+    NewNotification {
+        kind: NotificationKind::CategoryBudget,
+        dedupe_key: format!("category_budget:{}:{}:{}", category.category_id, category.period_id, threshold),
+        title,
+        message,
+        metadata: serde_json::json!({
+            "category_id": category.category_id,
+            "period_id": category.period_id,
+            "threshold_basis_points": threshold,
+            "spent": category.spent,
+            "target": category.target,
+        }),
+    }
+}
+
+fn overlay_cap_notification(cap: &OverlayCapSpend, threshold: i32) -> NewNotification {
+    let percent = format_basis_points_as_percent(threshold);
+    let cap_name = match &cap.category_name {
+        Some(category_name) => format!("{} in {}", category_name, cap.overlay_name),
+        None => cap.overlay_name.clone(),
+    };
+    let title = if threshold >= 10_000 {
+        format!("{} reached {} of its cap", cap_name, percent)
+    } else {
+        format!("{} is at {} of its cap", cap_name, percent)
+    };
+    let message = format!(
+        "You have spent {} of the {} cap for {}.",
+        format_amount(cap.spent, cap.currency_decimal_places, &cap.currency_symbol),
+        format_amount(cap.cap_amount, cap.currency_decimal_places, &cap.currency_symbol),
+        cap_name
+    );
+    let cap_key = cap.category_id.map_or_else(|| "total".to_string(), |category_id| category_id.to_string());
+
+    NewNotification {
+        kind: NotificationKind::OverlayCap,
+        dedupe_key: format!("overlay_cap:{}:{}:{}", cap.overlay_id, cap_key, threshold),
+        title,
+        message,
+        metadata: serde_json::json!({
+            "overlay_id": cap.overlay_id,
+            "category_id": cap.category_id,
+            "threshold_basis_points": threshold,
+            "spent": cap.spent,
+            "cap_amount": cap.cap_amount,
+        }),
+    }
+}
+
+fn format_basis_points_as_percent(basis_points: i32) -> String {
+    if basis_points % 100 == 0 {
+        format!("{}%", basis_points / 100)
+    } else {
+        format!("{:.2}%", f64::from(basis_points) / 100.0)
+    }
+}
+
+fn format_amount(cents: i64, decimal_places: i32, symbol: &str) -> String {
+    let places = decimal_places.clamp(0, 6) as usize;
+    let value = cents as f64 / 10_f64.powi(places as i32);
+    format!("{}{:.*}", symbol, places, value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossed_thresholds_returns_reached_levels_in_order() {
+        assert_eq!(crossed_thresholds(8500, &[10000, 8000]), vec![8000]);
+        assert_eq!(crossed_thresholds(10000, &[8000, 10000, 8000]), vec![8000, 10000]);
+        assert!(crossed_thresholds(7999, &[8000, 10000]).is_empty());
+    }
+
+    #[test]
+    fn usage_with_zero_limit_counts_as_exceeded() {
+        assert!(crossed_thresholds(spend_limit_usage_basis_points(0, 1), &[10000]).contains(&10000));
+        assert!(crossed_thresholds(spend_limit_usage_basis_points(0, 0), &[10000]).is_empty());
+    }
+
+    #[test]
+    fn notification_dedupe_key_is_scoped_to_period_and_threshold() {
+        let account = AccountSpendInPeriod {
+            account_id: Uuid::new_v4(),
+            account_name: "Groceries card".to_string(),
+            spend_limit: 10_000,
+            spent: 8_250,
+            currency_symbol: "€".to_string(),
+            currency_decimal_places: 2,
+            period_id: Uuid::new_v4(),
+            period_name: "March".to_string(),
+        };
+
+        let notification = spend_limit_notification(&account, 8000);
+        assert_eq!(
+            notification.dedupe_key,
+            format!("spend_limit:{}:{}:8000", account.account_id, account.period_id)
+        );
+        assert_eq!(notification.title, "Groceries card is at 80% of its spend limit");
+        assert_eq!(notification.message, "You have spent €82.50 of the €100.00 limit on Groceries card in March.");
+    }
+
+    #[test]
+    fn category_budget_notification_is_scoped_to_period_and_threshold() {
+        let category = CategorySpendInPeriod {
+            category_id: Uuid::new_v4(),
+            category_name: "Dining out".to_string(),
+            target: 20_000,
+            spent: 24_000,
+            thresholds: vec![5000, 8000, 10000, 12000],
+            currency_symbol: "€".to_string(),
+            currency_decimal_places: 2,
+            period_id: Uuid::new_v4(),
+            period_name: "March".to_string(),
+        };
+
+        let usage = spend_limit_usage_basis_points(category.target, category.spent);
+        assert_eq!(crossed_thresholds(usage, &category.thresholds), vec![5000, 8000, 10000, 12000]);
+
+        let notification = category_budget_notification(&category, 12000);
+        assert_eq!(
+            notification.dedupe_key,
+            format!("category_budget:{}:{}:12000", category.category_id, category.period_id)
+        );
+        assert_eq!(notification.title, "Dining out reached 120% of its budget");
+        assert_eq!(notification.message, "You have spent €240.00 of the €200.00 budgeted for Dining out in March.");
+    }
+
+    #[test]
+    fn overlay_cap_notification_is_scoped_to_cap_and_threshold() {
+        let category_id = Uuid::new_v4();
+        let cap = OverlayCapSpend {
+            overlay_id: Uuid::new_v4(),
+            overlay_name: "Lisbon trip".to_string(),
+            category_id: Some(category_id),
+            category_name: Some("Dining out".to_string()),
+            cap_amount: 30_000,
+            spent: 25_500,
+            thresholds: vec![8000, 10000],
+            currency_symbol: "€".to_string(),
+            currency_decimal_places: 2,
+        };
+
+        let usage = cap_usage_basis_points(cap.cap_amount, cap.spent);
+        assert_eq!(crossed_thresholds(usage, &cap.thresholds), vec![8000]);
+
+        let notification = overlay_cap_notification(&cap, 8000);
+        assert_eq!(notification.dedupe_key, format!("overlay_cap:{}:{}:8000", cap.overlay_id, category_id));
+        assert_eq!(notification.title, "Dining out in Lisbon trip is at 80% of its cap");
+        assert_eq!(notification.message, "You have spent €255.00 of the €300.00 cap for Dining out in Lisbon trip.");
+
+        let total = OverlayCapSpend {
+            category_id: None,
+            category_name: None,
+            ..cap
+        };
+        assert_eq!(
+            overlay_cap_notification(&total, 10000).dedupe_key,
+            format!("overlay_cap:{}:total:10000", total.overlay_id)
+        );
+    }
+}
@@ -921,6 +921,88 @@ PiggyPulse Security Team
         )
     }
 
+    /// Send a budgeting alert (spend limits, thresholds) as a short notification email
+    pub async fn send_alert_email(&self, to_email: &str, to_name: &str, subject: &str, title: &str, message: &str) -> Result<(), AppError> {
+        if !self.config.enabled {
+            tracing::warn!("Email service is disabled, skipping alert email to {}", to_email);
+            return Ok(());
+        }
+
+        let html_body = self.generate_alert_html(to_name, title, message);
+        let text_body = self.generate_alert_text(to_name, title, message);
+
+        self.send_email(to_email, subject, &html_body, &text_body).await
+    }
+
+    /// Generate HTML version of an alert email. Titles and messages carry account, category and overlay names, so
+    /// everything is escaped.
+    fn generate_alert_html(&self, to_name: &str, title: &str, message: &str) -> String {
+        let (to_name, title, message) = (escape_html(to_name), escape_html(title), escape_html(message));
+        format!(
+            r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>{}</title>
+    <style>
+        body {{ font-family: Inter, -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, Arial, sans-serif; margin: 0; padding: 0; background-color: #FAFBFC; color: #141517; line-height: 1.6; }}
+        .wrapper {{ width: 100%; background-color: #FAFBFC; padding: 28px 12px; }}
+        .card {{ width: 100%; max-width: 640px; margin: 0 auto; background-color: #FFFFFF; border: 1px solid rgba(0,0,0,0.08); border-radius: 16px; overflow: hidden; box-shadow: 0 8px 24px rgba(20,21,23,0.08); }}
+        .brand-header {{ background-color: #FFFFFF; border-bottom: 1px solid rgba(0,0,0,0.08); padding: 24px; }}
+        .brand-title {{ margin: 0; font-size: 26px; font-weight: 700; color: #4FD1FF; }}
+        .content {{ padding: 28px 24px 16px; }}
+        .eyebrow {{ margin: 0 0 8px; color: #5C5F66; font-size: 12px; text-transform: uppercase; letter-spacing: 0.08em; font-weight: 700; }}
+        .title {{ margin: 0 0 14px; color: #141517; font-size: 28px; font-weight: 700; }}
+        .body-text {{ margin: 0 0 14px; color: #2E3035; font-size: 15px; }}
+        .footer {{ border-top: 1px solid rgba(0,0,0,0.08); padding: 18px 24px 24px; background-color: #FAFBFC; }}
+        .footer-text {{ margin: 0 0 10px; color: #5C5F66; font-size: 12px; }}
+        .footer-signoff {{ margin: 0; color: #2E3035; font-size: 12px; font-weight: 600; }}
+    </style>
+</head>
+<body>
+    <table role="presentation" class="wrapper" width="100%" cellspacing="0" cellpadding="0" border="0">
+      <tr><td align="center">
+        <table role="presentation" class="card" width="640" cellspacing="0" cellpadding="0" border="0">
+          <tr><td class="brand-header"><p class="brand-title">PiggyPulse</p></td></tr>
+          <tr><td class="content">
+            <p class="eyebrow">Budget Alert</p>
+            <h1 class="title">{}</h1>
+            <p class="body-text">Hi {},</p>
+            <p class="body-text">{}</p>
+          </td></tr>
+          <tr><td class="footer">
+            <p class="footer-text">You can change alert thresholds or turn off alert emails in your PiggyPulse settings.</p>
+            <p class="footer-signoff">PiggyPulse</p>
+          </td></tr>
+        </table>
+      </td></tr>
+    </table>
+</body>
+</html>"##,
+            title, title, to_name, message
+        )
+    }
+
+    /// Generate plain text version of an alert email
+    fn generate_alert_text(&self, to_name: &str, title: &str, message: &str) -> String {
+        format!(
+            r#"PiggyPulse | Budget Alert
+
+{}
+
+Hi {},
+
+{}
+
+You can change alert thresholds or turn off alert emails in your PiggyPulse settings.
+
+PiggyPulse
+"#,
+            title, to_name, message
+        )
+    }
+
     /// Send an email using SMTP
     async fn send_email(&self, to_email: &str, subject: &str, html_body: &str, text_body: &str) -> Result<(), AppError> {
         // Build the email message
@@ -960,6 +1042,21 @@ PiggyPulse Security Team
     }
 }
 
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1051,4 +1148,48 @@ mod tests {
         assert!(text.contains("15 minutes"));
         assert!(text.contains("significantly less secure"));
     }
+
+    #[test]
+    fn test_generate_alert_email() {
+        let config = EmailConfig {
+            smtp_host: "localhost".to_string(),
+            smtp_port: 587,
+            smtp_username: "test".to_string(),
+            smtp_password: "test".to_string(),
+            from_address: "noreply@piggy-pulse.com".to_string(),
+            from_name: "PiggyPulse".to_string(),
+            enabled: false,
+        };
+
+        let service = EmailService::new(config);
+        let html = service.generate_alert_html("Jane Smith", "Groceries reached 80% of its limit", "You have spent 80.00 of 100.00.");
+        let text = service.generate_alert_text("Jane Smith", "Groceries reached 80% of its limit", "You have spent 80.00 of 100.00.");
+
+        for body in [html, text] {
+            assert!(body.contains("Jane Smith"));
+            assert!(body.contains("Groceries reached 80% of its limit"));
+            assert!(body.contains("You have spent 80.00 of 100.00."));
+        }
+    }
+
+    #[test]
+    fn test_alert_html_escapes_names() {
+        let config = EmailConfig {
+            smtp_host: "localhost".to_string(),
+            smtp_port: 587,
+            smtp_username: "test".to_string(),
+            smtp_password: "test".to_string(),
+            from_address: "noreply@piggy-pulse.com".to_string(),
+            from_name: "PiggyPulse".to_string(),
+            enabled: false,
+        };
+
+        let service = EmailService::new(config);
+        let html = service.generate_alert_html("Jane <b>", "<script>alert(1)</script> reached 80% of its budget", "Food & \"Drinks\" at 80%");
+
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("&lt;script&gt;alert(1)&lt;/script&gt; reached 80% of its budget"));
+        assert!(html.contains("Hi Jane &lt;b&gt;,"));
+        assert!(html.contains("Food &amp; &quot;Drinks&quot; at 80%"));
+    }
 }